@@ -0,0 +1,60 @@
+use chrono::{TimeZone, Utc};
+use criterion::{criterion_group, criterion_main, Criterion};
+use cron_schedule::Schedule;
+use std::hint::black_box;
+use std::str::FromStr;
+
+// Representative expressions, from densest to sparsest.
+const DENSE_SECONDS: &str = "* * * * * *";
+const BUSINESS_HOURS: &str = "0 */15 9-17 * * Mon-Fri";
+const SPARSE_YEARLY: &str = "@yearly";
+
+const EXPRESSIONS: [(&str, &str); 3] = [
+    ("dense_seconds", DENSE_SECONDS),
+    ("business_hours", BUSINESS_HOURS),
+    ("sparse_yearly", SPARSE_YEARLY),
+];
+
+// A fixed reference instant keeps results reproducible between runs, unlike `upcoming()`
+// which starts from the current time.
+fn reference() -> u64 {
+    Utc.with_ymd_and_hms(2021, 6, 15, 14, 29, 36)
+        .unwrap()
+        .timestamp_nanos_opt()
+        .unwrap() as u64
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, expression) in EXPRESSIONS {
+        group.bench_function(name, |b| {
+            b.iter(|| Schedule::from_str(black_box(expression)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_includes(c: &mut Criterion) {
+    let date_time = Utc.with_ymd_and_hms(2021, 6, 15, 10, 15, 0).unwrap();
+    let mut group = c.benchmark_group("includes");
+    for (name, expression) in EXPRESSIONS {
+        let schedule = Schedule::from_str(expression).unwrap();
+        group.bench_function(name, |b| b.iter(|| schedule.includes(black_box(date_time))));
+    }
+    group.finish();
+}
+
+fn bench_upcoming(c: &mut Criterion) {
+    let after = reference();
+    let mut group = c.benchmark_group("upcoming_take_1000");
+    for (name, expression) in EXPRESSIONS {
+        let schedule = Schedule::from_str(expression).unwrap();
+        group.bench_function(name, |b| {
+            b.iter(|| schedule.after(black_box(&after)).take(1000).count())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_includes, bench_upcoming);
+criterion_main!(benches);
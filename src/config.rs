@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use crate::error::{Error, ErrorKind};
+use crate::ordinal::Ordinal;
+use crate::schedule::Schedule;
+
+/// A schedule described as one entry per field rather than as a single cron expression.
+///
+/// Each field accepts either a single ordinal, a cron substring such as `"9-17"` or `"*/15"`, or a
+/// list of ordinals and names. Omitted fields default to `*`, except for `second`, which defaults
+/// to `0` so that a config only mentioning minutes and hours doesn't fire every second. Omitting
+/// `year` produces a six field expression.
+/// # Example
+/// ```
+/// use cron_schedule::{Schedule, ScheduleConfig};
+/// use std::convert::TryFrom;
+///
+/// let config: ScheduleConfig = serde_json::from_str(
+///     r#"{ "minute": [0, 30], "hour": "9-17", "weekday": ["MON", "FRI"] }"#,
+/// ).unwrap();
+/// let schedule = Schedule::try_from(config).unwrap();
+/// assert_eq!("0 0,30 9-17 * * MON,FRI", schedule.to_string());
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScheduleConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub second: Option<FieldConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minute: Option<FieldConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hour: Option<FieldConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day_of_month: Option<FieldConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub month: Option<FieldConfig>,
+    #[serde(alias = "day_of_week", skip_serializing_if = "Option::is_none")]
+    pub weekday: Option<FieldConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<FieldConfig>,
+}
+
+/// The value of a single field in a [ScheduleConfig](struct.ScheduleConfig.html).
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum FieldConfig {
+    Ordinal(Ordinal),
+    Expression(String),
+    List(Vec<FieldItem>),
+}
+
+/// An element of a [FieldConfig::List](enum.FieldConfig.html#variant.List).
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum FieldItem {
+    Ordinal(Ordinal),
+    Name(String),
+}
+
+impl FieldConfig {
+    fn to_field_string(&self, name: &str) -> Result<String, Error> {
+        let field = match self {
+            FieldConfig::Ordinal(ordinal) => ordinal.to_string(),
+            FieldConfig::Expression(expression) => expression.trim().to_owned(),
+            FieldConfig::List(items) => items
+                .iter()
+                .map(|item| match item {
+                    FieldItem::Ordinal(ordinal) => ordinal.to_string(),
+                    FieldItem::Name(name) => name.trim().to_owned(),
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+        };
+        // A field containing whitespace would be split into several fields once joined.
+        if field.is_empty() || field.contains(char::is_whitespace) {
+            return Err(ErrorKind::Expression(format!(
+                "The '{}' field must be a non-empty value without whitespace. ('{}' specified.)",
+                name, field
+            ))
+            .into());
+        }
+        Ok(field)
+    }
+}
+
+impl ScheduleConfig {
+    /// Renders the config as the equivalent cron expression.
+    pub fn to_expression(&self) -> Result<String, Error> {
+        let field = |value: &Option<FieldConfig>, name: &str, default: &str| match value {
+            Some(value) => value.to_field_string(name),
+            None => Ok(default.to_owned()),
+        };
+        let mut fields = vec![
+            field(&self.second, "second", "0")?,
+            field(&self.minute, "minute", "*")?,
+            field(&self.hour, "hour", "*")?,
+            field(&self.day_of_month, "day_of_month", "*")?,
+            field(&self.month, "month", "*")?,
+            field(&self.weekday, "weekday", "*")?,
+        ];
+        if let Some(year) = &self.year {
+            fields.push(year.to_field_string("year")?);
+        }
+        Ok(fields.join(" "))
+    }
+}
+
+impl TryFrom<ScheduleConfig> for Schedule {
+    type Error = Error;
+    fn try_from(config: ScheduleConfig) -> Result<Self, Self::Error> {
+        Schedule::from_str(&config.to_expression()?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TimeUnitSpec;
+
+    #[test]
+    fn test_deserialize_mixed_field_forms() {
+        let config: ScheduleConfig = serde_json::from_str(
+            r#"{
+                "minute": [0, 30],
+                "hour": "9-17",
+                "weekday": ["MON", "FRI"],
+                "month": 6
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            ScheduleConfig {
+                minute: Some(FieldConfig::List(vec![
+                    FieldItem::Ordinal(0),
+                    FieldItem::Ordinal(30)
+                ])),
+                hour: Some(FieldConfig::Expression("9-17".to_owned())),
+                month: Some(FieldConfig::Ordinal(6)),
+                weekday: Some(FieldConfig::List(vec![
+                    FieldItem::Name("MON".to_owned()),
+                    FieldItem::Name("FRI".to_owned())
+                ])),
+                ..ScheduleConfig::default()
+            }
+        );
+
+        let schedule = Schedule::try_from(config).unwrap();
+        assert_eq!("0 0,30 9-17 * 6 MON,FRI", schedule.to_string());
+        assert_eq!(1, schedule.seconds().count());
+        assert_eq!(2, schedule.minutes().count());
+        assert_eq!(9, schedule.hours().count());
+        assert!(schedule.days_of_week().includes(2));
+        assert!(schedule.days_of_week().includes(6));
+        assert!(schedule.years().is_all());
+    }
+
+    #[test]
+    fn test_config_with_year_and_day_of_week_alias() {
+        let config: ScheduleConfig =
+            serde_json::from_str(r#"{ "second": "*/30", "day_of_week": "Sun", "year": 2030 }"#)
+                .unwrap();
+        let schedule = Schedule::try_from(config).unwrap();
+        assert_eq!("*/30 * * * * Sun 2030", schedule.to_string());
+        assert_eq!(1, schedule.years().count());
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let config: ScheduleConfig = serde_json::from_str(r#"{ "hour": "9 17" }"#).unwrap();
+        assert!(Schedule::try_from(config).is_err());
+
+        let config: ScheduleConfig = serde_json::from_str(r#"{ "minute": [] }"#).unwrap();
+        assert!(Schedule::try_from(config).is_err());
+
+        let config: ScheduleConfig = serde_json::from_str(r#"{ "hour": [25] }"#).unwrap();
+        assert!(Schedule::try_from(config).is_err());
+
+        assert!(serde_json::from_str::<ScheduleConfig>(r#"{ "minutes": [0] }"#).is_err());
+    }
+}
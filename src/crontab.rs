@@ -0,0 +1,214 @@
+use std::io::BufRead;
+
+use crate::error::{Error, ErrorKind};
+use crate::options::ParseOptions;
+use crate::schedule::Schedule;
+
+/// A line of a [Crontab](struct.Crontab.html): a schedule and the command it runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrontabEntry {
+    line_number: usize,
+    schedule: Schedule,
+    command: String,
+}
+
+impl CrontabEntry {
+    /// Returns the number of the line the entry was read from, counting from 1.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    pub fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+}
+
+/// The entries of a crontab file. Each line holds a six field expression or a shorthand such as
+/// `@daily` or `@every 15m`, followed by the command to run. A seventh field is read as the year
+/// when it's made up of digits, `*`, `?`, `,`, `-` and `/` and a command follows it. Blank lines
+/// and lines starting with `#` are skipped.
+/// # Example
+/// ```
+/// use cron_schedule::Crontab;
+/// use std::io::Cursor;
+///
+/// let file = "# Nightly jobs\n0 0 2 * * * backup --all\n@hourly rotate-logs\n";
+/// let crontab = Crontab::from_reader(Cursor::new(file)).unwrap();
+/// assert_eq!(2, crontab.entries().len());
+/// assert_eq!("backup --all", crontab.entries()[0].command());
+/// assert_eq!(3, crontab.entries()[1].line_number());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Crontab {
+    entries: Vec<CrontabEntry>,
+}
+
+impl Crontab {
+    /// Reads a crontab a line at a time from `reader`. The first line that can't be read or
+    /// parsed stops reading, and the error reports its line number, see
+    /// [Error::line](error/struct.Error.html#method.line).
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Crontab, Error> {
+        Crontab::from_reader_with(reader, &ParseOptions::default())
+    }
+
+    /// Like `from_reader`, parsing each schedule with `options`. With
+    /// [ParseOptions::no_seconds](struct.ParseOptions.html#method.no_seconds), schedules have
+    /// five fields, as in Unix crontabs.
+    pub fn from_reader_with<R: BufRead>(
+        reader: R,
+        options: &ParseOptions,
+    ) -> Result<Crontab, Error> {
+        let mut entries = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line_number = index + 1;
+            let line =
+                line.map_err(|error| Error::from(ErrorKind::Io(error)).on_line(line_number))?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = CrontabEntry::parse(line, line_number, options)
+                .map_err(|error| error.on_line(line_number))?;
+            entries.push(entry);
+        }
+        Ok(Crontab { entries })
+    }
+
+    pub fn entries(&self) -> &[CrontabEntry] {
+        &self.entries
+    }
+}
+
+impl CrontabEntry {
+    fn parse(line: &str, line_number: usize, options: &ParseOptions) -> Result<Self, Error> {
+        let mut words = line.split_whitespace();
+        let number_of_fields = match words.next() {
+            // `@every 1h aligned-to :15` spans four words, `@every 1h` two.
+            Some("@every") => match words.nth(1) {
+                Some("aligned-to") => 4,
+                _ => 2,
+            },
+            Some(word) if word.starts_with('@') => 1,
+            _ => {
+                let number_of_fields = if options.no_seconds { 5 } else { 6 };
+                // A year follows the fields when the next word reads as one and a command is
+                // still left after it.
+                let mut rest = words.skip(number_of_fields - 1);
+                match (rest.next(), rest.next()) {
+                    (Some(word), Some(_)) if is_year_field(word) => number_of_fields + 1,
+                    _ => number_of_fields,
+                }
+            }
+        };
+        // The command starts after the last field, keeping its own spacing.
+        let mut rest = line;
+        for _ in 0..number_of_fields {
+            rest = rest.trim_start();
+            rest = &rest[rest.find(char::is_whitespace).unwrap_or(rest.len())..];
+        }
+        let expression = &line[..line.len() - rest.len()];
+        let command = rest.trim();
+        if command.is_empty() {
+            return Err(
+                ErrorKind::Expression(format!("Crontab entry '{}' has no command.", line)).into(),
+            );
+        }
+        Ok(CrontabEntry {
+            line_number,
+            schedule: Schedule::parse_with(expression, options)?,
+            command: command.to_owned(),
+        })
+    }
+}
+
+fn is_year_field(word: &str) -> bool {
+    word.starts_with(|c: char| c.is_ascii_digit() || c == '*' || c == '?')
+        && word
+            .chars()
+            .all(|c| c.is_ascii_digit() || "*?,-/".contains(c))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TimeUnitSpec;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_from_reader() {
+        let file = b"# m h dom mon dow\n\
+                     \n\
+                     0 */15 * * * Mon-Fri  sync  --quiet\n\
+                     @daily backup\n\
+                     \t0 0 9 1 * * report\n";
+        let crontab = Crontab::from_reader(Cursor::new(&file[..])).unwrap();
+        let entries = crontab.entries();
+        assert_eq!(3, entries.len());
+        assert_eq!(3, entries[0].line_number());
+        assert_eq!("0 */15 * * * Mon-Fri", entries[0].schedule().to_string());
+        assert_eq!("sync  --quiet", entries[0].command());
+        assert_eq!("@daily", entries[1].schedule().to_string());
+        assert_eq!("backup", entries[1].command());
+        assert_eq!(5, entries[2].line_number());
+        assert!(entries[2].schedule().days_of_month().includes(1));
+
+        let options = ParseOptions::new().no_seconds(true);
+        let crontab = Crontab::from_reader_with(Cursor::new("*/5 * * * * poll\n"), &options);
+        assert_eq!("poll", crontab.unwrap().entries()[0].command());
+        let crontab = Crontab::from_reader_with(Cursor::new("*/5 * * * * 2030 poll\n"), &options);
+        let crontab = crontab.unwrap();
+        let entry = &crontab.entries()[0];
+        assert_eq!("poll", entry.command());
+        assert!(entry.schedule().years().includes(2030));
+        assert!(Crontab::from_reader(Cursor::new(""))
+            .unwrap()
+            .entries()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_from_reader_every_and_years() {
+        let file = "@every 5m backup\n\
+                    @every 1h aligned-to :15 report --daily\n\
+                    0 0 2 * * * 2025 backup\n\
+                    0 0 2 * * * 2025-2030/5 rotate\n\
+                    0 0 2 * * * 2025\n";
+        let crontab = Crontab::from_reader(Cursor::new(file)).unwrap();
+        let entries = crontab.entries();
+        assert_eq!("@every 5m", entries[0].schedule().to_string());
+        assert_eq!("backup", entries[0].command());
+        assert_eq!(
+            "@every 1h aligned-to :15",
+            entries[1].schedule().to_string()
+        );
+        assert_eq!("report --daily", entries[1].command());
+        assert_eq!("0 0 2 * * * 2025", entries[2].schedule().to_string());
+        assert_eq!("backup", entries[2].command());
+        assert_eq!("rotate", entries[3].command());
+        assert!(entries[3].schedule().years().includes(2030));
+        // Without a command after it, the word is the command.
+        assert_eq!("2025", entries[4].command());
+        assert!(entries[4].schedule().years().includes(2026));
+    }
+
+    #[test]
+    fn test_from_reader_errors() {
+        let error =
+            Crontab::from_reader(Cursor::new("@daily backup\n0 0 25 * * * report\n")).unwrap_err();
+        assert_eq!(Some(2), error.line());
+        assert!(error.is_out_of_range());
+        assert!(error.to_string().contains("line 2"), "{}", error);
+
+        let error = Crontab::from_reader(Cursor::new("0 0 9 * * *\n")).unwrap_err();
+        assert_eq!(Some(1), error.line());
+        assert!(error.is_parse_error());
+
+        let error = Crontab::from_reader(Cursor::new(&b"@daily \xff\n"[..])).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Io(_)));
+        assert_eq!(Some(1), error.line());
+    }
+}
@@ -0,0 +1,163 @@
+use crate::ordinal::Ordinal;
+use crate::time_unit::{TimeUnitSpec, UnitKind};
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const DAYS_OF_WEEK: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// Names the unit of a field in English, such as `day of the month`.
+pub(crate) fn field_name(unit: UnitKind) -> &'static str {
+    match unit {
+        UnitKind::Seconds => "second",
+        UnitKind::Minutes => "minute",
+        UnitKind::Hours => "hour",
+        UnitKind::DaysOfMonth => "day of the month",
+        UnitKind::Months => "month",
+        UnitKind::DaysOfWeek => "day of the week",
+        UnitKind::Years => "year",
+        UnitKind::DaysOfYear => "day of the year",
+        UnitKind::WeeksOfYear => "ISO week",
+    }
+}
+
+/// Describes the values of one field in English, such as `at 9 AM, 12 PM, and 3 PM` for hours
+/// of `9,12,15`. Runs of three or more consecutive values read as a range, `May through August`.
+pub fn describe_field(unit: UnitKind, field: &dyn TimeUnitSpec) -> String {
+    if field.is_all() {
+        return format!("every {}", field_name(unit));
+    }
+    let ordinals: Vec<Ordinal> = field.iter().collect();
+    if ordinals.is_empty() {
+        return String::from("never");
+    }
+    let plural = |singular: &str| {
+        if ordinals.len() == 1 {
+            singular.to_string()
+        } else {
+            format!("{}s", singular)
+        }
+    };
+    match unit {
+        UnitKind::Seconds => format!("at {} {}", plural("second"), list(&ordinals, number)),
+        UnitKind::Minutes => format!("at {} {}", plural("minute"), list(&ordinals, number)),
+        UnitKind::Hours => format!("at {}", list(&ordinals, hour)),
+        UnitKind::DaysOfMonth => format!("on the {}", list(&ordinals, ordinal_number)),
+        UnitKind::Months => format!("in {}", list(&ordinals, month)),
+        UnitKind::DaysOfWeek => format!("on {}", list(&ordinals, day_of_week)),
+        UnitKind::Years => format!("in {}", list(&ordinals, number)),
+        UnitKind::DaysOfYear => format!(
+            "on {} {} of the year",
+            plural("day"),
+            list(&ordinals, number)
+        ),
+        UnitKind::WeeksOfYear => format!("in ISO {} {}", plural("week"), list(&ordinals, number)),
+    }
+}
+
+fn number(ordinal: Ordinal) -> String {
+    ordinal.to_string()
+}
+
+fn hour(hour: Ordinal) -> String {
+    match hour {
+        0 => String::from("12 AM"),
+        1..=11 => format!("{} AM", hour),
+        12 => String::from("12 PM"),
+        _ => format!("{} PM", hour - 12),
+    }
+}
+
+fn month(month: Ordinal) -> String {
+    MONTHS[month as usize - 1].to_string()
+}
+
+fn day_of_week(day: Ordinal) -> String {
+    DAYS_OF_WEEK[day as usize - 1].to_string()
+}
+
+fn ordinal_number(ordinal: Ordinal) -> String {
+    let suffix = match (ordinal % 10, ordinal % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", ordinal, suffix)
+}
+
+// Joins the values as `A`, `A and B` or `A, B, and C`, writing runs of three or more consecutive
+// values as `A through B`.
+fn list<F: Fn(Ordinal) -> String>(ordinals: &[Ordinal], name: F) -> String {
+    let mut parts = Vec::new();
+    let mut ordinals = ordinals.iter().cloned().peekable();
+    while let Some(start) = ordinals.next() {
+        let mut end = start;
+        while ordinals.peek() == Some(&(end + 1)) {
+            end = ordinals.next().unwrap();
+        }
+        match end - start {
+            0 => parts.push(name(start)),
+            1 => parts.extend([name(start), name(end)]),
+            _ => parts.push(format!("{} through {}", name(start), name(end))),
+        }
+    }
+    match parts.as_slice() {
+        [only] => only.clone(),
+        [first, second] => format!("{} and {}", first, second),
+        [rest @ .., last] => format!("{}, and {}", rest.join(", "), last),
+        [] => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_list() {
+        assert_eq!("1", list(&[1], number));
+        assert_eq!("1 and 2", list(&[1, 2], number));
+        assert_eq!("1, 3, and 5", list(&[1, 3, 5], number));
+        assert_eq!("1 through 3 and 7", list(&[1, 2, 3, 7], number));
+        assert_eq!(
+            "1, 2, and 9 through 12",
+            list(&[1, 2, 9, 10, 11, 12], number)
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(
+            vec!["12 AM", "1 AM", "11 AM", "12 PM", "1 PM", "11 PM"],
+            [0, 1, 11, 12, 13, 23].map(hour)
+        );
+        assert_eq!(
+            vec!["1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "22nd", "31st"],
+            [1, 2, 3, 4, 11, 12, 13, 21, 22, 31].map(ordinal_number)
+        );
+        assert_eq!("August", month(8));
+        assert_eq!("Sunday", day_of_week(1));
+    }
+}
@@ -1,27 +1,155 @@
-use std::{error, fmt};
+use std::{error, fmt, io};
 
 #[derive(Debug)]
 pub struct Error {
     kind: ErrorKind,
+    input: Option<String>,
+    line: Option<usize>,
 }
 
+/// The reason an expression was rejected. New kinds may be added, so prefer the predicates on
+/// [Error](struct.Error.html) over matching every variant.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ErrorKind {
+    /// The expression isn't valid cron syntax.
     Expression(String),
+    /// A field holds a value outside the range its unit allows, such as hour 25.
+    OutOfRange(String),
+    /// Reading the expressions failed, see
+    /// [Crontab::from_reader](../struct.Crontab.html#method.from_reader).
+    Io(io::Error),
+}
+
+impl Error {
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Returns the expression that failed to parse, if the error came from parsing one.
+    pub fn input(&self) -> Option<&str> {
+        self.input.as_deref()
+    }
+
+    /// Returns the line of a crontab the error was found on, counting from 1.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    pub(crate) fn on_line(self, line: usize) -> Error {
+        Error {
+            line: Some(line),
+            ..self
+        }
+    }
+
+    pub(crate) fn with_input(self, input: &str) -> Error {
+        Error {
+            input: Some(input.to_owned()),
+            ..self
+        }
+    }
+
+    /// Returns true if the expression couldn't be parsed as cron syntax.
+    pub fn is_parse_error(&self) -> bool {
+        matches!(self.kind, ErrorKind::Expression(_))
+    }
+
+    /// Returns true if the expression was well formed but a value in it was out of range for its
+    /// field.
+    pub fn is_out_of_range(&self) -> bool {
+        matches!(self.kind, ErrorKind::OutOfRange(_))
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.kind {
-            ErrorKind::Expression(ref expr) => write!(f, "Invalid expression: {}", expr),
+        let reason = match self.kind {
+            ErrorKind::Expression(ref expr) => expr,
+            ErrorKind::OutOfRange(ref expr) => expr,
+            ErrorKind::Io(ref error) => {
+                return match self.line {
+                    Some(line) => write!(f, "Failed to read line {}: {}", line, error),
+                    None => write!(f, "Failed to read: {}", error),
+                }
+            }
+        };
+        match (&self.input, self.line) {
+            (Some(input), Some(line)) => {
+                write!(
+                    f,
+                    "Invalid expression {:?} on line {}: {}",
+                    input, line, reason
+                )
+            }
+            (Some(input), None) => write!(f, "Invalid expression {:?}: {}", input, reason),
+            (None, Some(line)) => write!(f, "Invalid expression on line {}: {}", line, reason),
+            (None, None) => write!(f, "Invalid expression: {}", reason),
         }
     }
 }
 
-impl error::Error for Error {}
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.kind {
+            ErrorKind::Io(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
 
 impl From<ErrorKind> for Error {
     fn from(kind: ErrorKind) -> Error {
-        Error { kind }
+        Error {
+            kind,
+            input: None,
+            line: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Schedule, Years};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_error_predicates() {
+        for expression in [
+            "* * * *",
+            "0 0 12 * * Someday",
+            "@fortnightly",
+            "0 0 12 * * * */0",
+        ] {
+            let error = Schedule::from_str(expression).unwrap_err();
+            assert!(error.is_parse_error(), "{}", expression);
+            assert!(!error.is_out_of_range(), "{}", expression);
+        }
+        for expression in [
+            "0 0 25 * * *",
+            "61 * * * * *",
+            "0 0 0 0 * *",
+            "0 0 0 1 1 * 1969",
+        ] {
+            let error = Schedule::from_str(expression).unwrap_err();
+            assert!(error.is_out_of_range(), "{}", expression);
+            assert!(!error.is_parse_error(), "{}", expression);
+        }
+        assert!(Years::from_range(2000, 3000).unwrap_err().is_out_of_range());
+        assert!(Years::from_range(2010, 2000).unwrap_err().is_parse_error());
+    }
+
+    #[test]
+    fn test_error_input() {
+        let error = Schedule::from_str("* * * *").unwrap_err();
+        assert_eq!(Some("* * * *"), error.input());
+        assert_eq!(
+            "Invalid expression \"* * * *\": Expression has 4 fields. Valid cron expressions have \
+             6 or 7.",
+            error.to_string()
+        );
+        let error = Years::from_range(2010, 2000).unwrap_err();
+        assert_eq!(None, error.input());
+        assert!(error.to_string().starts_with("Invalid expression: "));
     }
 }
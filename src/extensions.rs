@@ -0,0 +1,102 @@
+/// The parts of a schedule that plain Unix cron can't express, as reported by
+/// [Schedule::extensions](struct.Schedule.html#method.extensions). Exporters can check these to
+/// warn about or reject schedules they can't write out faithfully.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[non_exhaustive]
+pub struct ExtensionFlags {
+    /// The schedule fires at seconds other than the start of the minute.
+    pub seconds: bool,
+    /// The years field leaves out some years.
+    pub years: bool,
+    /// The days of year field leaves out some days. See
+    /// [ParseOptions::allow_day_of_year](struct.ParseOptions.html#method.allow_day_of_year).
+    pub days_of_year: bool,
+    /// The ISO weeks field leaves out some weeks. See
+    /// [ParseOptions::allow_iso_week](struct.ParseOptions.html#method.allow_iso_week).
+    pub weeks_of_year: bool,
+    /// The expression used `?` for a field.
+    pub any: bool,
+    /// The expression used `L` for Saturday in the days of the week.
+    pub last_day_of_week: bool,
+}
+
+impl ExtensionFlags {
+    /// Returns true if none of the extensions are used.
+    pub fn is_empty(&self) -> bool {
+        *self == ExtensionFlags::default()
+    }
+}
+
+/// The syntax and integrations this build of the crate supports, as returned by
+/// [supported_features](fn.supported_features.html). Syntax behind a
+/// [ParseOptions](struct.ParseOptions.html) flag is available in every build, while the
+/// integrations depend on the cargo features enabled. Syntax the crate doesn't support at all,
+/// such as `W` for the nearest weekday or `#` for the nth weekday, has no flag yet.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct FeatureSet {
+    /// A leading seconds field, and `ParseOptions::no_seconds` to leave it out.
+    pub seconds: bool,
+    /// An optional trailing years field.
+    pub years: bool,
+    /// `?` for the days of the month and of the week.
+    pub any: bool,
+    /// `L` for Saturday in the days of the week.
+    pub last_day_of_week: bool,
+    /// Shorthands such as `@daily` and `@every 15m`.
+    pub shorthands: bool,
+    /// `R(start-end)` with `ParseOptions::random_seed`.
+    pub random: bool,
+    /// The days of year field with `ParseOptions::allow_day_of_year`.
+    pub days_of_year: bool,
+    /// The ISO weeks field with `ParseOptions::allow_iso_week`.
+    pub iso_weeks: bool,
+    /// `WEEKDAY` and `WEEKEND` with `ParseOptions::day_groups`.
+    pub day_groups: bool,
+    /// Serialization, with the `serde` cargo feature.
+    pub serde: bool,
+    /// Logging fire times, with the `log` cargo feature.
+    pub log: bool,
+    /// Jittered fire times, with the `rand` cargo feature.
+    pub rand: bool,
+}
+
+/// Returns the syntax and integrations this build supports, for tools that offer hints about
+/// the syntax.
+/// # Example
+/// ```
+/// let features = cron_schedule::supported_features();
+/// assert!(features.seconds && features.years);
+/// ```
+pub fn supported_features() -> FeatureSet {
+    FeatureSet {
+        seconds: true,
+        years: true,
+        any: true,
+        last_day_of_week: true,
+        shorthands: true,
+        random: true,
+        days_of_year: true,
+        iso_weeks: true,
+        day_groups: true,
+        serde: cfg!(feature = "serde"),
+        log: cfg!(feature = "log"),
+        rand: cfg!(feature = "rand"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_supported_features() {
+        let features = supported_features();
+        assert!(features.seconds && features.years && features.any && features.shorthands);
+        assert!(features.random && features.days_of_year && features.iso_weeks);
+        assert_eq!(cfg!(feature = "serde"), features.serde);
+        assert_eq!(cfg!(feature = "log"), features.log);
+        assert_eq!(cfg!(feature = "rand"), features.rand);
+    }
+}
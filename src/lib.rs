@@ -1,6 +1,3 @@
-#![cfg_attr(feature = "clippy", feature(plugin))]
-#![cfg_attr(feature = "clippy", plugin(clippy))]
-
 //! A cron expression parser and schedule explorer
 //! # Example
 //! ```
@@ -35,13 +32,42 @@
 //! -> 2018-08-15 09:30:00 UTC
 //! */
 //! ```
+#[cfg(feature = "serde")]
+mod config;
+mod crontab;
+mod describe;
 pub mod error;
+mod extensions;
+mod options;
 mod ordinal;
 mod parsing;
 mod queries;
 mod schedule;
+mod schedule_set;
+#[cfg(feature = "serde")]
+mod serialization;
 mod specifier;
 mod time_unit;
+pub mod warning;
 
-pub use crate::schedule::Schedule;
-pub use crate::time_unit::TimeUnitSpec;
+#[cfg(feature = "serde")]
+pub use crate::config::{FieldConfig, FieldItem, ScheduleConfig};
+pub use crate::crontab::{Crontab, CrontabEntry};
+pub use crate::extensions::{supported_features, ExtensionFlags, FeatureSet};
+pub use crate::options::ParseOptions;
+#[cfg(feature = "log")]
+pub use crate::schedule::InspectLogIterator;
+pub use crate::schedule::{
+    dedupe, CandidatesScannedIterator, DateTimesIterator, DedupByIterator, Dialect, FrequencyClass,
+    Granularity, GroupedByDayIterator, IntervalStats, ReverseScheduleIterator, Schedule,
+};
+pub use crate::schedule_set::{
+    PrioritizedSchedules, PrioritizedSchedulesIterator, ScheduleIntersection,
+    ScheduleIntersectionIterator, ScheduleSet, ScheduleSetIterator,
+};
+#[cfg(feature = "serde")]
+pub use crate::serialization::CompiledSchedule;
+pub use crate::time_unit::{
+    DaysOfMonth, DaysOfWeek, DaysOfYear, Hours, Minutes, Months, Seconds, TimeUnitField,
+    TimeUnitSpec, UnitKind, WeeksOfYear, Years, MAX_YEAR,
+};
@@ -0,0 +1,172 @@
+use chrono::Duration;
+use std::collections::HashMap;
+
+use crate::time_unit::Years;
+
+/// Settings for [Schedule::parse_with](struct.Schedule.html#method.parse_with). The default
+/// options parse exactly what `Schedule::from_str` does.
+/// # Example
+/// ```
+/// use cron_schedule::{ParseOptions, Schedule, TimeUnitSpec};
+///
+/// let options = ParseOptions::new().day_groups(true).strip_comments(true);
+/// let schedule = Schedule::parse_with("0 0 9 * * WEEKDAY # standup", &options).unwrap();
+/// assert_eq!(5, schedule.days_of_week().count());
+/// assert_eq!("0 0 9 * * WEEKDAY", schedule.to_string());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub(crate) day_groups: bool,
+    pub(crate) strip_comments: bool,
+    pub(crate) max_length: Option<usize>,
+    pub(crate) no_seconds: bool,
+    pub(crate) allow_day_of_year: bool,
+    pub(crate) quartz_lenient: bool,
+    pub(crate) random_seed: Option<u64>,
+    pub(crate) default_years: Option<Years>,
+    pub(crate) allow_custom_directives: bool,
+    pub(crate) allow_iso_week: bool,
+    pub(crate) iso_days_of_week: bool,
+    pub(crate) min_interval: Option<Duration>,
+    pub(crate) require_restriction: bool,
+    pub(crate) aliases: HashMap<String, String>,
+    pub(crate) reject_redundant_lists: bool,
+}
+
+impl ParseOptions {
+    pub fn new() -> ParseOptions {
+        ParseOptions::default()
+    }
+
+    /// Accepts `WEEKDAY` for `MON-FRI` and `WEEKEND` for `SAT,SUN` in the day of week field.
+    pub fn day_groups(mut self, day_groups: bool) -> ParseOptions {
+        self.day_groups = day_groups;
+        self
+    }
+
+    /// Ignores everything from a `#` to the end of the expression, as in
+    /// `0 0 2 * * * # nightly backup`. The schedule's source won't include the comment.
+    pub fn strip_comments(mut self, strip_comments: bool) -> ParseOptions {
+        self.strip_comments = strip_comments;
+        self
+    }
+
+    /// Parses Unix style expressions without a seconds field, so the fields are minutes, hours,
+    /// days of the month, months, days of the week and optionally years. Schedules fire at the
+    /// start of each matching minute. Shorthands such as `@hourly` are unaffected.
+    pub fn no_seconds(mut self, no_seconds: bool) -> ParseOptions {
+        self.no_seconds = no_seconds;
+        self
+    }
+
+    /// Accepts an eighth field after the years listing days of the year, from 1 through 366. A
+    /// date must match it as well as the other date fields, so `0 0 0 * * * * 60` fires on
+    /// March 1st in common years and on February 29th in leap years. This isn't standard cron.
+    pub fn allow_day_of_year(mut self, allow_day_of_year: bool) -> ParseOptions {
+        self.allow_day_of_year = allow_day_of_year;
+        self
+    }
+
+    /// Accepts `?` in every field rather than only the days of the month and week, meaning the
+    /// same as `*`. Quartz forbids this, but some exported Quartz expressions use it anyway.
+    pub fn quartz_lenient(mut self, quartz_lenient: bool) -> ParseOptions {
+        self.quartz_lenient = quartz_lenient;
+        self
+    }
+
+    /// Accepts `R(start-end)` in any field, replacing it by a value from `start` through `end`
+    /// drawn from a generator seeded with `seed`. See
+    /// [Schedule::from_str_with_seed](struct.Schedule.html#method.from_str_with_seed).
+    pub fn random_seed(mut self, seed: u64) -> ParseOptions {
+        self.random_seed = Some(seed);
+        self
+    }
+
+    /// Uses `years` instead of 1970 through 2100 when the expression doesn't restrict the years,
+    /// either because it has no years field or because the field is `*`. Narrowing the span
+    /// bounds how long iterating over a sparse schedule, such as `0 0 0 29 2 Mon`, can take. The
+    /// schedule's source is left as written.
+    pub fn default_years(mut self, years: Years) -> ParseOptions {
+        self.default_years = Some(years);
+        self
+    }
+
+    /// Accepts any `@word` besides the shorthands, such as `@startup`, as a custom directive for
+    /// the caller to handle. The resulting schedule never fires, see
+    /// [Schedule::custom_directive](struct.Schedule.html#method.custom_directive).
+    pub fn allow_custom_directives(mut self, allow_custom_directives: bool) -> ParseOptions {
+        self.allow_custom_directives = allow_custom_directives;
+        self
+    }
+
+    /// Accepts a last field of ISO 8601 week numbers, from 1 through 53, set apart by a leading
+    /// `W`. A date must be in one of the weeks as well as match the other date fields, so
+    /// `0 0 9 * * Mon W*/2` fires on the Monday of every odd numbered week. The week number is
+    /// that of the ISO week-numbering year, so January 1st can be in week 52 or 53. Years
+    /// without a week 53 have no days matching it.
+    pub fn allow_iso_week(mut self, allow_iso_week: bool) -> ParseOptions {
+        self.allow_iso_week = allow_iso_week;
+        self
+    }
+
+    /// Numbers the days of the week as in ISO 8601, from Monday 1 through Sunday 7, instead of
+    /// from Sunday 1 through Saturday 7. A step over `*` starts on Monday, and a bare `L`, the
+    /// last day of the week, is Sunday. Names such as `Mon` are unaffected, and expressions
+    /// written out again, such as by
+    /// [Schedule::with_hours](struct.Schedule.html#method.with_hours), use the usual numbering.
+    pub fn iso_days_of_week(mut self, iso_days_of_week: bool) -> ParseOptions {
+        self.iso_days_of_week = iso_days_of_week;
+        self
+    }
+
+    /// Rejects schedules that can fire twice within less than `min_interval`, such as schedules
+    /// firing every second when the minimum is a minute. The gaps are those of
+    /// [Schedule::shortest_interval](struct.Schedule.html#method.shortest_interval), so schedules
+    /// firing at most once always pass.
+    pub fn min_interval(mut self, min_interval: Duration) -> ParseOptions {
+        self.min_interval = Some(min_interval);
+        self
+    }
+
+    /// Rejects schedules in which every field is `*`, which fire every second. Expressions built
+    /// by a program rather than written by hand are more likely to leave every field out by
+    /// mistake.
+    pub fn require_restriction(mut self, require_restriction: bool) -> ParseOptions {
+        self.require_restriction = require_restriction;
+        self
+    }
+
+    /// Rejects fields written out in full instead of as `*`, such as seconds of `0-59` or of
+    /// every second from 0 through 59, as a lint for crontabs kept clean. `?` is accepted wherever
+    /// the expression allows it, as are shorthands such as `@daily`.
+    pub fn reject_redundant_lists(mut self, reject_redundant_lists: bool) -> ParseOptions {
+        self.reject_redundant_lists = reject_redundant_lists;
+        self
+    }
+
+    /// Accepts `@name` for each `name` in `aliases`, parsing the expression it maps to instead,
+    /// with the same options. Aliases are looked up before the shorthands, so an alias named
+    /// `daily` replaces `@daily`, and the expressions they map to can't use other aliases. The
+    /// schedule's source is the expression the alias maps to.
+    /// # Example
+    /// ```
+    /// use cron_schedule::{ParseOptions, Schedule};
+    /// use std::collections::HashMap;
+    ///
+    /// let aliases = HashMap::from([("business".to_owned(), "0 0 9-17 * * Mon-Fri".to_owned())]);
+    /// let options = ParseOptions::new().aliases(aliases);
+    /// let schedule = Schedule::parse_with("@business", &options).unwrap();
+    /// assert_eq!("0 0 9-17 * * Mon-Fri", schedule.to_string());
+    /// ```
+    pub fn aliases(mut self, aliases: HashMap<String, String>) -> ParseOptions {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Rejects expressions longer than `max_length` bytes before parsing them, for expressions
+    /// coming from untrusted input.
+    pub fn max_length(mut self, max_length: usize) -> ParseOptions {
+        self.max_length = Some(max_length);
+        self
+    }
+}
@@ -7,14 +7,20 @@ use nom::multi::separated_list1;
 use nom::sequence::tuple;
 use nom::IResult;
 
+use chrono::Duration;
+use std::borrow::Cow;
 use std::iter::Iterator;
 use std::str::{self, FromStr};
 
+use crate::describe::field_name;
 use crate::error::{Error, ErrorKind};
+use crate::extensions::ExtensionFlags;
+use crate::options::ParseOptions;
 use crate::ordinal::*;
-use crate::schedule::{Schedule, ScheduleFields};
+use crate::schedule::{Dialect, Schedule, ScheduleFields};
 use crate::specifier::*;
 use crate::time_unit::*;
+use crate::warning::Warning;
 
 /// A combinator that takes a parser `inner` and produces a parser that also consumes both leading and
 /// trailing whitespace, returning the output of `inner`.
@@ -30,11 +36,594 @@ where
 impl FromStr for Schedule {
     type Err = Error;
     fn from_str(expression: &str) -> Result<Self, Self::Err> {
-        match schedule(expression) {
-            Ok((_, schedule_fields)) => {
-                Ok(Schedule::new(String::from(expression), schedule_fields))
-            } // Extract from nom tuple
-            Err(_) => Err(ErrorKind::Expression("Invalid cron expression.".to_owned()).into()), //TODO: Details
+        Schedule::parse_with(expression, &ParseOptions::default())
+    }
+}
+
+fn parse_fields(expression: &str) -> Result<ScheduleFields, Error> {
+    match schedule(expression) {
+        Ok((_, schedule_fields)) => Ok(schedule_fields), // Extract from nom tuple
+        Err(_) => rejected_fields(expression),
+    }
+}
+
+// Reports why nom rejected an expression. When the fields are well formed, nom has discarded the
+// reason one of them was rejected, so they're interpreted again to find it.
+fn rejected_fields(expression: &str) -> Result<ScheduleFields, Error> {
+    if let Ok((_, fields)) = longhand_fields(expression) {
+        ScheduleFields::from_field_list(fields)?;
+    }
+    // Runs of whitespace separate fields the same as a single space, so a field that is missing
+    // outright shows up in the number of words.
+    let number_of_fields = expression.split_whitespace().count();
+    if !expression.trim_start().starts_with('@') && !(6..=8).contains(&number_of_fields) {
+        return Err(field_count_error(number_of_fields));
+    }
+    Err(ErrorKind::Expression("Invalid cron expression.".to_owned()).into()) //TODO: Details
+}
+
+// Like `parse_fields`, but first replaces each `R(start-end)` with a random point of its range,
+// drawn from a generator seeded with `seed`. Also returns whether there were any to replace.
+fn parse_fields_with_seed(expression: &str, seed: u64) -> Result<(ScheduleFields, bool), Error> {
+    let mut fields = match longhand_fields(expression) {
+        Ok((_, fields)) => fields,
+        Err(_) => return Ok((parse_fields(expression)?, false)),
+    };
+    let mut state = seed;
+    let mut resolved_random = false;
+    for (index, field) in fields.iter_mut().enumerate() {
+        resolved_random |= match index {
+            0 => resolve_random::<Seconds>(field, &mut state)?,
+            1 => resolve_random::<Minutes>(field, &mut state)?,
+            2 => resolve_random::<Hours>(field, &mut state)?,
+            3 => resolve_random::<DaysOfMonth>(field, &mut state)?,
+            4 => resolve_random::<Months>(field, &mut state)?,
+            5 => resolve_random::<DaysOfWeek>(field, &mut state)?,
+            6 => resolve_random::<Years>(field, &mut state)?,
+            _ => resolve_random::<DaysOfYear>(field, &mut state)?,
+        };
+    }
+    Ok((ScheduleFields::from_field_list(fields)?, resolved_random))
+}
+
+// The whole range is validated before drawing from it, so whether an expression parses doesn't
+// depend on the seed.
+fn resolve_random<T: TimeUnitField>(field: &mut Field, state: &mut u64) -> Result<bool, Error> {
+    let mut resolved_random = false;
+    for root_specifier in field.specifiers.iter_mut() {
+        let specifier = match root_specifier {
+            RootSpecifier::Specifier(specifier) | RootSpecifier::Period(specifier, _) => specifier,
+            RootSpecifier::NamedPoint(_) => continue,
+        };
+        if let Specifier::Random(start, end) = *specifier {
+            let start = T::validate_ordinal(start)?;
+            let end = T::validate_ordinal(end)?;
+            if start > end {
+                return Err(ErrorKind::Expression(format!(
+                    "Invalid random range for {}: R({}-{})",
+                    T::name(),
+                    start,
+                    end
+                ))
+                .into());
+            }
+            let offset = split_mix64(state) % (u64::from(end - start) + 1);
+            *specifier = Specifier::Point(start + offset as Ordinal);
+            resolved_random = true;
+        }
+    }
+    Ok(resolved_random)
+}
+
+// SplitMix64, a small generator that is plenty for spreading out fire times. It isn't suitable
+// for anything that needs to be unpredictable.
+fn split_mix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// Returns the expression if it's a single `@word` other than one of the shorthands.
+fn custom_directive(expression: &str) -> Option<&str> {
+    let directive = expression.trim();
+    let name = directive.strip_prefix('@')?;
+    let is_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    (is_name && shorthand(directive).is_err()).then_some(directive)
+}
+
+// Reads an interval such as `15m` as a number of seconds. The units are `s`, `m` and `h`.
+fn every_duration(text: &str) -> Option<u32> {
+    let unit = match text.chars().last()? {
+        's' => 1,
+        'm' => 60,
+        'h' => 3_600,
+        _ => return None,
+    };
+    let count: u32 = text[..text.len() - 1].parse().ok()?;
+    count.checked_mul(unit)
+}
+
+// Expands `@every <interval>`, optionally followed by `aligned-to <offset>`, into the cron
+// expression firing at the same times. The offset is an interval or `:MM` for minutes past the
+// hour, and fire times are placed `offset` after each multiple of the interval since midnight.
+// Only intervals dividing a minute, an hour or a day evenly repeat the same way every day, so
+// those are the only ones cron fields can express. Returns `None` if the expression isn't an
+// `@every` at all.
+fn every_expression(expression: &str) -> Option<Result<String, Error>> {
+    let mut words = expression.split_whitespace();
+    if words.next() != Some("@every") {
+        return None;
+    }
+    let words: Vec<&str> = words.collect();
+    let invalid = || ErrorKind::Expression(format!("Invalid interval: {}", expression)).into();
+    let (interval, offset) = match words[..] {
+        [interval] => (every_duration(interval), Some(0)),
+        [interval, "aligned-to", offset] => {
+            let offset = match offset.strip_prefix(':') {
+                Some(minutes) => minutes
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|minutes| *minutes < 60)
+                    .map(|minutes| minutes * 60),
+                None => every_duration(offset),
+            };
+            (every_duration(interval), offset)
+        }
+        _ => return Some(Err(invalid())),
+    };
+    let (interval, offset) = match (interval, offset) {
+        (Some(interval), Some(offset)) if interval > 0 => (interval, offset),
+        _ => return Some(Err(invalid())),
+    };
+    if offset >= interval {
+        return Some(Err(ErrorKind::Expression(format!(
+            "The offset of {} must be shorter than the interval.",
+            expression
+        ))
+        .into()));
+    }
+    let (seconds, minutes, hours) = if interval < 60 && 60 % interval == 0 {
+        let seconds = format!("{}/{}", offset, interval);
+        (seconds, "*".to_owned(), "*".to_owned())
+    } else if interval < 3_600 && interval % 60 == 0 && 3_600 % interval == 0 {
+        let minutes = format!("{}/{}", offset / 60, interval / 60);
+        ((offset % 60).to_string(), minutes, "*".to_owned())
+    } else if interval <= 86_400 && interval % 3_600 == 0 && 86_400 % interval == 0 {
+        let hours = format!("{}/{}", offset / 3_600, interval / 3_600);
+        (
+            (offset % 60).to_string(),
+            (offset / 60 % 60).to_string(),
+            hours,
+        )
+    } else {
+        return Some(Err(ErrorKind::Expression(format!(
+            "The interval of {} doesn't divide a minute, an hour or a day evenly.",
+            expression
+        ))
+        .into()));
+    };
+    Some(Ok(format!("{} {} {} * * *", seconds, minutes, hours)))
+}
+
+// Finds the extensions which only show in how a longhand expression is written.
+fn syntax_extensions(expression: &str) -> ExtensionFlags {
+    let mut extensions = ExtensionFlags::default();
+    if expression.trim_start().starts_with('@') {
+        return extensions;
+    }
+    extensions.any = expression.contains('?');
+    extensions.last_day_of_week = expression.split_whitespace().nth(5).is_some_and(|field| {
+        field
+            .split(',')
+            .any(|element| element.eq_ignore_ascii_case("l"))
+    });
+    extensions
+}
+
+// Replaces day group names in the day of week field of a longhand expression with the days they
+// stand for. Groups are only replaced when they make up a whole list element.
+fn expand_day_groups(expression: &str) -> String {
+    let mut fields: Vec<String> = expression.split_whitespace().map(str::to_owned).collect();
+    if !(6..=8).contains(&fields.len()) {
+        return expression.to_owned();
+    }
+    fields[5] = fields[5]
+        .split(',')
+        .map(|element| {
+            DAY_GROUPS
+                .iter()
+                .find(|(name, _)| element.eq_ignore_ascii_case(name))
+                .map_or(element, |(_, days)| days)
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    fields.join(" ")
+}
+
+// Takes a last field of ISO weeks, a `W` followed by a field such as `W2/2`, off the end of the
+// expression. Days of the week such as `Wed` are left alone.
+fn split_weeks_of_year(expression: &str) -> Result<(&str, Option<WeeksOfYear>), Error> {
+    let expression = expression.trim_end();
+    let (rest, last) = match expression.rsplit_once(char::is_whitespace) {
+        Some(split) => split,
+        None => return Ok((expression, None)),
+    };
+    let weeks = match last.strip_prefix(['W', 'w']) {
+        Some(weeks) if weeks.starts_with(|c: char| c.is_ascii_digit() || c == '*') => weeks,
+        _ => return Ok((expression, None)),
+    };
+    match complete(field)(weeks) {
+        Ok(("", field)) => Ok((rest, Some(WeeksOfYear::from_field(field)?))),
+        _ => Err(ErrorKind::Expression(format!("Invalid ISO weeks field: {}", last)).into()),
+    }
+}
+
+// Renumbers the days of the week of a longhand expression from ISO 8601, Monday 1 through Sunday
+// 7, to Sunday 1 through Saturday 7. ISO weeks start on Monday, so a step over `*` does too, and
+// a step from a single day runs through Sunday, as does a bare `L`, the last day of the ISO week.
+// Other names and numbers outside 1 through 7 are left for the parser to accept or reject.
+fn iso_days_of_week(expression: &str) -> String {
+    let mut fields: Vec<String> = expression.split_whitespace().map(str::to_owned).collect();
+    if fields.len() < 6 {
+        return expression.to_owned();
+    }
+    let renumber = |day: &str| match day.parse::<Ordinal>() {
+        Ok(day @ 1..=7) => (day % 7 + 1).to_string(),
+        _ => day.to_owned(),
+    };
+    fields[5] = fields[5]
+        .split(',')
+        .map(|element| {
+            if element.eq_ignore_ascii_case("l") {
+                return "Sun".to_owned();
+            }
+            let (days, step) = match element.split_once('/') {
+                Some(("*", step)) => (Cow::Borrowed("1-7"), Some(step)),
+                Some((day, step)) if !day.contains('-') => {
+                    let sunday = if day.parse::<Ordinal>().is_ok() {
+                        "7"
+                    } else {
+                        "Sun"
+                    };
+                    (Cow::Owned(format!("{}-{}", day, sunday)), Some(step))
+                }
+                Some((days, step)) => (Cow::Borrowed(days), Some(step)),
+                None => (Cow::Borrowed(element), None),
+            };
+            let days = match days.split_once('-') {
+                Some((start, end)) => format!("{}-{}", renumber(start), renumber(end)),
+                None => renumber(&days),
+            };
+            match step {
+                Some(step) => format!("{}/{}", days, step),
+                None => days,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    fields.join(" ")
+}
+
+// Rejects expressions longer than the limit. The expression isn't kept in the error, as it may
+// be arbitrarily long.
+fn check_length(expression: &str, options: &ParseOptions) -> Result<(), Error> {
+    match options.max_length {
+        Some(max_length) if expression.len() > max_length => Err(ErrorKind::Expression(format!(
+            "Expression is {} bytes long, the limit is {}.",
+            expression.len(),
+            max_length
+        ))
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+fn field_count_error(number_of_fields: usize) -> Error {
+    ErrorKind::Expression(format!(
+        "Expression has {} fields. Valid cron \
+         expressions have 6 or 7.",
+        number_of_fields
+    ))
+    .into()
+}
+
+impl Schedule {
+    /// Parses an expression like `from_str`, additionally reporting likely mistakes in it.
+    /// # Example
+    /// ```
+    /// use cron_schedule::warning::Warning;
+    /// use cron_schedule::Schedule;
+    ///
+    /// let (_, warnings) = Schedule::from_str_with_warnings("* * * * * *").unwrap();
+    /// assert_eq!(vec![Warning::EverySecond], warnings);
+    /// ```
+    pub fn from_str_with_warnings(expression: &str) -> Result<(Schedule, Vec<Warning>), Error> {
+        let schedule = Schedule::from_str(expression)?;
+        let warnings = schedule.warnings();
+        Ok((schedule, warnings))
+    }
+
+    /// Parses an expression with the given options. `from_str` is the same as parsing with the
+    /// default options. Errors carry the expression, see
+    /// [Error::input](error/struct.Error.html#method.input), unless it was rejected for being
+    /// longer than [ParseOptions::max_length](struct.ParseOptions.html#method.max_length).
+    pub fn parse_with(expression: &str, options: &ParseOptions) -> Result<Schedule, Error> {
+        check_length(expression, options)?;
+        Schedule::parse_expression(expression, options)
+            .and_then(|schedule| schedule.check_restriction(options))
+            .and_then(|schedule| schedule.check_min_interval(options))
+            .and_then(|schedule| schedule.check_redundant_lists(options))
+            .map_err(|error| error.with_input(expression))
+    }
+
+    fn check_restriction(self, options: &ParseOptions) -> Result<Schedule, Error> {
+        if options.require_restriction && self.fields().all(|(_, field)| field.is_all()) {
+            return Err(ErrorKind::Expression(
+                "Every field is `*`, so the schedule fires every second.".to_owned(),
+            )
+            .into());
+        }
+        Ok(self)
+    }
+
+    fn check_min_interval(self, options: &ParseOptions) -> Result<Schedule, Error> {
+        let min_interval = match options.min_interval {
+            Some(min_interval) => min_interval,
+            None => return Ok(self),
+        };
+        match self.shortest_interval() {
+            Some(shortest) if shortest < min_interval => Err(ErrorKind::Expression(format!(
+                "The schedule can fire twice within {}s, the minimum interval is {}s.",
+                shortest.num_seconds(),
+                min_interval.num_seconds()
+            ))
+            .into()),
+            _ => Ok(self),
+        }
+    }
+
+    // Rejects fields written out in full, such as minutes of `0-59`, rather than as `*`. The
+    // fields are matched up with the words of the source, which Unix expressions write without
+    // the seconds and which end with the ISO weeks if there are any.
+    fn check_redundant_lists(self, options: &ParseOptions) -> Result<Schedule, Error> {
+        let source = self.to_string();
+        if !options.reject_redundant_lists || source.trim_start().starts_with('@') {
+            return Ok(self);
+        }
+        let mut words: Vec<&str> = source.split_whitespace().collect();
+        if self.dialect() == Dialect::Unix {
+            words.insert(0, "0");
+        }
+        let mut fields: Vec<(UnitKind, &dyn TimeUnitSpec)> = self.fields().collect();
+        if let Some(&(UnitKind::WeeksOfYear, weeks)) = fields.last() {
+            let word = words.pop().unwrap_or_default();
+            fields.pop();
+            // Without a years field, the weeks come right after the days of the week.
+            fields.retain(|(unit, _)| *unit != UnitKind::Years || words.len() > 6);
+            fields.push((UnitKind::WeeksOfYear, weeks));
+            words.push(word.trim_start_matches(['W', 'w']));
+        }
+        for ((unit, field), word) in fields.into_iter().zip(words) {
+            if field.is_all() && word != "*" && word != "?" {
+                return Err(ErrorKind::Expression(format!(
+                    "The {} field `{}` matches every value, write `*` instead.",
+                    field_name(unit),
+                    word
+                ))
+                .into());
+            }
+        }
+        Ok(self)
+    }
+
+    fn parse_expression(expression: &str, options: &ParseOptions) -> Result<Schedule, Error> {
+        let expression = match expression.split_once('#') {
+            Some((expression, _comment)) if options.strip_comments => expression.trim_end(),
+            _ => expression,
+        };
+        let alias = expression.trim().strip_prefix('@');
+        if let Some(aliased) = alias.and_then(|name| options.aliases.get(name)) {
+            let options = ParseOptions {
+                aliases: Default::default(),
+                ..options.clone()
+            };
+            return Schedule::parse_expression(aliased, &options);
+        }
+        // An interval is expanded into the fields it stands for, which are already longhand with
+        // seconds, and then parsed like any other expression.
+        let every = every_expression(expression).transpose()?;
+        if options.allow_custom_directives && every.is_none() {
+            if let Some(directive) = custom_directive(expression) {
+                return Ok(Schedule::new(
+                    directive.to_owned(),
+                    ScheduleFields::custom_directive(),
+                ));
+            }
+        }
+        let (fields_part, weeks_of_year) = match &every {
+            Some(every) => (every.as_str(), None),
+            None if options.allow_iso_week => split_weeks_of_year(expression)?,
+            None => (expression, None),
+        };
+        let mut fields_expression = Cow::Borrowed(fields_part);
+        let unix =
+            options.no_seconds && every.is_none() && !fields_part.trim_start().starts_with('@');
+        if unix {
+            let number_of_fields = fields_part.split_whitespace().count();
+            let (max_fields, valid_counts) = if options.allow_day_of_year {
+                (7, "5, 6 or 7")
+            } else {
+                (6, "5 or 6")
+            };
+            if !(5..=max_fields).contains(&number_of_fields) {
+                return Err(ErrorKind::Expression(format!(
+                    "Expression has {} fields. Valid cron expressions without seconds have {}.",
+                    number_of_fields, valid_counts
+                ))
+                .into());
+            }
+            fields_expression = Cow::Owned(format!("0 {}", fields_part));
+        }
+        let syntax_extensions = syntax_extensions(&fields_expression);
+        let dialect = if unix {
+            Dialect::Unix
+        } else if syntax_extensions.any {
+            Dialect::Quartz
+        } else {
+            Dialect::Cron
+        };
+        let words: Vec<&str> = fields_expression.split_whitespace().collect();
+        let any_days_of_month = words.get(3) == Some(&"?");
+        let any_days_of_week = words.get(5) == Some(&"?");
+        if options.day_groups {
+            fields_expression = Cow::Owned(expand_day_groups(&fields_expression));
+        }
+        if options.iso_days_of_week && !fields_expression.trim_start().starts_with('@') {
+            fields_expression = Cow::Owned(iso_days_of_week(&fields_expression));
+        }
+        // `?` means the same as `*` wherever it's accepted.
+        if options.quartz_lenient {
+            fields_expression = Cow::Owned(fields_expression.replace('?', "*"));
+        }
+        // Without the option, a days of year field is just one field too many.
+        if !options.allow_day_of_year && fields_expression.split_whitespace().count() == 8 {
+            return Err(field_count_error(8));
+        }
+        let (schedule_fields, resolved_random) = match options.random_seed {
+            Some(seed) => parse_fields_with_seed(&fields_expression, seed)?,
+            None => (parse_fields(&fields_expression)?, false),
+        };
+        if schedule_fields.has_explicit_days_of_year() && !options.allow_day_of_year {
+            return Err(field_count_error(8));
+        }
+        let mut schedule_fields = schedule_fields
+            .with_syntax_extensions(syntax_extensions)
+            .with_dialect(dialect, any_days_of_month, any_days_of_week);
+        if let Some(weeks_of_year) = weeks_of_year {
+            schedule_fields = schedule_fields.with_weeks_of_year(weeks_of_year);
+        }
+        // The source records the values that were drawn, so it parses to the same schedule
+        // without the seed.
+        let source = if resolved_random {
+            schedule_fields.to_expression()
+        } else if every.is_some() {
+            expression.trim().to_owned()
+        } else {
+            String::from(expression)
+        };
+        if let Some(default_years) = &options.default_years {
+            schedule_fields = schedule_fields.with_default_years(default_years);
+        }
+        Ok(Schedule::new(source, schedule_fields))
+    }
+
+    /// Parses an expression like `from_str`, additionally accepting `WEEKDAY` for `MON-FRI` and
+    /// `WEEKEND` for `SAT,SUN` in the day of week field. These group names aren't standard cron,
+    /// so `from_str` rejects them. This is the same as parsing with
+    /// [ParseOptions::day_groups](struct.ParseOptions.html#method.day_groups).
+    /// # Example
+    /// ```
+    /// use cron_schedule::{Schedule, TimeUnitSpec};
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str_with_day_groups("0 0 9 * * WEEKDAY").unwrap();
+    /// assert_eq!(5, schedule.days_of_week().count());
+    /// assert!(Schedule::from_str("0 0 9 * * WEEKDAY").is_err());
+    /// ```
+    pub fn from_str_with_day_groups(expression: &str) -> Result<Schedule, Error> {
+        Schedule::parse_with(expression, &ParseOptions::new().day_groups(true))
+    }
+
+    /// Parses an expression like `from_str`, additionally accepting `R(start-end)` in any field,
+    /// which is replaced by a random value from `start` through `end`. The values are drawn
+    /// from a generator seeded with `seed`, so the same seed always gives the same schedule. Giving
+    /// each job its own seed, such as a hash of its name, spreads their fire times apart. This is
+    /// the same as parsing with
+    /// [ParseOptions::random_seed](struct.ParseOptions.html#method.random_seed).
+    /// # Example
+    /// ```
+    /// use cron_schedule::{Schedule, TimeUnitSpec};
+    ///
+    /// let schedule = Schedule::from_str_with_seed("0 R(0-59) 3 * * *", 42).unwrap();
+    /// assert_eq!(1, schedule.minutes().count());
+    /// assert_eq!(schedule, Schedule::from_str_with_seed("0 R(0-59) 3 * * *", 42).unwrap());
+    /// ```
+    pub fn from_str_with_seed(expression: &str, seed: u64) -> Result<Schedule, Error> {
+        Schedule::parse_with(expression, &ParseOptions::new().random_seed(seed))
+    }
+
+    /// Builds the schedule firing every `interval`, as `@every` does, for callers holding a
+    /// duration rather than a string. The interval must be a positive whole number of seconds
+    /// that divides a minute, an hour or a day evenly, since fire times are counted from
+    /// midnight rather than from when the schedule starts. The source is the equivalent
+    /// `@every` expression.
+    /// # Example
+    /// ```
+    /// use chrono::Duration;
+    /// use cron_schedule::Schedule;
+    ///
+    /// let schedule = Schedule::from_interval(Duration::minutes(15)).unwrap();
+    /// assert_eq!("@every 15m", schedule.to_string());
+    /// let quarter_hour = 900_000_000_000;
+    /// assert_eq!(Some(2 * quarter_hour), schedule.next_after(&quarter_hour));
+    /// assert!(Schedule::from_interval(Duration::seconds(90)).is_err());
+    /// ```
+    pub fn from_interval(interval: Duration) -> Result<Schedule, Error> {
+        let seconds = match interval.num_seconds() {
+            seconds if seconds <= 0 || interval != Duration::seconds(seconds) => None,
+            seconds => u32::try_from(seconds).ok(),
+        };
+        let seconds = seconds.ok_or_else(|| -> Error {
+            ErrorKind::Expression(format!(
+                "Invalid interval: {}, it must be a positive whole number of seconds.",
+                interval
+            ))
+            .into()
+        })?;
+        let source = match seconds {
+            seconds if seconds % 3_600 == 0 => format!("@every {}h", seconds / 3_600),
+            seconds if seconds % 60 == 0 => format!("@every {}m", seconds / 60),
+            seconds => format!("@every {}s", seconds),
+        };
+        Schedule::from_str(&source)
+    }
+
+    /// Parses an expression that may be prefixed with a label, as in `backup: 0 0 2 * * *`,
+    /// returning the label if there was one. Everything before the first colon is the label,
+    /// unless it starts with `@`: the colon then belongs to an `@every … aligned-to :MM` offset.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    ///
+    /// let (label, schedule) = Schedule::from_labeled_str("backup: 0 0 2 * * *").unwrap();
+    /// assert_eq!(Some("backup".to_owned()), label);
+    /// assert_eq!("0 0 2 * * *", schedule.to_string());
+    ///
+    /// let (label, _) = Schedule::from_labeled_str("@every 1h aligned-to :15").unwrap();
+    /// assert_eq!(None, label);
+    /// ```
+    pub fn from_labeled_str(expression: &str) -> Result<(Option<String>, Schedule), Error> {
+        let labeled = expression
+            .split_once(':')
+            .filter(|(label, _)| !label.trim_start().starts_with('@'));
+        match labeled {
+            Some((label, expression)) => {
+                let label = label.trim();
+                if label.is_empty() {
+                    return Err(ErrorKind::Expression(
+                        "The label before ':' must not be empty.".to_owned(),
+                    )
+                    .into());
+                }
+                let schedule = Schedule::from_str(expression.trim())?;
+                Ok((Some(label.to_owned()), schedule))
+            }
+            None => Ok((None, Schedule::from_str(expression)?)),
         }
     }
 }
@@ -42,13 +631,8 @@ impl FromStr for Schedule {
 impl ScheduleFields {
     pub fn from_field_list(fields: Vec<Field>) -> Result<ScheduleFields, Error> {
         let number_of_fields = fields.len();
-        if number_of_fields != 6 && number_of_fields != 7 {
-            return Err(ErrorKind::Expression(format!(
-                "Expression has {} fields. Valid cron \
-                 expressions have 6 or 7.",
-                number_of_fields
-            ))
-            .into());
+        if !(6..=8).contains(&number_of_fields) {
+            return Err(field_count_error(number_of_fields));
         }
 
         let mut iter = fields.into_iter();
@@ -63,8 +647,9 @@ impl ScheduleFields {
             .next()
             .map(Years::from_field)
             .unwrap_or_else(|| Ok(Years::all()))?;
+        let days_of_year = iter.next().map(DaysOfYear::from_field).transpose()?;
 
-        Ok(ScheduleFields::new(
+        let fields = ScheduleFields::new(
             seconds,
             minutes,
             hours,
@@ -72,7 +657,12 @@ impl ScheduleFields {
             months,
             days_of_week,
             years,
-        ))
+        )
+        .with_explicit_years(number_of_fields >= 7);
+        Ok(match days_of_year {
+            Some(days_of_year) => fields.with_days_of_year(days_of_year),
+            None => fields,
+        })
     }
 }
 
@@ -95,16 +685,14 @@ where
 {
     fn from_field(field: Field) -> Result<T, Error> {
         if field.specifiers.len() == 1
-            && field.specifiers.get(0).unwrap() == &RootSpecifier::from(Specifier::All)
+            && field.specifiers.first().unwrap() == &RootSpecifier::from(Specifier::All)
         {
             return Ok(T::all());
         }
+        // Each specifier is validated as it's expanded, so the first invalid one stops parsing.
         let mut ordinals = OrdinalSet::new();
         for specifier in field.specifiers {
-            let specifier_ordinals: OrdinalSet = T::ordinals_from_root_specifier(&specifier)?;
-            for ordinal in specifier_ordinals {
-                ordinals.insert(T::validate_ordinal(ordinal)?);
-            }
+            ordinals.extend(T::ordinals_from_root_specifier(&specifier)?);
         }
         Ok(T::from_ordinal_set(ordinals))
     }
@@ -153,6 +741,13 @@ fn named_range(x: &str) -> IResult<&str, Specifier, nom::error::Error<&str>> {
     })(x)
 }
 
+fn random(x: &str) -> IResult<&str, Specifier, nom::error::Error<&str>> {
+    map(
+        tuple((ws(tag("R(")), ordinal, tag("-"), ordinal, tag(")"))),
+        |(_open, start, _split, end, _close)| Specifier::Random(start, end),
+    )(x)
+}
+
 fn all(x: &str) -> IResult<&str, Specifier, nom::error::Error<&str>> {
     map(tag("*"), |_s: &str| Specifier::All)(x)
 }
@@ -162,7 +757,7 @@ fn any(x: &str) -> IResult<&str, Specifier, nom::error::Error<&str>> {
 }
 
 fn specifier(x: &str) -> IResult<&str, Specifier, nom::error::Error<&str>> {
-    alt((all, range, point, named_range))(x)
+    alt((all, random, range, point, named_range))(x)
 }
 
 fn specifier_with_any(x: &str) -> IResult<&str, Specifier, nom::error::Error<&str>> {
@@ -302,8 +897,9 @@ fn shorthand(x: &str) -> IResult<&str, ScheduleFields, nom::error::Error<&str>>
     )(x)
 }
 
-fn longhand(x: &str) -> IResult<&str, ScheduleFields, nom::error::Error<&str>> {
-    map_res(
+// Splits a longhand expression into its fields without interpreting their values.
+fn longhand_fields(x: &str) -> IResult<&str, Vec<Field>, nom::error::Error<&str>> {
+    map(
         tuple((
             field,
             field,
@@ -312,18 +908,32 @@ fn longhand(x: &str) -> IResult<&str, ScheduleFields, nom::error::Error<&str>> {
             field,
             field_with_any,
             opt(field),
+            opt(field),
             complete(eof),
         )),
-        |(seconds, minutes, hours, days_of_month, months, days_of_week, years, _eof)| {
+        |(
+            seconds,
+            minutes,
+            hours,
+            days_of_month,
+            months,
+            days_of_week,
+            years,
+            days_of_year,
+            _eof,
+        )| {
             let mut fields = vec![seconds, minutes, hours, days_of_month, months, days_of_week];
-            if let Some(years) = years {
-                fields.push(years);
-            }
-            ScheduleFields::from_field_list(fields)
+            fields.extend(years);
+            fields.extend(days_of_year);
+            fields
         },
     )(x)
 }
 
+fn longhand(x: &str) -> IResult<&str, ScheduleFields, nom::error::Error<&str>> {
+    map_res(longhand_fields, ScheduleFields::from_field_list)(x)
+}
+
 fn schedule(x: &str) -> IResult<&str, ScheduleFields, nom::error::Error<&str>> {
     alt((shorthand, longhand))(x)
 }
@@ -759,6 +1369,48 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_valid_longhand_with_all_seconds_and_year() {
+        let expression = "* 0 12 * * MON 2025";
+        let res = longhand(expression).unwrap();
+        assert_eq!(
+            res,
+            (
+                "",
+                ScheduleFields::new(
+                    Seconds::all(),
+                    Minutes::from_ordinal(0),
+                    Hours::from_ordinal(12),
+                    DaysOfMonth::all(),
+                    Months::all(),
+                    DaysOfWeek::from_ordinal(2),
+                    Years::from_ordinal(2025)
+                )
+            )
+        )
+    }
+
+    #[test]
+    fn test_valid_longhand_with_all_seconds_without_year() {
+        let expression = "* 0 12 * * MON";
+        let res = longhand(expression).unwrap();
+        assert_eq!(
+            res,
+            (
+                "",
+                ScheduleFields::new(
+                    Seconds::all(),
+                    Minutes::from_ordinal(0),
+                    Hours::from_ordinal(12),
+                    DaysOfMonth::all(),
+                    Months::all(),
+                    DaysOfWeek::from_ordinal(2),
+                    Years::all()
+                )
+            )
+        )
+    }
+
     #[test]
     fn test_nom_valid_schedule() {
         let expression = "* * * * * *";
@@ -1050,6 +1702,20 @@ mod test {
         assert!(schedule(expression).is_err());
     }
 
+    #[test]
+    fn test_every_second_warning() {
+        let (_, warnings) = Schedule::from_str_with_warnings("* * * * * *").unwrap();
+        assert_eq!(warnings, vec![Warning::EverySecond]);
+
+        let (_, warnings) = Schedule::from_str_with_warnings("0 * * * * *").unwrap();
+        assert!(warnings.is_empty());
+
+        let (_, warnings) = Schedule::from_str_with_warnings("@hourly").unwrap();
+        assert!(warnings.is_empty());
+
+        assert!(Schedule::from_str_with_warnings("* * * *").is_err());
+    }
+
     #[test]
     fn test_nom_invalid_trailing_characters() {
         let expression = "* * * * * *foo *";
@@ -1058,4 +1724,686 @@ mod test {
         let expression = "* * * * * * * foo";
         assert!(schedule(expression).is_err());
     }
+
+    #[test]
+    fn test_extra_whitespace_between_fields() {
+        let expected = Schedule::from_str("0 0 12 * * Mon").unwrap();
+        for expression in [
+            "0  0 12 * *   Mon",
+            "\t0 0\t12 * * Mon\n",
+            "  0 0 12\n* * Mon  ",
+        ] {
+            let schedule = Schedule::from_str(expression).unwrap();
+            assert!(schedule.timeunitspec_eq(&expected), "{:?}", expression);
+        }
+        let schedule = Schedule::from_str("0 0 12 * *  Mon  2030").unwrap();
+        assert_eq!(1, schedule.years().count());
+    }
+
+    #[test]
+    fn test_missing_field_is_field_count_error() {
+        for (expression, number_of_fields) in [
+            ("* *  * * *", 5),
+            ("0 0 12 * ", 4),
+            ("   ", 0),
+            ("0 0 12 * * Mon 2030 2031", 8),
+        ] {
+            let error = Schedule::from_str(expression).unwrap_err();
+            assert_eq!(
+                format!(
+                    "Invalid expression {:?}: Expression has {} fields. Valid cron expressions \
+                     have 6 or 7.",
+                    expression, number_of_fields
+                ),
+                error.to_string(),
+                "{:?}",
+                expression
+            );
+        }
+
+        // With the right number of fields, a bad field is still reported as such.
+        let error = Schedule::from_str("0 0 12 * * Someday").unwrap_err();
+        assert_eq!(
+            "Invalid expression \"0 0 12 * * Someday\": 'Someday' is not a valid day of the week.",
+            error.to_string()
+        );
+        let error = Schedule::from_str("0 0 12 * * Mon-").unwrap_err();
+        assert_eq!(
+            "Invalid expression \"0 0 12 * * Mon-\": Invalid cron expression.",
+            error.to_string()
+        );
+        let error = Schedule::from_str("@hourly *").unwrap_err();
+        assert_eq!(
+            "Invalid expression \"@hourly *\": Invalid cron expression.",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_labeled_expressions() {
+        let (label, schedule) = Schedule::from_labeled_str("backup: 0 0 2 * * *").unwrap();
+        assert_eq!(Some("backup".to_owned()), label);
+        assert_eq!("0 0 2 * * *", schedule.to_string());
+
+        let (label, schedule) = Schedule::from_labeled_str("  nightly report :@daily").unwrap();
+        assert_eq!(Some("nightly report".to_owned()), label);
+        assert_eq!("@daily", schedule.to_string());
+
+        let (label, schedule) = Schedule::from_labeled_str("0 30 9 * * Mon-Fri").unwrap();
+        assert_eq!(None, label);
+        assert_eq!("0 30 9 * * Mon-Fri", schedule.to_string());
+
+        let (label, schedule) = Schedule::from_labeled_str("@every 1h aligned-to :15").unwrap();
+        assert_eq!(None, label);
+        assert_eq!("@every 1h aligned-to :15", schedule.to_string());
+
+        let (label, schedule) =
+            Schedule::from_labeled_str("report: @every 1h aligned-to :15").unwrap();
+        assert_eq!(Some("report".to_owned()), label);
+        assert_eq!("@every 1h aligned-to :15", schedule.to_string());
+
+        // Only the first colon separates the label, any later one is part of the expression.
+        assert!(Schedule::from_labeled_str("backup: 0 0 2:30 * * *").is_err());
+        assert!(Schedule::from_labeled_str(": 0 0 2 * * *").is_err());
+        assert!(Schedule::from_labeled_str("backup:").is_err());
+        assert!(Schedule::from_labeled_str("backup 0 0 2 * * *").is_err());
+    }
+
+    #[test]
+    fn test_day_groups() {
+        let schedule = Schedule::from_str_with_day_groups("0 0 9 * * WEEKDAY").unwrap();
+        assert_eq!(
+            schedule.days_of_week().iter().collect::<OrdinalSet>(),
+            OrdinalSet::from([2, 3, 4, 5, 6])
+        );
+        assert_eq!("0 0 9 * * WEEKDAY", schedule.to_string());
+
+        let schedule = Schedule::from_str_with_day_groups("0 0 9 * * weekend,Wed 2030").unwrap();
+        assert_eq!(
+            schedule.days_of_week().iter().collect::<OrdinalSet>(),
+            OrdinalSet::from([1, 4, 7])
+        );
+        assert_eq!(1, schedule.years().count());
+
+        // Group names are only recognized in the day of week field, and only when enabled.
+        assert!(Schedule::from_str("0 0 9 * * WEEKDAY").is_err());
+        assert!(Schedule::from_str_with_day_groups("0 0 9 WEEKDAY * *").is_err());
+        assert!(Schedule::from_str_with_day_groups("0 0 9 * * WEEKDAY-Sun").is_err());
+        // Everything else parses as before.
+        let schedule = Schedule::from_str_with_day_groups("0 0 9 * * Mon-Fri").unwrap();
+        assert_eq!(5, schedule.days_of_week().count());
+        assert!(Schedule::from_str_with_day_groups("@weekly").is_ok());
+    }
+
+    #[test]
+    fn test_bare_last_day_of_week() {
+        for expression in ["0 0 0 * * L", "0 0 0 * * l", "0 0 0 * * Sat"] {
+            let schedule = Schedule::from_str(expression).unwrap();
+            assert_eq!(
+                schedule.days_of_week().iter().collect::<OrdinalSet>(),
+                OrdinalSet::from([7]),
+                "{}",
+                expression
+            );
+        }
+        let schedule = Schedule::from_str("0 0 0 * * Mon,L").unwrap();
+        assert_eq!(
+            schedule.days_of_week().iter().collect::<OrdinalSet>(),
+            OrdinalSet::from([2, 7])
+        );
+        // The last weekday of the month form isn't supported, and `L` means nothing in other
+        // fields.
+        assert!(Schedule::from_str("0 0 0 * * 5L").is_err());
+        assert!(Schedule::from_str("0 0 0 L * *").is_err());
+        // `L` is a day on its own, not a range bound.
+        for expression in ["0 0 0 * * Mon-L", "0 0 0 * * L-Mon", "0 0 0 * * Mon-L/2"] {
+            assert!(Schedule::from_str(expression).is_err(), "{}", expression);
+        }
+    }
+
+    #[test]
+    fn test_parse_with_options() {
+        let schedule = Schedule::parse_with("0 0 9 * * *", &ParseOptions::default()).unwrap();
+        assert!(schedule.timeunitspec_eq(&Schedule::from_str("0 0 9 * * *").unwrap()));
+
+        // Comments and day groups together.
+        let options = ParseOptions::new().strip_comments(true).day_groups(true);
+        let schedule = Schedule::parse_with("0 0 10 * * weekend  # lie in", &options).unwrap();
+        assert_eq!("0 0 10 * * weekend", schedule.to_string());
+        assert_eq!(
+            schedule.days_of_week().iter().collect::<OrdinalSet>(),
+            OrdinalSet::from([1, 7])
+        );
+        assert!(Schedule::parse_with("0 0 10 * * weekend # lie in", &ParseOptions::new()).is_err());
+        assert!(Schedule::parse_with(
+            "0 0 10 * * weekend # lie in",
+            &ParseOptions::new().strip_comments(true)
+        )
+        .is_err());
+        assert!(Schedule::parse_with("# only a comment", &options).is_err());
+
+        // Length limits together with comments, which count towards the limit.
+        let options = ParseOptions::new().max_length(20).strip_comments(true);
+        assert!(Schedule::parse_with("0 0 9 * * * # short", &options).is_ok());
+        let error = Schedule::parse_with("0 0 9 * * * # a longer comment", &options).unwrap_err();
+        assert_eq!(
+            "Invalid expression: Expression is 30 bytes long, the limit is 20.",
+            error.to_string()
+        );
+        assert_eq!(None, error.input());
+    }
+
+    #[test]
+    fn test_aliases() {
+        let aliases = std::collections::HashMap::from([
+            ("business".to_owned(), "0 0 9-17 * * MON-FRI".to_owned()),
+            ("daily".to_owned(), "0 0 6 * * *".to_owned()),
+            ("nested".to_owned(), "@business".to_owned()),
+            ("minutely".to_owned(), "*/15 * * * *".to_owned()),
+        ]);
+        let options = ParseOptions::new().aliases(aliases);
+        let schedule = Schedule::parse_with(" @business ", &options).unwrap();
+        assert_eq!("0 0 9-17 * * MON-FRI", schedule.to_string());
+        assert!(schedule.timeunitspec_eq(&Schedule::from_str("0 0 9-17 * * 2-6").unwrap()));
+        // Shorthands are replaced, or used as usual if there's no such alias.
+        let daily = Schedule::parse_with("@daily", &options).unwrap();
+        assert_eq!(vec![6], daily.hours().iter().collect::<Vec<Ordinal>>());
+        assert!(Schedule::parse_with("@hourly", &options).is_ok());
+        // The aliased expression is parsed with the other options, but not the aliases.
+        assert!(Schedule::parse_with("@minutely", &options).is_err());
+        let no_seconds = options.clone().no_seconds(true);
+        assert!(Schedule::parse_with("@minutely", &no_seconds).is_ok());
+        assert!(Schedule::parse_with("@nested", &options).is_err());
+
+        let error = Schedule::parse_with("@weekdays", &options).unwrap_err();
+        assert_eq!(Some("@weekdays"), error.input());
+        assert!(Schedule::parse_with("@business", &ParseOptions::new()).is_err());
+    }
+
+    #[test]
+    fn test_require_restriction() {
+        let options = ParseOptions::new().require_restriction(true);
+        for expression in ["* * * * * *", "* * * * * * *", "* * * ? * ?"] {
+            let error = Schedule::parse_with(expression, &options).unwrap_err();
+            assert!(
+                error.to_string().contains("Every field is `*`"),
+                "{}",
+                error
+            );
+            assert!(Schedule::from_str(expression).is_ok());
+        }
+        let no_seconds = options.clone().no_seconds(true);
+        assert!(Schedule::parse_with("* * * * *", &no_seconds).is_ok());
+        for expression in [
+            "0 * * * * *",
+            "* * * * * Mon",
+            "* * * * * * 2030",
+            "@yearly",
+        ] {
+            assert!(
+                Schedule::parse_with(expression, &options).is_ok(),
+                "{}",
+                expression
+            );
+        }
+    }
+
+    #[test]
+    fn test_reject_redundant_lists() {
+        let options = ParseOptions::new().reject_redundant_lists(true);
+        let minutes: Vec<String> = (0..60).map(|minute| minute.to_string()).collect();
+        let enumerated = format!("0 {} * * * *", minutes.join(","));
+        assert!(Schedule::from_str(&enumerated).is_ok());
+        let error = Schedule::parse_with(&enumerated, &options).unwrap_err();
+        assert!(error.is_parse_error());
+        assert!(
+            error.to_string().contains("The minute field `0,1,2,"),
+            "{}",
+            error
+        );
+        assert!(
+            error.to_string().ends_with("write `*` instead."),
+            "{}",
+            error
+        );
+
+        for expression in [
+            "0-59 * * * * *",
+            "0 */1 * * * *",
+            "0 0 0-11,12-23 * * *",
+            "0 0 0 ? Jan-Dec *",
+            "0 0 0 * * Sun-Sat",
+            "0 0 0 * * * 1970-2100",
+        ] {
+            assert!(
+                Schedule::parse_with(expression, &options).is_err(),
+                "{}",
+                expression
+            );
+        }
+        for expression in [
+            "0 * * * * *",
+            "* * * ? * Mon",
+            "0 0-58 * * * *",
+            "0 0 0 * * * 1970-2099",
+            "@hourly",
+            "@every 15m",
+        ] {
+            assert!(
+                Schedule::parse_with(expression, &options).is_ok(),
+                "{}",
+                expression
+            );
+        }
+
+        let unix = options.clone().no_seconds(true);
+        assert!(Schedule::parse_with("* * * * *", &unix).is_ok());
+        assert!(Schedule::parse_with("* 0-23 * * *", &unix).is_err());
+        let weeks = options.clone().allow_iso_week(true);
+        assert!(Schedule::parse_with("0 0 9 * * Mon W*", &weeks).is_ok());
+        assert!(Schedule::parse_with("0 0 9 * * Mon W1-53", &weeks).is_err());
+        assert!(Schedule::parse_with("0 0 9 * * Mon 2030 W1-53", &weeks).is_err());
+        let error = Schedule::parse_with("0 0 9 * * Mon 1970-2100 W1/2", &weeks).unwrap_err();
+        assert!(error.to_string().contains("The year field"), "{}", error);
+    }
+
+    #[test]
+    fn test_min_interval() {
+        let options = ParseOptions::new().min_interval(chrono::Duration::minutes(1));
+        let error = Schedule::parse_with("* * * * * *", &options).unwrap_err();
+        assert_eq!(
+            "Invalid expression \"* * * * * *\": The schedule can fire twice within 1s, the \
+             minimum interval is 60s.",
+            error.to_string()
+        );
+        for expression in [
+            "0 * * * * *",
+            "@hourly",
+            "0 0 9 * * Mon",
+            "0 0 0 1 1 * 2030",
+        ] {
+            assert!(
+                Schedule::parse_with(expression, &options).is_ok(),
+                "{}",
+                expression
+            );
+        }
+        assert!(Schedule::parse_with("0,30 * * * * *", &options).is_err());
+        assert!(Schedule::parse_with("@every 30s", &options).is_err());
+        // Across midnight, 23:59:30 to 00:00:00.
+        assert!(Schedule::parse_with("30 59 23 * * *", &options).is_ok());
+        assert!(Schedule::parse_with("0,30 0 0 * * *", &options).is_err());
+
+        let options = ParseOptions::new().min_interval(chrono::Duration::days(2));
+        assert!(Schedule::parse_with("@daily", &options).is_err());
+        assert!(Schedule::parse_with("@weekly", &options).is_ok());
+    }
+
+    #[test]
+    fn test_parse_without_seconds() {
+        let options = ParseOptions::new().no_seconds(true);
+        let schedule = Schedule::parse_with("*/15 9-17 * * Mon-Fri", &options).unwrap();
+        assert_eq!(
+            schedule.seconds().iter().collect::<OrdinalSet>(),
+            OrdinalSet::from([0])
+        );
+        assert_eq!(4, schedule.minutes().count());
+        assert_eq!(9, schedule.hours().count());
+        assert_eq!("*/15 9-17 * * Mon-Fri", schedule.to_string());
+
+        // Six fields end with the year.
+        let schedule = Schedule::parse_with("0 12 * * * 2030", &options).unwrap();
+        assert_eq!(1, schedule.years().count());
+        assert!(schedule.years().includes(2030));
+        assert!(schedule.timeunitspec_eq(&Schedule::from_str("0 0 12 * * * 2030").unwrap()));
+
+        // Even a field of all stars fires at most once a minute.
+        let schedule = Schedule::parse_with("* * * * *", &options).unwrap();
+        let fire_times: Vec<u64> = schedule.after(&0).take(3).collect();
+        assert_eq!(
+            fire_times,
+            vec![60_000_000_000, 120_000_000_000, 180_000_000_000]
+        );
+
+        let error = Schedule::parse_with("0 0 12 * * * 2030", &options).unwrap_err();
+        assert_eq!(
+            "Invalid expression \"0 0 12 * * * 2030\": Expression has 7 fields. Valid cron \
+             expressions without seconds have 5 or 6.",
+            error.to_string()
+        );
+        assert!(Schedule::parse_with("@hourly", &options).is_ok());
+        let options = options.day_groups(true);
+        let schedule = Schedule::parse_with("30 8 * * WEEKEND", &options).unwrap();
+        assert_eq!(2, schedule.days_of_week().count());
+    }
+
+    #[test]
+    fn test_day_of_year_field() {
+        assert_eq!(
+            "Invalid expression \"0 0 0 * * * * 60\": Expression has 8 fields. Valid cron \
+             expressions have 6 or 7.",
+            Schedule::from_str("0 0 0 * * * * 60")
+                .unwrap_err()
+                .to_string()
+        );
+
+        let options = ParseOptions::new().allow_day_of_year(true);
+        let schedule = Schedule::parse_with("0 0 12 * * * 2030 1-7,100", &options).unwrap();
+        assert_eq!(8, schedule.days_of_year().count());
+        assert!(schedule.days_of_year().includes(100));
+        assert_eq!(8, schedule.fields_count());
+        assert_eq!(8, schedule.fields().count());
+        assert_eq!(
+            "0 0 9 * * * 2030 1-7,100",
+            schedule.with_hours(Hours::from_ordinal(9)).to_string()
+        );
+        assert!(!schedule.timeunitspec_eq(&Schedule::from_str("0 0 12 * * * 2030").unwrap()));
+
+        // Without an eighth field, every day of the year matches.
+        let schedule = Schedule::parse_with("0 0 12 * * *", &options).unwrap();
+        assert!(schedule.days_of_year().is_all());
+        assert_eq!(7, schedule.fields().count());
+
+        assert!(Schedule::parse_with("0 0 12 * * * * 367", &options)
+            .unwrap_err()
+            .is_out_of_range());
+        assert!(Schedule::parse_with("0 12 * * * * 60", &options.no_seconds(true)).is_ok());
+    }
+
+    #[test]
+    fn test_out_of_range_fails_before_expanding() {
+        let started = std::time::Instant::now();
+        for expression in [
+            "0-100000 * * * * *",
+            "0-4000000000 * * * * *",
+            "0/1,4000000000 * * * * *",
+            "* * * * * * 1970-4000000000",
+        ] {
+            let error = Schedule::from_str(expression).unwrap_err();
+            assert!(error.is_out_of_range(), "{}: {}", expression, error);
+        }
+        // Expanding any of these ranges would take far longer.
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+
+        let field = Field {
+            specifiers: vec![
+                RootSpecifier::from(Specifier::Point(61)),
+                RootSpecifier::from(Specifier::Range(0, 100_000)),
+            ],
+        };
+        assert_eq!(
+            "Invalid expression: Seconds must be less than 59. ('61' specified.)",
+            Seconds::from_field(field).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_quartz_lenient_any() {
+        let options = ParseOptions::new().quartz_lenient(true);
+        for expression in ["? * * * * *", "0 ? * * * *", "0 0 ? * ? ?", "0 0 0 ? * * ?"] {
+            assert!(Schedule::from_str(expression).is_err(), "{}", expression);
+            let schedule = Schedule::parse_with(expression, &options).unwrap();
+            assert_eq!(expression, schedule.to_string());
+        }
+        let schedule = Schedule::parse_with("? 0 12 * * ?", &options).unwrap();
+        assert!(schedule.seconds().is_all());
+        assert!(schedule.timeunitspec_eq(&Schedule::from_str("* 0 12 * * *").unwrap()));
+        let schedule = Schedule::parse_with("0 ?/15 * ? * *", &options).unwrap();
+        assert_eq!(4, schedule.minutes().count());
+    }
+
+    #[test]
+    fn test_random_specifiers() {
+        let expression = "R(0-59) R(0-59) R(9-17) * * Mon-Fri";
+        let schedule = Schedule::from_str_with_seed(expression, 7).unwrap();
+        assert_eq!(1, schedule.seconds().count());
+        assert_eq!(1, schedule.minutes().count());
+        let hour = schedule.hours().iter().next().unwrap();
+        assert!((9..=17).contains(&hour));
+        assert_eq!(
+            schedule,
+            Schedule::from_str_with_seed(expression, 7).unwrap()
+        );
+        // The drawn values are written into the source.
+        let reparsed = Schedule::from_str(&schedule.to_string()).unwrap();
+        assert!(schedule.timeunitspec_eq(&reparsed));
+
+        let schedules: Vec<Schedule> = (0..10)
+            .map(|seed| Schedule::from_str_with_seed(expression, seed).unwrap())
+            .collect();
+        assert!(schedules
+            .iter()
+            .any(|other| !other.timeunitspec_eq(&schedule)));
+
+        // Random points can start a period, and a single value range has only one outcome.
+        let schedule = Schedule::from_str_with_seed("0 R(0-14)/15 R(3-3) * * *", 1).unwrap();
+        assert_eq!(4, schedule.minutes().count());
+        assert_eq!(Some(3), schedule.hours().iter().next());
+
+        assert_eq!(
+            "Invalid expression \"0 R(0-59) * * * *\": R(0-59) in Minutes needs a random seed, \
+             see Schedule::from_str_with_seed.",
+            Schedule::from_str("0 R(0-59) * * * *")
+                .unwrap_err()
+                .to_string()
+        );
+        // Out of range bounds fail for every seed, not only when they are drawn.
+        for seed in 0..10 {
+            let error = Schedule::from_str_with_seed("0 R(0-60) * * * *", seed).unwrap_err();
+            assert!(error.is_out_of_range());
+        }
+        assert!(Schedule::from_str_with_seed("0 R(30-10) * * * *", 1).is_err());
+    }
+
+    #[test]
+    fn test_default_years() {
+        // Monday February 29th only happens a few times in the default span.
+        let schedule = Schedule::from_str("0 0 0 29 2 Mon").unwrap();
+        assert_eq!(4, schedule.after(&0).count());
+
+        let options = ParseOptions::new().default_years(Years::from_range(2000, 2050).unwrap());
+        let schedule = Schedule::parse_with("0 0 0 29 2 Mon", &options).unwrap();
+        assert_eq!(51, schedule.years().count());
+        assert_eq!(2, schedule.after(&0).count());
+        assert_eq!("0 0 0 29 2 Mon", schedule.to_string());
+        let schedule = Schedule::parse_with("@daily", &options).unwrap();
+        assert_eq!(Some(2000), schedule.years().iter().next());
+        let schedule = Schedule::parse_with("@every 1h", &options).unwrap();
+        assert_eq!(51, schedule.years().count());
+        assert_eq!("@every 1h", schedule.to_string());
+
+        // Years given in the expression are kept.
+        let schedule = Schedule::parse_with("0 0 0 29 2 Mon 1988", &options).unwrap();
+        assert_eq!(1, schedule.after(&0).count());
+
+        // The span may also be wider than the default.
+        let options = ParseOptions::new().default_years(Years::from_range(2090, 2554).unwrap());
+        let schedule = Schedule::parse_with("0 0 0 1 1 * *", &options).unwrap();
+        assert_eq!(465, schedule.after(&0).count());
+    }
+
+    #[test]
+    fn test_custom_directives() {
+        assert!(Schedule::from_str("@startup").is_err());
+
+        let options = ParseOptions::new().allow_custom_directives(true);
+        let schedule = Schedule::parse_with(" @startup ", &options).unwrap();
+        assert_eq!(Some("@startup"), schedule.custom_directive());
+        assert_eq!("@startup", schedule.to_string());
+        assert_eq!(None, schedule.next_after(&0));
+        assert_eq!(None, schedule.after(&0).next());
+        assert_eq!(None, schedule.prev_before(&u64::MAX));
+        assert!(!schedule.includes(chrono::Utc::now()));
+        assert_ne!(
+            schedule,
+            Schedule::parse_with("@shutdown", &options).unwrap()
+        );
+
+        // Shorthands and ordinary expressions parse as usual.
+        let schedule = Schedule::parse_with("@daily", &options).unwrap();
+        assert_eq!(None, schedule.custom_directive());
+        assert!(schedule.next_after(&0).is_some());
+        assert_eq!(
+            None,
+            Schedule::parse_with("0 0 * * * *", &options)
+                .unwrap()
+                .custom_directive()
+        );
+        assert!(Schedule::parse_with("@startup now", &options).is_err());
+        assert!(Schedule::parse_with("@", &options).is_err());
+    }
+
+    #[test]
+    fn test_iso_days_of_week() {
+        let options = ParseOptions::new().iso_days_of_week(true);
+        let days = |expression: &str| {
+            let schedule = Schedule::parse_with(expression, &options).unwrap();
+            schedule.days_of_week().iter().collect::<Vec<_>>()
+        };
+        // Sunday is 1 and Monday 2 in the default numbering.
+        assert_eq!(vec![2], days("0 0 9 * * 1"));
+        assert_eq!(vec![1], days("0 0 9 * * 7"));
+        assert_eq!(vec![2, 3, 4, 5, 6], days("0 0 9 * * 1-5"));
+        assert_eq!(vec![1, 7], days("0 0 9 * * 6,7"));
+        assert_eq!(vec![1, 2, 4, 6], days("0 0 9 * * */2"));
+        assert_eq!(vec![1, 2, 6, 7], days("0 0 9 * * 5-1"));
+        // Stepping from Friday continues through Sunday, the end of the ISO week.
+        assert_eq!(vec![1, 6], days("0 0 9 * * 5/2"));
+        assert_eq!(vec![1, 6], days("0 0 9 * * Fri/2"));
+        assert_eq!(vec![1], days("0 0 9 * * 7/3"));
+        assert_eq!(vec![2, 7], days("0 0 9 * * Mon,Sat"));
+        // The ISO week ends on Sunday, so that's the last day.
+        assert_eq!(vec![1], days("0 0 9 * * L"));
+        assert_eq!(vec![1, 6], days("0 0 9 * * 5,l"));
+        assert!(Schedule::parse_with("0 0 9 * * 1-L", &options).is_err());
+        assert_eq!(vec![1], days("@weekly"));
+        assert!(Schedule::parse_with("0 0 9 * * 8", &options).is_err());
+        assert!(Schedule::parse_with("0 0 9 * * 0", &options).is_err());
+
+        let options = options.no_seconds(true);
+        let schedule = Schedule::parse_with("0 9 * * 7", &options).unwrap();
+        assert_eq!(vec![1], schedule.days_of_week().iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_from_interval() {
+        let schedule = Schedule::from_interval(Duration::seconds(30)).unwrap();
+        assert_eq!("@every 30s", schedule.to_string());
+        let t = 1_623_758_400_000_000_000; // 2021-06-15 12:00:00 UTC
+        let step = 30_000_000_000;
+        let fire_times: Vec<u64> = schedule.after(&t).take(4).collect();
+        assert_eq!(
+            vec![t + step, t + 2 * step, t + 3 * step, t + 4 * step],
+            fire_times
+        );
+
+        for (interval, source) in [
+            (Duration::seconds(1), "@every 1s"),
+            (Duration::minutes(20), "@every 20m"),
+            (Duration::seconds(3_600), "@every 1h"),
+            (Duration::hours(24), "@every 24h"),
+        ] {
+            let schedule = Schedule::from_interval(interval).unwrap();
+            assert_eq!(source, schedule.to_string());
+            assert!(
+                schedule.timeunitspec_eq(&Schedule::from_str(source).unwrap()),
+                "{}",
+                source
+            );
+        }
+
+        for interval in [
+            Duration::zero(),
+            Duration::seconds(-60),
+            Duration::milliseconds(1_500),
+            // Fire times restart at midnight, so these don't keep a steady step.
+            Duration::seconds(90),
+            Duration::minutes(7),
+            Duration::hours(48),
+        ] {
+            assert!(Schedule::from_interval(interval).is_err(), "{}", interval);
+        }
+    }
+
+    #[test]
+    fn test_every() {
+        let schedule = Schedule::from_str("@every 1h aligned-to :15").unwrap();
+        assert_eq!("@every 1h aligned-to :15", schedule.to_string());
+        let fire_times: Vec<u64> = schedule.after(&0).take(3).collect();
+        let quarter_past = 15 * 60 * 1_000_000_000;
+        let hour = 3_600 * 1_000_000_000;
+        assert_eq!(
+            vec![quarter_past, hour + quarter_past, 2 * hour + quarter_past],
+            fire_times
+        );
+        assert!(schedule.timeunitspec_eq(&Schedule::from_str("@every 1h aligned-to 15m").unwrap()));
+        assert!(schedule.timeunitspec_eq(&Schedule::from_str("0 15 * * * *").unwrap()));
+
+        for (every, expression) in [
+            ("@every 15m", "0 */15 * * * *"),
+            ("@every 10s aligned-to 5s", "5/10 * * * * *"),
+            ("@every 6h aligned-to 1h", "0 0 1/6 * * *"),
+            ("@every 20m aligned-to :05", "0 5/20 * * * *"),
+            ("@every 24h aligned-to 9h", "0 0 9 * * *"),
+        ] {
+            let schedule = Schedule::from_str(every).unwrap();
+            let expected = Schedule::from_str(expression).unwrap();
+            assert!(schedule.timeunitspec_eq(&expected), "{}", every);
+            // Intervals already have a seconds field.
+            let options = ParseOptions::new().no_seconds(true);
+            let schedule = Schedule::parse_with(every, &options).unwrap();
+            assert!(schedule.timeunitspec_eq(&expected), "{}", every);
+        }
+
+        for every in [
+            "@every",
+            "@every 7m",
+            "@every 90m",
+            "@every 0s",
+            "@every 1d",
+            "@every 1h aligned-to 1h",
+            "@every 1h aligned-to :60",
+            "@every 1h aligned :15",
+        ] {
+            assert!(Schedule::from_str(every).is_err(), "{}", every);
+        }
+    }
+
+    #[test]
+    fn test_iso_weeks() {
+        use crate::queries::NANOS_PER_DAY;
+        use chrono::{Datelike, TimeZone, Utc, Weekday};
+
+        let expression = "0 0 9 * * Mon 2024-2030 W2/2";
+        assert!(Schedule::from_str(expression).is_err());
+        let options = ParseOptions::new().allow_iso_week(true);
+        let schedule = Schedule::parse_with(expression, &options).unwrap();
+        assert_eq!(26, schedule.weeks_of_year().count());
+        assert_eq!(8, schedule.fields_count());
+        for fire_time in schedule.after(&0).take(60) {
+            let date_time = Utc.timestamp_nanos(fire_time as i64);
+            assert_eq!(Weekday::Mon, date_time.weekday());
+            assert_eq!(0, date_time.iso_week().week() % 2, "{}", date_time);
+        }
+        // Every other Monday, which stays even across the turn of a year with 52 weeks.
+        let fire_times: Vec<u64> = schedule.after(&0).take(27).collect();
+        assert!(fire_times
+            .windows(2)
+            .all(|pair| pair[1] - pair[0] == 14 * NANOS_PER_DAY));
+
+        // Days of the week starting with W aren't a weeks field.
+        let schedule = Schedule::parse_with("0 0 9 * * Wed", &options).unwrap();
+        assert!(schedule.weeks_of_year().is_all());
+        let schedule = Schedule::parse_with("0 0 9 * * Mon W*", &options).unwrap();
+        assert!(schedule.weeks_of_year().is_all());
+        assert!(Schedule::parse_with("0 0 9 * * Mon W54", &options).is_err());
+        assert!(Schedule::parse_with("0 0 9 * * Mon W2-", &options).is_err());
+
+        // 2022 through 2025 have no week 53, 2026 does.
+        let schedule = Schedule::parse_with("0 0 0 * * * 2022-2025 W53", &options).unwrap();
+        assert_eq!(None, schedule.after(&0).next());
+        let schedule = Schedule::parse_with("0 0 0 * * * 2022-2026 W53", &options).unwrap();
+        let first = Utc.timestamp_nanos(schedule.after(&0).next().unwrap() as i64);
+        assert_eq!((2026, 12, 28), (first.year(), first.month(), first.day()));
+    }
 }
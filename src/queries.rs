@@ -1,10 +1,77 @@
-use chrono::{DateTime, Datelike, NaiveDateTime, Timelike, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, Timelike, Utc};
 
 use crate::ordinal::Ordinal;
 use crate::time_unit::{DaysOfMonth, Hours, Minutes, Months, Seconds, TimeUnitField};
 
-const NANOS: u64 = 1_000_000;
-const SECONDS: u64 = 1_000;
+pub const NANOS_PER_SECOND: u64 = 1_000_000_000;
+pub const NANOS_PER_DAY: u64 = 86_400 * NANOS_PER_SECOND;
+
+/// The number of days in each month of a common year, indexed by month ordinal minus one.
+const DAYS_IN_MONTH: [Ordinal; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+/// Returns true if `year` is a leap year in the Gregorian calendar: divisible by 4, except for
+/// century years not divisible by 400.
+pub fn is_leap_year(year: Ordinal) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+/// Returns the number of days in `month` (1-12) of `year`. Only February needs the leap year
+/// check, every other month is a table lookup.
+pub fn days_in_month(year: Ordinal, month: Ordinal) -> Ordinal {
+    if month == 2 && is_leap_year(year) {
+        29
+    } else {
+        DAYS_IN_MONTH[(month - 1) as usize]
+    }
+}
+
+/// Returns the year containing the instant `nanos` nanoseconds after the Unix epoch.
+pub fn year_of(nanos: &u64) -> Ordinal {
+    NaiveDateTime::from_timestamp_opt((nanos / NANOS_PER_SECOND) as i64, 0)
+        .unwrap()
+        .year() as Ordinal
+}
+
+/// Returns the nanoseconds between the Unix epoch and `date_time`, which may be negative or
+/// beyond the range of fire times.
+pub fn nanos_of(date_time: &DateTime<Utc>) -> i128 {
+    i128::from(date_time.timestamp()) * i128::from(NANOS_PER_SECOND)
+        + i128::from(date_time.timestamp_subsec_nanos())
+}
+
+/// Returns the first and last whole seconds since the epoch that fire times from `start` up to
+/// but excluding `end` can fall on, or `None` if there are none, such as when `end` is before the
+/// epoch.
+pub fn window_seconds(start: &DateTime<Utc>, end: &DateTime<Utc>) -> Option<(u64, u64)> {
+    let (start_nanos, end_nanos) = (nanos_of(start), nanos_of(end));
+    let max_nanos = i128::from(u64::MAX);
+    if end_nanos <= 0 || start_nanos >= end_nanos || start_nanos > max_nanos {
+        return None;
+    }
+    let second = i128::from(NANOS_PER_SECOND);
+    // The seconds starting from `start`'s, as with `upcoming_in`, and before `end`.
+    let first = (start_nanos.max(0) / second) as u64;
+    let last = ((end_nanos.min(max_nanos + 1) + second - 1) / second - 1) as u64;
+    Some((first, last))
+}
+
+/// Returns the midnight UTC following the instant `nanos`, if it can be represented.
+pub fn start_of_next_day(nanos: &u64) -> Option<u64> {
+    (nanos / NANOS_PER_DAY + 1).checked_mul(NANOS_PER_DAY)
+}
+
+/// Converts a duration to nanoseconds, saturating durations too long to represent. Negative
+/// durations have no equivalent and return `None`.
+pub fn duration_nanos(duration: &Duration) -> Option<u64> {
+    if *duration < Duration::zero() {
+        return None;
+    }
+    Some(
+        duration
+            .num_nanoseconds()
+            .map_or(u64::MAX, |nanos| nanos as u64),
+    )
+}
 
 pub struct NextAfterQuery {
     initial_datetime: DateTime<Utc>,
@@ -16,9 +83,20 @@ pub struct NextAfterQuery {
 }
 
 impl NextAfterQuery {
+    /// Starts the search at the first whole second strictly after `after`. Fire times always fall
+    /// on whole seconds, so any sub-second part of `after` is truncated first.
     pub fn from(after: &u64) -> NextAfterQuery {
-        let rem = *after % NANOS;
-        let secs = ((*after - rem) / (NANOS * SECONDS)) + 1;
+        // Can't overflow, but stay explicit about the edge of the u64 range.
+        NextAfterQuery::starting_at_second((after / NANOS_PER_SECOND).saturating_add(1))
+    }
+
+    /// Starts the search at the second containing `at`. A fire time is treated as covering its
+    /// whole second, so a reference of `12:00:00.250` still finds a fire time at `12:00:00`.
+    pub fn at_or_after(at: &u64) -> NextAfterQuery {
+        NextAfterQuery::starting_at_second(at / NANOS_PER_SECOND)
+    }
+
+    fn starting_at_second(secs: u64) -> NextAfterQuery {
         let initial_datetime = DateTime::from_naive_utc_and_offset(
             NaiveDateTime::from_timestamp_opt(secs as i64, 0).unwrap(),
             Utc,
@@ -102,3 +180,156 @@ impl NextAfterQuery {
         self.first_second = false;
     }
 }
+
+/// The mirror image of `NextAfterQuery`, bounding each unit from above while scanning backwards
+/// from a reference time.
+pub struct PrevBeforeQuery {
+    initial_datetime: DateTime<Utc>,
+    first_month: bool,
+    first_day_of_month: bool,
+    first_hour: bool,
+    first_minute: bool,
+    first_second: bool,
+}
+
+impl PrevBeforeQuery {
+    /// Starts the search at the last whole second strictly before `before`, if there is one.
+    pub fn before(before: &u64) -> Option<PrevBeforeQuery> {
+        let before = before.checked_sub(1)?;
+        Some(PrevBeforeQuery::starting_at_second(
+            before / NANOS_PER_SECOND,
+        ))
+    }
+
+    /// Starts the search at the second containing `at`, which is never later than `at`.
+    pub fn at_or_before(at: &u64) -> PrevBeforeQuery {
+        PrevBeforeQuery::starting_at_second(at / NANOS_PER_SECOND)
+    }
+
+    fn starting_at_second(secs: u64) -> PrevBeforeQuery {
+        let initial_datetime = DateTime::from_naive_utc_and_offset(
+            NaiveDateTime::from_timestamp_opt(secs as i64, 0).unwrap(),
+            Utc,
+        );
+        PrevBeforeQuery {
+            initial_datetime,
+            first_month: true,
+            first_day_of_month: true,
+            first_hour: true,
+            first_minute: true,
+            first_second: true,
+        }
+    }
+
+    pub fn year_upper_bound(&self) -> Ordinal {
+        self.initial_datetime.year() as u32
+    }
+
+    pub fn month_upper_bound(&mut self) -> Ordinal {
+        if self.first_month {
+            self.first_month = false;
+            return self.initial_datetime.month();
+        }
+        Months::inclusive_max()
+    }
+
+    pub fn reset_month(&mut self) {
+        self.first_month = false;
+        self.reset_day_of_month();
+    }
+
+    pub fn day_of_month_upper_bound(&mut self, year: Ordinal, month: Ordinal) -> Ordinal {
+        if self.first_day_of_month {
+            self.first_day_of_month = false;
+            return self.initial_datetime.day();
+        }
+        days_in_month(year, month)
+    }
+
+    pub fn reset_day_of_month(&mut self) {
+        self.first_day_of_month = false;
+        self.reset_hour();
+    }
+
+    pub fn hour_upper_bound(&mut self) -> Ordinal {
+        if self.first_hour {
+            self.first_hour = false;
+            return self.initial_datetime.hour();
+        }
+        Hours::inclusive_max()
+    }
+
+    pub fn reset_hour(&mut self) {
+        self.first_hour = false;
+        self.reset_minute();
+    }
+
+    pub fn minute_upper_bound(&mut self) -> Ordinal {
+        if self.first_minute {
+            self.first_minute = false;
+            return self.initial_datetime.minute();
+        }
+        Minutes::inclusive_max()
+    }
+
+    pub fn reset_minute(&mut self) {
+        self.first_minute = false;
+        self.reset_second();
+    }
+
+    pub fn second_upper_bound(&mut self) -> Ordinal {
+        if self.first_second {
+            self.first_second = false;
+            return self.initial_datetime.second();
+        }
+        Seconds::inclusive_max()
+    }
+
+    pub fn reset_second(&mut self) {
+        self.first_second = false;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_duration_nanos() {
+        assert_eq!(Some(5_000_000_000), duration_nanos(&Duration::seconds(5)));
+        assert_eq!(Some(0), duration_nanos(&Duration::zero()));
+        assert_eq!(None, duration_nanos(&Duration::seconds(-5)));
+        assert_eq!(Some(u64::MAX), duration_nanos(&Duration::days(365 * 1_000)));
+    }
+
+    #[test]
+    fn test_leap_years() {
+        assert!(is_leap_year(2000));
+        assert!(is_leap_year(2024));
+        assert!(!is_leap_year(1900));
+        assert!(!is_leap_year(2100));
+        assert!(!is_leap_year(2023));
+    }
+
+    #[test]
+    fn test_days_in_february() {
+        assert_eq!(29, days_in_month(2000, 2));
+        assert_eq!(28, days_in_month(1900, 2));
+        assert_eq!(28, days_in_month(2100, 2));
+        assert_eq!(29, days_in_month(2024, 2));
+        assert_eq!(28, days_in_month(2023, 2));
+    }
+
+    #[test]
+    fn test_days_in_month() {
+        let lengths: Vec<Ordinal> = (1..=12).map(|month| days_in_month(2023, month)).collect();
+        assert_eq!(DAYS_IN_MONTH.to_vec(), lengths);
+        assert_eq!(365, lengths.iter().sum::<Ordinal>());
+        assert_eq!(
+            366,
+            (1..=12)
+                .map(|month| days_in_month(2024, month))
+                .sum::<Ordinal>()
+        );
+    }
+}
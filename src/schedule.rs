@@ -1,11 +1,20 @@
 use chrono::offset::TimeZone;
-use chrono::{DateTime, Datelike, Timelike, Utc};
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, Timelike, Utc, Weekday,
+};
+use std::collections::{BTreeSet, HashSet};
 use std::fmt::{Display, Formatter, Result as FmtResult};
-use std::ops::Bound::{Included, Unbounded};
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::ops::RangeBounds;
 
+use crate::error::{Error, ErrorKind};
+use crate::extensions::ExtensionFlags;
 use crate::ordinal::*;
 use crate::queries::*;
 use crate::time_unit::*;
+use crate::warning::Warning;
 
 impl From<Schedule> for String {
     fn from(schedule: Schedule) -> String {
@@ -13,6 +22,80 @@ impl From<Schedule> for String {
     }
 }
 
+/// The finest unit of time a schedule distinguishes, as returned by
+/// [Schedule::granularity](struct.Schedule.html#method.granularity). Checking a schedule with
+/// `Minute` granularity more often than once a minute finds nothing new. Granularities are
+/// ordered from finest to coarsest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Granularity {
+    /// Fires at more than one second of some minute.
+    Second,
+    /// Fires at a single second of more than one minute of some hour.
+    Minute,
+    /// Fires at a single minute and second of more than one hour of the day.
+    Hour,
+    /// Fires at most once a day.
+    Day,
+}
+
+/// A rough idea of how often a schedule fires, as returned by
+/// [Schedule::frequency_class](struct.Schedule.html#method.frequency_class), for grouping
+/// schedules on dashboards. Classes are ordered from most to least frequent, with `Irregular`
+/// last.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FrequencyClass {
+    /// Fires more than once in some minute.
+    SubMinute,
+    /// Fires in more than one minute of some hour, at most once a minute.
+    Minutely,
+    /// Fires in more than one hour of the day, at most once an hour.
+    Hourly,
+    /// Fires once a day, every day or on several days of the week.
+    Daily,
+    /// Fires once a day, on one day of the week.
+    Weekly,
+    /// Fires once a day, on one day of every month.
+    Monthly,
+    /// Fires once a day, on one day of one month.
+    Yearly,
+    /// Fires on some other pattern of days, or never.
+    Irregular,
+}
+
+/// The gaps between consecutive fire times in a window, as returned by
+/// [Schedule::interval_stats](struct.Schedule.html#method.interval_stats). The intervals are
+/// `None` unless the schedule fires at least twice in the window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct IntervalStats {
+    /// The number of fire times in the window.
+    pub count: u64,
+    /// The shortest gap between two consecutive fire times.
+    pub min: Option<Duration>,
+    /// The longest gap between two consecutive fire times.
+    pub max: Option<Duration>,
+    /// The mean gap, the time from the first fire time to the last divided by the number of
+    /// gaps, rounded down to the nanosecond.
+    pub mean: Option<Duration>,
+}
+
+/// The flavour of cron an expression was written in, as returned by
+/// [Schedule::dialect](struct.Schedule.html#method.dialect). Expressions regenerated from the
+/// fields, such as by [with_hours](struct.Schedule.html#method.with_hours), are written in the
+/// same dialect.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Dialect {
+    /// Six or seven fields starting with seconds, this crate's own format.
+    #[default]
+    Cron,
+    /// Five or six fields without seconds, as parsed with
+    /// [ParseOptions::no_seconds](struct.ParseOptions.html#method.no_seconds).
+    Unix,
+    /// Like `Cron`, but using `?` for the days of the month or of the week.
+    Quartz,
+}
+
 #[derive(Clone, Debug, Eq)]
 pub struct Schedule {
     source: String,
@@ -24,94 +107,228 @@ impl Schedule {
         Schedule { source, fields }
     }
 
+    #[cfg(feature = "serde")]
+    pub(crate) fn schedule_fields(&self) -> &ScheduleFields {
+        &self.fields
+    }
+
+    /// Returns the first fire time strictly after `after`.
     pub fn next_after(&self, after: &u64) -> Option<u64> {
-        let mut query = NextAfterQuery::from(after);
+        self.next_matching(NextAfterQuery::from(after), None, &mut 0)
+    }
+
+    /// Returns the first fire time at or after `at`. A fire time counts for its whole second, so
+    /// if `at` falls partway through a second the schedule fires in, that second is returned.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 12 * * *").unwrap();
+    /// let noon = 1_623_758_400_000_000_000; // 2021-06-15 12:00:00 UTC
+    /// assert_eq!(Some(noon), schedule.next_at_or_after(&(noon + 250_000_000)));
+    /// assert_ne!(Some(noon), schedule.next_after(&(noon + 250_000_000)));
+    /// ```
+    pub fn next_at_or_after(&self, at: &u64) -> Option<u64> {
+        self.next_matching(NextAfterQuery::at_or_after(at), None, &mut 0)
+    }
+
+    /// Snaps `at` to the schedule: returns `at` if the schedule fires then, otherwise the next fire
+    /// time. This is `next_at_or_after` under a name that reads better when bucketing timestamps
+    /// into scheduled windows, and likewise treats a fire time as covering its whole second.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 */15 * * * *").unwrap();
+    /// let quarter_hour = 900_000_000_000;
+    /// assert_eq!(Some(quarter_hour), schedule.align_to_next(&quarter_hour));
+    /// let a_minute_later = quarter_hour + 60_000_000_000;
+    /// assert_eq!(Some(2 * quarter_hour), schedule.align_to_next(&a_minute_later));
+    /// ```
+    pub fn align_to_next(&self, at: &u64) -> Option<u64> {
+        self.next_at_or_after(at)
+    }
+
+    /// Returns the fire time `n` places after the first one strictly after `after`, so
+    /// `nth_after(0, after)` is `next_after(after)`. When the schedule fires at the same times on
+    /// every day of a contiguous span of years, the result is computed directly instead of
+    /// iterating over the fire times before it.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 */15 * * * *").unwrap();
+    /// let quarter_hour = 900_000_000_000;
+    /// assert_eq!(Some(quarter_hour), schedule.nth_after(0, &0));
+    /// assert_eq!(Some(100 * quarter_hour), schedule.nth_after(99, &0));
+    /// ```
+    pub fn nth_after(&self, n: usize, after: &u64) -> Option<u64> {
+        let first = self.next_after(after)?;
+        match self.nth_daily_fire_time(first, n) {
+            Some(nth) => nth,
+            None => self.after(after).nth(n),
+        }
+    }
+
+    // Finds the fire time `n` places after `first` arithmetically, for schedules that fire at the
+    // same times every day from the start of their first year to the end of their last. Returns
+    // `None` for other schedules.
+    fn nth_daily_fire_time(&self, first: u64, n: usize) -> Option<Option<u64>> {
+        let fields = &self.fields;
+        let years = fields.years.ordinals();
+        let (first_year, last_year) = (*years.first()?, *years.last()?);
+        if !(fields.months.is_all()
+            && fields.days_of_month.is_all()
+            && fields.days_of_week.is_all()
+            && fields.days_of_year.is_all()
+            && fields.weeks_of_year.is_all()
+            && years.len() as Ordinal == last_year - first_year + 1)
+        {
+            return None;
+        }
+        let hours: Vec<u64> = fields.hours.iter().map(u64::from).collect();
+        let minutes: Vec<u64> = fields.minutes.iter().map(u64::from).collect();
+        let seconds: Vec<u64> = fields.seconds.iter().map(u64::from).collect();
+        let per_hour = (minutes.len() * seconds.len()) as u64;
+        let per_day = hours.len() as u64 * per_hour;
+        let rank =
+            |ordinals: &[u64], ordinal: u64| ordinals.binary_search(&ordinal).unwrap() as u64;
+
+        // Number the fire times from the first one of `first`'s day.
+        let first = first / NANOS_PER_SECOND;
+        let time_of_day = first % 86_400;
+        let index = (rank(&hours, time_of_day / 3_600) * per_hour
+            + rank(&minutes, time_of_day / 60 % 60) * seconds.len() as u64
+            + rank(&seconds, time_of_day % 60))
+        .checked_add(n as u64);
+        let day = index.and_then(|index| (first / 86_400).checked_add(index / per_day));
+        let nth = index.zip(day).and_then(|(index, day)| {
+            let index = index % per_day;
+            let time_of_day = hours[(index / per_hour) as usize] * 3_600
+                + minutes[(index % per_hour / seconds.len() as u64) as usize] * 60
+                + seconds[(index % seconds.len() as u64) as usize];
+            day.checked_mul(86_400)?.checked_add(time_of_day)
+        });
+        let end = NaiveDate::from_ymd_opt(last_year as i32 + 1, 1, 1)?
+            .and_time(NaiveTime::MIN)
+            .and_utc()
+            .timestamp() as u64;
+        Some(
+            nth.filter(|nth| *nth < end)
+                .and_then(|nth| nth.checked_mul(NANOS_PER_SECOND)),
+        )
+    }
+
+    /// Returns the first fire time strictly after `after` and at or before `until`, without
+    /// scanning years past `until`.
+    pub(crate) fn next_after_until(&self, after: &u64, until: &u64) -> Option<u64> {
+        self.next_matching(NextAfterQuery::from(after), Some(until), &mut 0)
+    }
+
+    /// Finds the first fire time matching `query`, adding the number of days and minutes examined
+    /// along the way to `candidates_scanned`.
+    ///
+    /// Rather than stepping through every instant, each unit jumps straight to the first value of
+    /// its sorted set at or after the query's bound, and carries into the next larger unit when
+    /// none is left, starting the smaller units over from their minimum. Only the days of the
+    /// month are checked one by one, against the weekday and day of year, so a monthly schedule
+    /// looks at a single day per month.
+    fn next_matching(
+        &self,
+        mut query: NextAfterQuery,
+        until: Option<&u64>,
+        candidates_scanned: &mut usize,
+    ) -> Option<u64> {
+        let year_start = query.year_lower_bound();
+        let year_end = match until {
+            Some(until) if year_of(until) < year_start => return None,
+            Some(until) => Included(year_of(until)),
+            None => Unbounded,
+        };
         for year in self
             .fields
             .years
             .ordinals()
-            .range((Included(query.year_lower_bound()), Unbounded))
+            .range((Included(year_start), year_end))
             .cloned()
         {
-            let month_start = query.month_lower_bound();
-            if !self.fields.months.ordinals().contains(&month_start) {
+            if year != year_start {
                 query.reset_month();
             }
+            // At each level, the first ordinal yielded by the range tells us whether the lower
+            // bound itself was included. If it wasn't, every unit below it starts over from its
+            // minimum. Comparing against the bound avoids a separate set lookup per level.
+            let month_start = query.month_lower_bound();
             let month_range = (Included(month_start), Included(Months::inclusive_max()));
             for month in self.fields.months.ordinals().range(month_range).cloned() {
-                let day_of_month_start = query.day_of_month_lower_bound();
-                if !self
-                    .fields
-                    .days_of_month
-                    .ordinals()
-                    .contains(&day_of_month_start)
-                {
-                    query.reset_day_of_month();
+                if month != month_start {
+                    query.reset_month();
                 }
-                let day_of_month_end = days_in_month(month, year);
+                let day_of_month_start = query.day_of_month_lower_bound();
+                let day_of_month_end = days_in_month(year, month);
                 let day_of_month_range = (Included(day_of_month_start), Included(day_of_month_end));
 
-                'day_loop: for day_of_month in self
+                for day_of_month in self
                     .fields
                     .days_of_month
                     .ordinals()
                     .range(day_of_month_range)
                     .cloned()
                 {
-                    let hour_start = query.hour_lower_bound();
-                    if !self.fields.hours.ordinals().contains(&hour_start) {
-                        query.reset_hour();
+                    *candidates_scanned += 1;
+                    if day_of_month != day_of_month_start {
+                        query.reset_day_of_month();
                     }
-                    let hour_range = (Included(hour_start), Included(Hours::inclusive_max()));
+                    // The weekday and day of year only depend on the date, so reject the whole
+                    // day before scanning its hours, minutes and seconds.
+                    let date = match NaiveDate::from_ymd_opt(year as i32, month, day_of_month) {
+                        Some(date) => date,
+                        None => {
+                            query.reset_day_of_month();
+                            continue;
+                        }
+                    };
+                    if !self.fields.includes_date(&date) {
+                        query.reset_day_of_month();
+                        continue;
+                    }
+                    let day_start = date.and_time(NaiveTime::MIN).and_utc().timestamp();
 
+                    let hour_start = query.hour_lower_bound();
+                    let hour_range = (Included(hour_start), Included(Hours::inclusive_max()));
                     for hour in self.fields.hours.ordinals().range(hour_range).cloned() {
-                        let minute_start = query.minute_lower_bound();
-                        if !self.fields.minutes.ordinals().contains(&minute_start) {
-                            query.reset_minute();
+                        if hour != hour_start {
+                            query.reset_hour();
                         }
+                        let minute_start = query.minute_lower_bound();
                         let minute_range =
                             (Included(minute_start), Included(Minutes::inclusive_max()));
 
                         for minute in self.fields.minutes.ordinals().range(minute_range).cloned() {
-                            let second_start = query.second_lower_bound();
-                            if !self.fields.seconds.ordinals().contains(&second_start) {
-                                query.reset_second();
+                            *candidates_scanned += 1;
+                            if minute != minute_start {
+                                query.reset_minute();
                             }
+                            let second_start = query.second_lower_bound();
                             let second_range =
                                 (Included(second_start), Included(Seconds::inclusive_max()));
 
-                            for second in
-                                self.fields.seconds.ordinals().range(second_range).cloned()
+                            if let Some(second) =
+                                self.fields.seconds.ordinals().range(second_range).next()
                             {
-                                // NOTE: this is nanoseconds being passed in as seconds!
-                                let rem = *after % 1_000_000;
-                                let secs = ((*after - rem) / 1_000_000_000) + 1;
-                                let timezone =
-                                    Utc.timestamp_opt(secs as i64, 0).unwrap().timezone();
-                                let candidate = if let Some(candidate) = timezone
-                                    .with_ymd_and_hms(
-                                        year as i32,
-                                        month,
-                                        day_of_month,
-                                        hour,
-                                        minute,
-                                        second,
-                                    )
-                                    .single()
-                                {
-                                    candidate
-                                } else {
-                                    continue;
-                                };
-                                if !self
-                                    .fields
-                                    .days_of_week
-                                    .ordinals()
-                                    .contains(&candidate.weekday().number_from_sunday())
-                                {
-                                    continue 'day_loop;
-                                }
-                                return Some(candidate.timestamp_nanos_opt().unwrap() as u64);
+                                // Schedules are evaluated in UTC, which has no gaps or folds,
+                                // so the candidate can be computed from the start of its day.
+                                let candidate = day_start
+                                    + i64::from(hour) * 3_600
+                                    + i64::from(minute) * 60
+                                    + i64::from(*second);
+                                // Years late in 2554 no longer fit in u64 nanoseconds.
+                                return (candidate as u64).checked_mul(1_000_000_000).filter(
+                                    |candidate| until.is_none_or(|until| candidate <= until),
+                                );
                             }
                             query.reset_minute();
                         } // End of minutes range
@@ -121,40 +338,905 @@ impl Schedule {
                 } // End of Day of Month range
                 query.reset_month();
             } // End of Month range
+            query.reset_month();
         }
 
         // We ran out of dates to try.
         None
     }
 
+    /// Returns the last fire time strictly before `before`.
+    pub fn prev_before(&self, before: &u64) -> Option<u64> {
+        self.prev_matching(PrevBeforeQuery::before(before)?)
+    }
+
+    /// Returns the last fire time at or before `at`.
+    pub fn prev_at_or_before(&self, at: &u64) -> Option<u64> {
+        self.prev_matching(PrevBeforeQuery::at_or_before(at))
+    }
+
+    /// Provides an iterator over the fire times strictly before `before`, latest first. It ends
+    /// after the earliest fire time, in the first year of the schedule. The iterator only holds
+    /// the last fire time it yielded, so a clone of it can be kept as a cursor to resume from.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 12 * * * 1970").unwrap();
+    /// let hour = 3_600_000_000_000;
+    /// let day = 24 * hour;
+    /// let fire_times: Vec<u64> = schedule.upcoming_rev_from(&(2 * day)).collect();
+    /// assert_eq!(vec![day + 12 * hour, 12 * hour], fire_times);
+    /// ```
+    pub fn upcoming_rev_from(&self, before: &u64) -> ReverseScheduleIterator<'_> {
+        ReverseScheduleIterator {
+            schedule: self,
+            before: Some(*before),
+        }
+    }
+
+    /// Returns the last fire time at or before `at` together with the first one strictly after
+    /// it, for showing where `at` falls between two runs.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 * * * *").unwrap();
+    /// let hour = 3_600_000_000_000;
+    /// assert_eq!((Some(hour), Some(2 * hour)), schedule.surrounding(&(hour + 1)));
+    /// // On a fire time, that fire time is the previous one.
+    /// assert_eq!((Some(hour), Some(2 * hour)), schedule.surrounding(&hour));
+    /// ```
+    pub fn surrounding(&self, at: &u64) -> (Option<u64>, Option<u64>) {
+        (self.prev_at_or_before(at), self.next_after(at))
+    }
+
+    /// Returns true if `a` and `b` are both fire times and the schedule doesn't fire strictly
+    /// between them, that is if `b` is `next_after(a)`. Instants that aren't exactly a fire
+    /// time, including ones partway through a second the schedule fires in, return false rather
+    /// than an error, as does `b` at or before `a`.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 * * * *").unwrap();
+    /// let hour = 3_600_000_000_000;
+    /// assert!(schedule.are_adjacent(&hour, &(2 * hour)));
+    /// assert!(!schedule.are_adjacent(&hour, &(3 * hour)));
+    /// ```
+    pub fn are_adjacent(&self, a: &u64, b: &u64) -> bool {
+        self.next_at_or_after(a) == Some(*a) && self.next_after(a) == Some(*b)
+    }
+
+    /// Returns true if a fire time is within `tolerance` of `date_time`, either before or after
+    /// it, as checked with [surrounding](#method.surrounding). Unlike `includes`, a run that
+    /// started a little late or early still matches. A negative tolerance is the same as none.
+    /// # Example
+    /// ```
+    /// use chrono::{Duration, TimeZone, Utc};
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 9 * * *").unwrap();
+    /// let late = Utc.with_ymd_and_hms(2021, 6, 15, 9, 0, 40).unwrap();
+    /// assert!(!schedule.includes(late));
+    /// assert!(schedule.matches_within(late, Duration::minutes(1)));
+    /// ```
+    pub fn matches_within<Z>(&self, date_time: DateTime<Z>, tolerance: Duration) -> bool
+    where
+        Z: TimeZone,
+    {
+        let tolerance = i128::from(duration_nanos(&tolerance).unwrap_or(0));
+        let at = nanos_of(&date_time.with_timezone(&Utc));
+        let (prev, next) = self.surrounding(&(at.clamp(0, u64::MAX.into()) as u64));
+        // Instants out of range are clamped, so the fire times found may be on either side.
+        [prev, next]
+            .iter()
+            .flatten()
+            .any(|fire_time| (i128::from(*fire_time) - at).abs() <= tolerance)
+    }
+
+    // Scans backwards from the query's starting point, mirroring `next_matching`.
+    fn prev_matching(&self, mut query: PrevBeforeQuery) -> Option<u64> {
+        let year_start = query.year_upper_bound();
+        for year in self
+            .fields
+            .years
+            .ordinals()
+            .range(..=year_start)
+            .rev()
+            .cloned()
+        {
+            if year != year_start {
+                query.reset_month();
+            }
+            let month_start = query.month_upper_bound();
+            let month_range = (Included(Months::inclusive_min()), Included(month_start));
+            for month in self
+                .fields
+                .months
+                .ordinals()
+                .range(month_range)
+                .rev()
+                .cloned()
+            {
+                if month != month_start {
+                    query.reset_month();
+                }
+                let day_of_month_start = query.day_of_month_upper_bound(year, month);
+                let day_of_month_range = (
+                    Included(DaysOfMonth::inclusive_min()),
+                    Included(day_of_month_start.min(days_in_month(year, month))),
+                );
+
+                for day_of_month in self
+                    .fields
+                    .days_of_month
+                    .ordinals()
+                    .range(day_of_month_range)
+                    .rev()
+                    .cloned()
+                {
+                    if day_of_month != day_of_month_start {
+                        query.reset_day_of_month();
+                    }
+                    let date = NaiveDate::from_ymd_opt(year as i32, month, day_of_month)?;
+                    if !self.fields.includes_date(&date) {
+                        query.reset_day_of_month();
+                        continue;
+                    }
+                    let day_start = date.and_time(NaiveTime::MIN).and_utc().timestamp();
+
+                    let hour_start = query.hour_upper_bound();
+                    let hour_range = (Included(Hours::inclusive_min()), Included(hour_start));
+                    for hour in self
+                        .fields
+                        .hours
+                        .ordinals()
+                        .range(hour_range)
+                        .rev()
+                        .cloned()
+                    {
+                        if hour != hour_start {
+                            query.reset_hour();
+                        }
+                        let minute_start = query.minute_upper_bound();
+                        let minute_range =
+                            (Included(Minutes::inclusive_min()), Included(minute_start));
+
+                        for minute in self
+                            .fields
+                            .minutes
+                            .ordinals()
+                            .range(minute_range)
+                            .rev()
+                            .cloned()
+                        {
+                            if minute != minute_start {
+                                query.reset_minute();
+                            }
+                            let second_start = query.second_upper_bound();
+                            let second_range =
+                                (Included(Seconds::inclusive_min()), Included(second_start));
+
+                            if let Some(second) = self
+                                .fields
+                                .seconds
+                                .ordinals()
+                                .range(second_range)
+                                .next_back()
+                            {
+                                let candidate = day_start
+                                    + i64::from(hour) * 3_600
+                                    + i64::from(minute) * 60
+                                    + i64::from(*second);
+                                return (candidate as u64).checked_mul(1_000_000_000);
+                            }
+                            query.reset_minute();
+                        }
+                        query.reset_hour();
+                    }
+                    query.reset_day_of_month();
+                }
+                query.reset_month();
+            }
+            query.reset_month();
+        }
+
+        None
+    }
+
+    /// Returns the first fire time strictly after `after` that falls on a weekday, Monday through
+    /// Friday in UTC, passing over any fire times on the weekend. A weekend that the schedule
+    /// fires on is skipped as a whole, so this stays quick for schedules firing every second.
+    /// # Example
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 9 * * *").unwrap();
+    /// let friday = Utc.with_ymd_and_hms(2021, 6, 18, 12, 0, 0).unwrap();
+    /// let monday = Utc.with_ymd_and_hms(2021, 6, 21, 9, 0, 0).unwrap();
+    /// let friday = friday.timestamp_nanos_opt().unwrap() as u64;
+    /// assert_eq!(Some(monday), schedule.next_weekday_occurrence(&friday));
+    /// ```
+    pub fn next_weekday_occurrence(&self, after: &u64) -> Option<DateTime<Utc>> {
+        let mut candidate = self.next_after(after)?;
+        loop {
+            let date_time = Utc.timestamp_nanos(candidate as i64);
+            let days_to_monday = match date_time.weekday() {
+                Weekday::Sat => 2,
+                Weekday::Sun => 1,
+                _ => return Some(date_time),
+            };
+            let monday = (candidate / NANOS_PER_DAY + days_to_monday).checked_mul(NANOS_PER_DAY)?;
+            candidate = self.next_at_or_after(&monday)?;
+        }
+    }
+
+    /// Returns the first fire time strictly after `after` that is also a whole multiple of `grid`
+    /// since the Unix epoch, such as the next one on a five minute boundary. Rather than trying
+    /// every fire time, the search jumps from each grid boundary to the next fire time at or
+    /// after it, but a schedule that never lands on the grid is still searched to its end.
+    /// Returns `None` if `grid` isn't positive.
+    /// # Example
+    /// ```
+    /// use chrono::{Duration, TimeZone, Utc};
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 */3 * * * *").unwrap();
+    /// let after = Utc.with_ymd_and_hms(2021, 6, 15, 9, 0, 0).unwrap();
+    /// let after = after.timestamp_nanos_opt().unwrap() as u64;
+    /// let next = schedule.next_aligned_to(Duration::minutes(5), &after);
+    /// assert_eq!(Some(Utc.with_ymd_and_hms(2021, 6, 15, 9, 15, 0).unwrap()), next);
+    /// ```
+    pub fn next_aligned_to(&self, grid: Duration, after: &u64) -> Option<DateTime<Utc>> {
+        let grid = duration_nanos(&grid).filter(|grid| *grid > 0)?;
+        let mut candidate = self.next_after(after)?;
+        while !candidate.is_multiple_of(grid) {
+            let boundary = (candidate / grid + 1).checked_mul(grid)?;
+            candidate = self.next_at_or_after(&boundary)?;
+            // A fire time counts for its whole second, so one starting before a boundary partway
+            // through that second isn't on the grid.
+            if candidate < boundary {
+                candidate = self.next_after(&boundary)?;
+            }
+        }
+        Some(Utc.timestamp_nanos(candidate as i64))
+    }
+
+    /// Returns the first fire time strictly after `after` that falls outside every blackout
+    /// interval. Each interval is a `(start, end)` pair of nanosecond timestamps covering `start`
+    /// up to but not including `end`, so a fire time exactly at `end` is allowed. Intervals may
+    /// overlap and needn't be sorted.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 * * * *").unwrap();
+    /// let hour = 3_600_000_000_000;
+    /// // Skip the fire times at 01:00 and 02:00.
+    /// let maintenance = [(hour, 2 * hour + 1)];
+    /// assert_eq!(Some(3 * hour), schedule.next_excluding(&0, &maintenance));
+    /// ```
+    pub fn next_excluding(&self, after: &u64, blackouts: &[(u64, u64)]) -> Option<u64> {
+        let mut candidate = self.next_after(after)?;
+        while let Some(end) = blackouts
+            .iter()
+            .filter(|(start, end)| (*start..*end).contains(&candidate))
+            .map(|(_, end)| *end)
+            .max()
+        {
+            // The first fire time at or after the end of the blackout. The end is after the
+            // candidate, so this always makes progress.
+            candidate = self.next_after(&(end - 1))?;
+        }
+        Some(candidate)
+    }
+
+    /// Returns the first fire time of each of the next `n` UTC days on which the schedule fires,
+    /// starting after `after`. Days without a fire time are skipped, so the results may be more
+    /// than a day apart.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 9,17 * * Mon-Fri").unwrap();
+    /// let saturday = 1_623_456_000_000_000_000; // 2021-06-12 00:00:00 UTC
+    /// let days: Vec<u64> = schedule.next_n_days(5, &saturday).collect();
+    /// assert_eq!(5, days.len());
+    /// ```
+    pub fn next_n_days(&self, n: usize, after: &u64) -> impl Iterator<Item = u64> + '_ {
+        std::iter::successors(self.next_after(after), move |previous| {
+            self.next_at_or_after(&start_of_next_day(previous)?)
+        })
+        .take(n)
+    }
+
+    /// Lists the next `n` fire times, one per line in UTC with how long until each, for
+    /// diagnostics. See [explain_next_after](#method.explain_next_after) for the format.
+    pub fn explain_next(&self, n: usize) -> String {
+        self.explain_next_after(
+            n,
+            &(Utc::now().naive_utc().timestamp_nanos_opt().unwrap() as u64),
+        )
+    }
+
+    /// Like the `explain_next` method, but listing the fire times after `after` rather than now.
+    /// The first line names the schedule, the number of fire times found and the reference time,
+    /// and each fire time follows on its own indented line. The time until a fire time is rounded
+    /// down to the largest whole unit, from seconds up to days, so 2 hours and 30 minutes shows as
+    /// `in 2 hours`.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 30 9 * * *").unwrap();
+    /// let after = 1_623_744_000_000_000_000; // 2021-06-15 08:00:00 UTC
+    /// assert_eq!(
+    ///     "Next 2 fire times of `0 30 9 * * *` after 2021-06-15 08:00:00 UTC:\n  \
+    ///      2021-06-15 09:30:00 UTC  in 1 hour\n  \
+    ///      2021-06-16 09:30:00 UTC  in 1 day",
+    ///     schedule.explain_next_after(2, &after)
+    /// );
+    /// ```
+    pub fn explain_next_after(&self, n: usize, after: &u64) -> String {
+        let format = |nanos: u64| {
+            Utc.timestamp_nanos(nanos as i64)
+                .format("%Y-%m-%d %H:%M:%S UTC")
+                .to_string()
+        };
+        let fire_times: Vec<u64> = self.after(after).take(n).collect();
+        let count = match fire_times.len() {
+            0 => String::from("fire times"),
+            1 => String::from("fire time"),
+            count => format!("{} fire times", count),
+        };
+        let mut explanation = format!("Next {} of `{}` after {}:", count, self, format(*after));
+        if fire_times.is_empty() {
+            explanation.push_str("\n  none");
+        }
+        for fire_time in fire_times {
+            explanation.push_str(&format!(
+                "\n  {}  {}",
+                format(fire_time),
+                describe_time_until(fire_time - after)
+            ));
+        }
+        explanation
+    }
+
+    /// Iterates over the UTC days on which the schedule fires from now on, each with all of its
+    /// fire times that day, for an agenda grouped by day.
+    pub fn upcoming_grouped_by_day(&self) -> GroupedByDayIterator<'_> {
+        self.upcoming().grouped_by_day()
+    }
+
+    /// Like the `upcoming_grouped_by_day` method, but starting after `after` rather than now.
+    /// # Example
+    /// ```
+    /// use chrono::{NaiveDate, Timelike};
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 9,17 * * *").unwrap();
+    /// let (date, fire_times) = schedule.grouped_by_day_after(&0).next().unwrap();
+    /// assert_eq!(NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(), date);
+    /// let hours: Vec<u32> = fire_times.iter().map(|fire_time| fire_time.hour()).collect();
+    /// assert_eq!(vec![9, 17], hours);
+    /// ```
+    pub fn grouped_by_day_after(&self, after: &u64) -> GroupedByDayIterator<'_> {
+        self.after(after).grouped_by_day()
+    }
+
+    /// Iterates over the first fire time of each UTC day on which the schedule fires, from now
+    /// on, for jobs that should run at most once a day. Unlike
+    /// [next_n_days](#method.next_n_days) there is no limit on the number of days.
+    pub fn once_per_day(&self) -> DedupByIterator<'_> {
+        self.upcoming().dedup_by(Granularity::Day)
+    }
+
+    /// Like the `once_per_day` method, but starting after `after` rather than now.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 */10 9 * * *").unwrap();
+    /// let nine = 9 * 3_600_000_000_000;
+    /// let day = 24 * 3_600_000_000_000;
+    /// let fire_times: Vec<u64> = schedule.once_per_day_after(&0).take(3).collect();
+    /// assert_eq!(vec![nine, nine + day, nine + 2 * day], fire_times);
+    /// ```
+    pub fn once_per_day_after(&self, after: &u64) -> DedupByIterator<'_> {
+        self.after(after).dedup_by(Granularity::Day)
+    }
+
+    /// Returns true if the schedule fires within `window` from now, that is in
+    /// `(now, now + window]`. This only looks for the first fire time and never searches past the
+    /// end of the window.
+    pub fn fires_within(&self, window: Duration) -> bool {
+        self.fires_within_after(
+            &(Utc::now().naive_utc().timestamp_nanos_opt().unwrap() as u64),
+            window,
+        )
+    }
+
+    /// Like the `fires_within` method, but the window starts at `after` rather than the present.
+    /// # Example
+    /// ```
+    /// use chrono::Duration;
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("*/10 * * * * *").unwrap();
+    /// let on_the_minute = 1_623_758_400_000_000_000; // 2021-06-15 12:00:00 UTC
+    /// assert!(!schedule.fires_within_after(&on_the_minute, Duration::seconds(5)));
+    /// assert!(schedule.fires_within_after(&on_the_minute, Duration::seconds(15)));
+    /// ```
+    pub fn fires_within_after(&self, after: &u64, window: Duration) -> bool {
+        match duration_nanos(&window) {
+            Some(window) => self
+                .next_after_until(after, &after.saturating_add(window))
+                .is_some(),
+            None => false,
+        }
+    }
+
+    /// Returns the first instant in `(now, now + within]` at which both this schedule and `other`
+    /// fire, for detecting jobs that would run at the same time.
+    pub fn overlaps(&self, other: &Schedule, within: Duration) -> Option<u64> {
+        self.overlaps_after(
+            &(Utc::now().naive_utc().timestamp_nanos_opt().unwrap() as u64),
+            other,
+            within,
+        )
+    }
+
+    /// Like the `overlaps` method, but the horizon starts at `after` rather than the present.
+    /// # Example
+    /// ```
+    /// use chrono::Duration;
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let twice_a_day = Schedule::from_str("0 0 0,12 * * *").unwrap();
+    /// let at_noon = Schedule::from_str("0 0 12 * * *").unwrap();
+    /// let noon = 12 * 3_600_000_000_000;
+    /// assert_eq!(Some(noon), twice_a_day.overlaps_after(&0, &at_noon, Duration::days(1)));
+    /// assert_eq!(None, twice_a_day.overlaps_after(&0, &at_noon, Duration::hours(11)));
+    /// ```
+    pub fn overlaps_after(&self, after: &u64, other: &Schedule, within: Duration) -> Option<u64> {
+        let until = after.saturating_add(duration_nanos(&within)?);
+        let mut candidate = self.next_after_until(after, &until)?;
+        // Leapfrog between the schedules until they agree, like `ScheduleIntersection`, never
+        // looking past `until`.
+        let (mut leader, mut follower) = (self, other);
+        loop {
+            let next = follower.next_matching(
+                NextAfterQuery::at_or_after(&candidate),
+                Some(&until),
+                &mut 0,
+            )?;
+            if next == candidate {
+                return Some(candidate);
+            }
+            candidate = next;
+            std::mem::swap(&mut leader, &mut follower);
+        }
+    }
+
     /// Provides an iterator which will return each DateTime that matches the schedule starting with
     /// the current time if applicable.
-    pub fn upcoming(&self) -> ScheduleIterator {
+    pub fn upcoming(&self) -> ScheduleIterator<'_> {
         self.after(&(Utc::now().naive_utc().timestamp_nanos_opt().unwrap() as u64))
     }
 
+    /// Like the `upcoming` method, but yields each fire time as whole seconds since the Unix
+    /// epoch, as `DateTime::timestamp` would, for storing as an integer. Use
+    /// `after(after).timestamps()` to start from another time.
+    pub fn upcoming_timestamps(&self) -> impl Iterator<Item = i64> + '_ {
+        self.upcoming().timestamps()
+    }
+
+    /// Like `upcoming_timestamps`, but yields milliseconds since the Unix epoch, as
+    /// `DateTime::timestamp_millis` would.
+    pub fn upcoming_timestamps_millis(&self) -> impl Iterator<Item = i64> + '_ {
+        self.upcoming().timestamps_millis()
+    }
+
     /// Like the `upcoming` method, but allows you to specify a start time other than the present.
-    pub fn after(&self, after: &u64) -> ScheduleIterator {
-        ScheduleIterator::new(self, after)
+    pub fn after(&self, after: &u64) -> ScheduleIterator<'_> {
+        ScheduleIterator::new(self, after, false)
+    }
+
+    /// Like the `after` method, but the first fire time may be at `at` itself or earlier within
+    /// the same second. See [next_at_or_after](#method.next_at_or_after).
+    pub fn at_or_after(&self, at: &u64) -> ScheduleIterator<'_> {
+        ScheduleIterator::new(self, at, true)
+    }
+
+    /// Provides an iterator over the upcoming fire times, each delayed by a random amount from
+    /// zero up to but excluding `max`, so that many clients don't all fire at once. Jitter never
+    /// reorders fire times: one delayed past the next is followed by that next fire time at the
+    /// same instant rather than an earlier one. A `max` that isn't positive delays nothing.
+    #[cfg(feature = "rand")]
+    pub fn upcoming_jittered<'a, R>(
+        &'a self,
+        max: Duration,
+        rng: R,
+    ) -> impl Iterator<Item = u64> + 'a
+    where
+        R: rand::Rng + 'a,
+    {
+        self.after_jittered(
+            &(Utc::now().naive_utc().timestamp_nanos_opt().unwrap() as u64),
+            max,
+            rng,
+        )
+    }
+
+    /// Like the `upcoming_jittered` method, but allows you to specify a start time other than
+    /// the present.
+    /// # Example
+    /// ```
+    /// use chrono::Duration;
+    /// use cron_schedule::Schedule;
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 * * * *").unwrap();
+    /// let rng = StdRng::seed_from_u64(7);
+    /// let hour = 3_600_000_000_000;
+    /// let first = schedule.after_jittered(&0, Duration::minutes(5), rng).next().unwrap();
+    /// assert!(hour <= first && first < hour + 300_000_000_000);
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn after_jittered<'a, R>(
+        &'a self,
+        after: &u64,
+        max: Duration,
+        mut rng: R,
+    ) -> impl Iterator<Item = u64> + 'a
+    where
+        R: rand::Rng + 'a,
+    {
+        let max = duration_nanos(&max).unwrap_or(0);
+        let mut previous = 0;
+        self.after(after).map(move |fire_time| {
+            let jitter = if max > 0 { rng.gen_range(0..max) } else { 0 };
+            previous = fire_time.saturating_add(jitter).max(previous);
+            previous
+        })
+    }
+
+    /// Provides an iterator over the upcoming fire times for which `predicate` returns true. This
+    /// is the same as filtering `upcoming`: every fire time is still computed and then passed to
+    /// the predicate, so a predicate rejecting most fire times makes the iterator correspondingly
+    /// slower. Prefer narrowing the expression itself when the filter can be written as fields.
+    pub fn upcoming_filtered<'a, F>(&'a self, predicate: F) -> impl Iterator<Item = u64> + 'a
+    where
+        F: Fn(&DateTime<Utc>) -> bool + 'a,
+    {
+        self.after_filtered(
+            &(Utc::now().naive_utc().timestamp_nanos_opt().unwrap() as u64),
+            predicate,
+        )
+    }
+
+    /// Like the `upcoming_filtered` method, but allows you to specify a start time other than the
+    /// present.
+    /// # Example
+    /// ```
+    /// use chrono::{Datelike, Weekday};
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 9 * * *").unwrap();
+    /// let friday = 1_623_974_400_000_000_000; // 2021-06-18 00:00:00 UTC
+    /// let mut workdays = schedule.after_filtered(&friday, |date_time| {
+    ///     !matches!(date_time.weekday(), Weekday::Sat | Weekday::Sun)
+    /// });
+    /// assert_eq!(Some(friday + 9 * 3_600_000_000_000), workdays.next());
+    /// // Monday
+    /// assert_eq!(Some(friday + (3 * 24 + 9) * 3_600_000_000_000), workdays.next());
+    /// ```
+    pub fn after_filtered<'a, F>(
+        &'a self,
+        after: &u64,
+        predicate: F,
+    ) -> impl Iterator<Item = u64> + 'a
+    where
+        F: Fn(&DateTime<Utc>) -> bool + 'a,
+    {
+        self.after(after)
+            .filter(move |next_datetime| predicate(&Utc.timestamp_nanos(*next_datetime as i64)))
+    }
+
+    /// Provides an iterator over the fire times within `range`, such as `start..end`,
+    /// `start..=end` or `start..`. Each bound may be inclusive or exclusive. Without a start bound
+    /// the iterator starts from the present, like `upcoming`.
+    /// # Example
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 * * * *").unwrap();
+    /// let start = Utc.with_ymd_and_hms(2021, 6, 15, 9, 0, 0).unwrap();
+    /// let end = Utc.with_ymd_and_hms(2021, 6, 15, 12, 0, 0).unwrap();
+    /// assert_eq!(3, schedule.upcoming_in(start..end).count());
+    /// assert_eq!(4, schedule.upcoming_in(start..=end).count());
+    /// ```
+    pub fn upcoming_in<R>(&self, range: R) -> impl Iterator<Item = u64> + '_
+    where
+        R: RangeBounds<DateTime<Utc>>,
+    {
+        let (start, is_inclusive) = match range.start_bound() {
+            Included(start) => (nanos_of(start), true),
+            Excluded(start) => (nanos_of(start), false),
+            Unbounded => (
+                i128::from(Utc::now().naive_utc().timestamp_nanos_opt().unwrap()),
+                false,
+            ),
+        };
+        let end = range.end_bound().map(nanos_of);
+        // Fire times are never before the epoch, so an earlier start is the same as the epoch.
+        let mut iterator = if start < 0 {
+            ScheduleIterator::new(self, &0, true)
+        } else {
+            ScheduleIterator::new(self, &(start.min(u64::MAX.into()) as u64), is_inclusive)
+        };
+        iterator.is_done = start > u64::MAX.into();
+        iterator.take_while(move |next_datetime| match end {
+            Included(end) => i128::from(*next_datetime) <= end,
+            Excluded(end) => i128::from(*next_datetime) < end,
+            Unbounded => true,
+        })
+    }
+
+    /// Provides an iterator over the fire times within `within`, as for `upcoming_in`, coalescing
+    /// bursts into windows. Consecutive fire times at most `gap_threshold` apart are merged, and
+    /// each window is yielded as its first fire time, its last fire time and the number of fire
+    /// times in it. A negative threshold merges nothing.
+    /// # Example
+    /// ```
+    /// use chrono::{Duration, TimeZone, Utc};
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("*/10 0-1 9,17 * * *").unwrap();
+    /// let day = Utc.with_ymd_and_hms(2021, 6, 15, 0, 0, 0).unwrap();
+    /// let windows: Vec<(u64, u64, u64)> = schedule
+    ///     .fire_windows(Duration::seconds(10), day..day + Duration::days(1))
+    ///     .collect();
+    /// assert_eq!(2, windows.len());
+    /// assert_eq!(12, windows[0].2);
+    /// ```
+    pub fn fire_windows<R>(
+        &self,
+        gap_threshold: Duration,
+        within: R,
+    ) -> impl Iterator<Item = (u64, u64, u64)> + '_
+    where
+        R: RangeBounds<DateTime<Utc>>,
+    {
+        let gap_threshold = duration_nanos(&gap_threshold).unwrap_or(0);
+        let mut fire_times = self.upcoming_in(within).peekable();
+        std::iter::from_fn(move || {
+            let start = fire_times.next()?;
+            let (mut end, mut count) = (start, 1);
+            while let Some(next) = fire_times.next_if(|next| next - end <= gap_threshold) {
+                end = next;
+                count += 1;
+            }
+            Some((start, end, count))
+        })
+    }
+
+    /// Provides an iterator over the upcoming fire times at which `other` doesn't also fire. A
+    /// fire time that coincides with one of `other` is skipped entirely, for example to leave out
+    /// the run of an hourly job that would overlap the nightly backup.
+    pub fn except<'a>(&'a self, other: &'a Schedule) -> impl Iterator<Item = u64> + 'a {
+        self.except_after(
+            &(Utc::now().naive_utc().timestamp_nanos_opt().unwrap() as u64),
+            other,
+        )
+    }
+
+    /// Like the `except` method, but allows you to specify a start time other than the present.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let hourly = Schedule::from_str("0 0 * * * *").unwrap();
+    /// let backup = Schedule::from_str("0 0 2 * * *").unwrap();
+    /// let hour = 3_600_000_000_000;
+    /// let fire_times: Vec<u64> = hourly.except_after(&0, &backup).take(2).collect();
+    /// assert_eq!(vec![hour, 3 * hour], fire_times);
+    /// ```
+    pub fn except_after<'a>(
+        &'a self,
+        after: &u64,
+        other: &'a Schedule,
+    ) -> impl Iterator<Item = u64> + 'a {
+        self.after_filtered(after, move |date_time| !other.includes(*date_time))
+    }
+
+    /// Provides an iterator over the upcoming fire times outside the maintenance windows. Each
+    /// fire time of `maintenance` opens a window lasting `window`, which covers the maintenance
+    /// fire time itself up to but not including its end, and any fire time in a window is
+    /// skipped. A zero or negative window covers nothing; use [except](#method.except) to skip
+    /// only the fire times coinciding with `maintenance`.
+    pub fn suppress_during<'a>(
+        &'a self,
+        maintenance: &'a Schedule,
+        window: Duration,
+    ) -> impl Iterator<Item = u64> + 'a {
+        self.suppress_during_after(
+            &(Utc::now().naive_utc().timestamp_nanos_opt().unwrap() as u64),
+            maintenance,
+            window,
+        )
+    }
+
+    /// Like the `suppress_during` method, but allows you to specify a start time other than the
+    /// present.
+    /// # Example
+    /// ```
+    /// use chrono::Duration;
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let hourly = Schedule::from_str("0 0 * * * *").unwrap();
+    /// let maintenance = Schedule::from_str("0 0 2 * * *").unwrap();
+    /// let hour = 3_600_000_000_000;
+    /// let fire_times: Vec<u64> = hourly
+    ///     .suppress_during_after(&0, &maintenance, Duration::minutes(90))
+    ///     .take(2)
+    ///     .collect();
+    /// assert_eq!(vec![hour, 4 * hour], fire_times);
+    /// ```
+    pub fn suppress_during_after<'a>(
+        &'a self,
+        after: &u64,
+        maintenance: &'a Schedule,
+        window: Duration,
+    ) -> impl Iterator<Item = u64> + 'a {
+        let window = duration_nanos(&window).unwrap_or(0);
+        // Only the latest window opened by the fire time can contain it, as the others have
+        // opened earlier and last just as long.
+        self.after(after).filter(move |fire_time| {
+            maintenance
+                .prev_at_or_before(fire_time)
+                .is_none_or(|start| fire_time - start >= window)
+        })
+    }
+
+    /// Provides an iterator over the upcoming fire times that aren't on any of the `holidays`,
+    /// for business calendars. The dates are those of the fire times in UTC. Each fire time is
+    /// still computed and checked against the set, so a schedule firing every second spends a
+    /// whole day's worth of fire times getting past each holiday.
+    pub fn skipping_dates(&self, holidays: HashSet<NaiveDate>) -> impl Iterator<Item = u64> + '_ {
+        self.skipping_dates_after(
+            &(Utc::now().naive_utc().timestamp_nanos_opt().unwrap() as u64),
+            holidays,
+        )
+    }
+
+    /// Like the `skipping_dates` method, but allows you to specify a start time other than the
+    /// present.
+    /// # Example
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use cron_schedule::Schedule;
+    /// use std::collections::HashSet;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 0 * * *").unwrap();
+    /// let holidays = HashSet::from([NaiveDate::from_ymd_opt(1970, 1, 2).unwrap()]);
+    /// let day = 86_400_000_000_000;
+    /// let fire_times: Vec<u64> = schedule.skipping_dates_after(&0, holidays).take(2).collect();
+    /// assert_eq!(vec![2 * day, 3 * day], fire_times);
+    /// ```
+    pub fn skipping_dates_after(
+        &self,
+        after: &u64,
+        holidays: HashSet<NaiveDate>,
+    ) -> impl Iterator<Item = u64> + '_ {
+        self.after_filtered(after, move |date_time| {
+            !holidays.contains(&date_time.date_naive())
+        })
+    }
+
+    /// Provides an iterator which will return each fire time, matching the fields against local
+    /// time at a fixed `offset` from UTC rather than against UTC itself. The fire times are still
+    /// nanoseconds since the Unix epoch. Fixed offsets have no daylight saving time, so every local
+    /// time occurs exactly once.
+    pub fn upcoming_with_offset(&self, offset: FixedOffset) -> ScheduleIterator<'_> {
+        self.after_with_offset(
+            &(Utc::now().naive_utc().timestamp_nanos_opt().unwrap() as u64),
+            offset,
+        )
+    }
+
+    /// Like the `upcoming_with_offset` method, but allows you to specify a start time other than
+    /// the present.
+    /// # Example
+    /// ```
+    /// use chrono::FixedOffset;
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 9 * * *").unwrap();
+    /// let india = FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap();
+    /// let midnight_utc = 1_623_715_200_000_000_000; // 2021-06-15 00:00:00 UTC
+    /// let first = schedule.after_with_offset(&midnight_utc, india).next().unwrap();
+    /// // 09:00 at +05:30 is 03:30 UTC.
+    /// assert_eq!(midnight_utc + (3 * 3600 + 30 * 60) * 1_000_000_000, first);
+    /// ```
+    pub fn after_with_offset(&self, after: &u64, offset: FixedOffset) -> ScheduleIterator<'_> {
+        ScheduleIterator::with_offset(
+            self,
+            after,
+            false,
+            offset.local_minus_utc() as i64 * 1_000_000_000,
+        )
     }
 
+    /// Returns true if the schedule fires at `date_time`, ignoring fractions of a second. Each
+    /// field is looked up in its stored set, so this doesn't allocate and is cheap enough for hot
+    /// filters. The time of day is checked first, from the seconds up, since most schedules
+    /// restrict it the most, and the date is only worked out if the time matches.
     pub fn includes<Z>(&self, date_time: DateTime<Z>) -> bool
     where
         Z: TimeZone,
     {
-        self.fields.years.includes(date_time.year() as Ordinal)
-            && self.fields.months.includes(date_time.month() as Ordinal)
-            && self
-                .fields
-                .days_of_week
-                .includes(date_time.weekday().number_from_sunday())
-            && self
-                .fields
-                .days_of_month
-                .includes(date_time.day() as Ordinal)
-            && self.fields.hours.includes(date_time.hour() as Ordinal)
-            && self.fields.minutes.includes(date_time.minute() as Ordinal)
-            && self.fields.seconds.includes(date_time.second() as Ordinal)
+        let time = date_time.time();
+        if !(self.fields.seconds.includes(time.second())
+            && self.fields.minutes.includes(time.minute())
+            && self.fields.hours.includes(time.hour()))
+        {
+            return false;
+        }
+        let date = date_time.date_naive();
+        self.fields.days_of_month.includes(date.day())
+            && self.fields.months.includes(date.month())
+            && self.fields.years.includes(date.year() as Ordinal)
+            && self.fields.includes_date(&date)
+    }
+
+    /// Like `includes`, but matches the fields against the local time in `tz` at the instant
+    /// `date_time`, whatever zone `date_time` itself is in. The fire times of
+    /// [after_with_offset](#method.after_with_offset) are all included at that offset.
+    /// # Example
+    /// ```
+    /// use chrono::{FixedOffset, TimeZone, Utc};
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 9 * * *").unwrap();
+    /// let india = FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap();
+    /// let date_time = Utc.with_ymd_and_hms(2021, 6, 15, 3, 30, 0).unwrap();
+    /// assert!(schedule.includes_tz(&date_time, &india));
+    /// assert!(!schedule.includes(date_time));
+    /// ```
+    pub fn includes_tz<Z, Tz>(&self, date_time: &DateTime<Z>, tz: &Tz) -> bool
+    where
+        Z: TimeZone,
+        Tz: TimeZone,
+    {
+        self.includes(date_time.with_timezone(tz))
     }
 
     /// Returns a [TimeUnitSpec](trait.TimeUnitSpec.html) describing the years included
@@ -181,6 +1263,20 @@ impl Schedule {
         &self.fields.days_of_week
     }
 
+    /// Returns a [TimeUnitSpec](trait.TimeUnitSpec.html) describing the days of the year included
+    /// in this [Schedule](struct.Schedule.html). Unless the expression had a days of year field,
+    /// every day is included.
+    pub fn days_of_year(&self) -> &impl TimeUnitSpec {
+        &self.fields.days_of_year
+    }
+
+    /// Returns a [TimeUnitSpec](trait.TimeUnitSpec.html) describing the ISO weeks included in
+    /// this [Schedule](struct.Schedule.html). Unless the expression had a weeks field, every week
+    /// is included.
+    pub fn weeks_of_year(&self) -> &impl TimeUnitSpec {
+        &self.fields.weeks_of_year
+    }
+
     /// Returns a [TimeUnitSpec](trait.TimeUnitSpec.html) describing the hours of the day included
     /// in this [Schedule](struct.Schedule.html).
     pub fn hours(&self) -> &impl TimeUnitSpec {
@@ -199,143 +1295,3603 @@ impl Schedule {
         &self.fields.seconds
     }
 
-    pub fn timeunitspec_eq(&self, other: &Schedule) -> bool {
-        self.fields == other.fields
+    /// Returns a copy of this schedule whose years are replaced by `years`. The source of the new
+    /// schedule is regenerated from its fields.
+    /// # Example
+    /// ```
+    /// use cron_schedule::{Schedule, TimeUnitSpec, Years};
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("@yearly").unwrap();
+    /// let schedule = schedule.with_years(Years::from_range(2500, 2510).unwrap());
+    /// assert_eq!("0 0 0 1 1 * 2500-2510", schedule.to_string());
+    /// assert_eq!(11, schedule.years().count());
+    /// ```
+    pub fn with_years(&self, years: Years) -> Schedule {
+        let fields = ScheduleFields {
+            years,
+            explicit_years: true,
+            ..self.fields.clone()
+        };
+        self.with_fields(fields)
     }
-}
 
-impl Display for Schedule {
-    fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        write!(f, "{}", self.source)
+    /// Returns a copy of this schedule that only fires from year `min` through year `max`, such
+    /// as for a report on one decade. A schedule whose years are `*` gets the whole range, which
+    /// may extend up to [MAX_YEAR](constant.MAX_YEAR.html) like
+    /// [Years::from_range](struct.Years.html#method.from_range). Otherwise the schedule keeps
+    /// those of its years within the range. Returns an error if the range is invalid, as
+    /// `from_range` does, or if none of the schedule's years are in it.
+    /// # Example
+    /// ```
+    /// use cron_schedule::{Schedule, TimeUnitSpec};
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 0 1 1 *").unwrap();
+    /// let decade = schedule.with_year_range(2030, 2039).unwrap();
+    /// assert_eq!("0 0 0 1 1 * 2030-2039", decade.to_string());
+    /// let schedule = Schedule::from_str("0 0 0 1 1 * 2020/5").unwrap();
+    /// assert_eq!(2, schedule.with_year_range(2030, 2039).unwrap().years().count());
+    /// assert!(schedule.with_year_range(2039, 2030).is_err());
+    /// ```
+    pub fn with_year_range(&self, min: Ordinal, max: Ordinal) -> Result<Schedule, Error> {
+        let range = Years::from_range(min, max)?;
+        if self.fields.years.is_all() {
+            return Ok(self.with_years(range));
+        }
+        let years: OrdinalSet = self
+            .fields
+            .years
+            .iter()
+            .filter(|year| range.includes(*year))
+            .collect();
+        if years.is_empty() {
+            return Err(ErrorKind::Expression(format!(
+                "None of the years of {} are from {} through {}.",
+                self.source, min, max
+            ))
+            .into());
+        }
+        Ok(self.with_years(Years::from_ordinal_set(years)))
     }
-}
 
-impl PartialEq for Schedule {
-    fn eq(&self, other: &Schedule) -> bool {
-        self.source == other.source
+    /// Returns each field of the schedule with the unit it describes, from seconds through years,
+    /// followed by the days of the year if the expression had that field.
+    /// # Example
+    /// ```
+    /// use cron_schedule::{Schedule, UnitKind};
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 30 9 * * Mon-Fri").unwrap();
+    /// for (unit, field) in schedule.fields() {
+    ///     println!("{:?}: {} values", unit, field.count());
+    /// }
+    /// let (unit, hours) = schedule.fields().nth(2).unwrap();
+    /// assert_eq!(UnitKind::Hours, unit);
+    /// assert!(hours.includes(9));
+    /// ```
+    pub fn fields(&self) -> impl Iterator<Item = (UnitKind, &dyn TimeUnitSpec)> {
+        [
+            (UnitKind::Seconds, &self.fields.seconds as &dyn TimeUnitSpec),
+            (UnitKind::Minutes, &self.fields.minutes),
+            (UnitKind::Hours, &self.fields.hours),
+            (UnitKind::DaysOfMonth, &self.fields.days_of_month),
+            (UnitKind::Months, &self.fields.months),
+            (UnitKind::DaysOfWeek, &self.fields.days_of_week),
+            (UnitKind::Years, &self.fields.years),
+        ]
+        .into_iter()
+        .chain(self.fields.explicit_days_of_year.then_some((
+            UnitKind::DaysOfYear,
+            &self.fields.days_of_year as &dyn TimeUnitSpec,
+        )))
+        .chain(self.fields.explicit_weeks_of_year.then_some((
+            UnitKind::WeeksOfYear,
+            &self.fields.weeks_of_year as &dyn TimeUnitSpec,
+        )))
     }
-}
-
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct ScheduleFields {
-    years: Years,
-    days_of_week: DaysOfWeek,
-    months: Months,
-    days_of_month: DaysOfMonth,
-    hours: Hours,
-    minutes: Minutes,
-    seconds: Seconds,
-}
 
-impl ScheduleFields {
-    pub(crate) fn new(
-        seconds: Seconds,
-        minutes: Minutes,
-        hours: Hours,
-        days_of_month: DaysOfMonth,
-        months: Months,
-        days_of_week: DaysOfWeek,
-        years: Years,
-    ) -> ScheduleFields {
-        ScheduleFields {
-            years,
-            days_of_week,
-            months,
-            days_of_month,
-            hours,
-            minutes,
-            seconds,
+    /// Returns the field of the schedule describing `unit`. The days of the year and the ISO
+    /// weeks are returned even if the expression had no such field, matching every value.
+    /// # Example
+    /// ```
+    /// use cron_schedule::{Schedule, UnitKind};
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 30 9,12,15 * * Mon-Fri").unwrap();
+    /// assert_eq!(3, schedule.field(UnitKind::Hours).count());
+    /// assert!(schedule.field(UnitKind::WeeksOfYear).is_all());
+    /// ```
+    pub fn field(&self, unit: UnitKind) -> &dyn TimeUnitSpec {
+        match unit {
+            UnitKind::Seconds => &self.fields.seconds,
+            UnitKind::Minutes => &self.fields.minutes,
+            UnitKind::Hours => &self.fields.hours,
+            UnitKind::DaysOfMonth => &self.fields.days_of_month,
+            UnitKind::Months => &self.fields.months,
+            UnitKind::DaysOfWeek => &self.fields.days_of_week,
+            UnitKind::Years => &self.fields.years,
+            UnitKind::DaysOfYear => &self.fields.days_of_year,
+            UnitKind::WeeksOfYear => &self.fields.weeks_of_year,
         }
     }
-}
 
-pub struct ScheduleIterator<'a> {
-    is_done: bool,
-    schedule: &'a Schedule,
-    previous_datetime: u64,
-}
+    /// Describes the values of the field for `unit` in English, for a hint next to the field.
+    /// Hours are written with AM and PM, days of the month as ordinals, and months and days of
+    /// the week by name. Runs of three or more values read as a range.
+    /// # Example
+    /// ```
+    /// use cron_schedule::{Schedule, UnitKind};
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 30 9,12,15 1,15 May-Aug Mon,Wed,Fri").unwrap();
+    /// assert_eq!("at 9 AM, 12 PM, and 3 PM", schedule.describe_field(UnitKind::Hours));
+    /// assert_eq!("on the 1st and 15th", schedule.describe_field(UnitKind::DaysOfMonth));
+    /// assert_eq!("every year", schedule.describe_field(UnitKind::Years));
+    /// ```
+    pub fn describe_field(&self, unit: UnitKind) -> String {
+        crate::describe::describe_field(unit, self.field(unit))
+    }
 
-impl<'a> ScheduleIterator<'a> {
-    fn new(schedule: &'a Schedule, starting_datetime: &u64) -> ScheduleIterator<'a> {
+    /// Returns a copy of this schedule whose seconds are replaced by `seconds`. The source of the
+    /// new schedule is regenerated from its fields.
+    /// # Example
+    /// ```
+    /// use cron_schedule::{Schedule, Seconds, TimeUnitField};
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("*/5 30 9 * * Mon-Fri").unwrap();
+    /// let schedule = schedule.with_seconds(Seconds::from_ordinal(0));
+    /// assert_eq!("0 30 9 * * 2-6", schedule.to_string());
+    /// ```
+    pub fn with_seconds(&self, seconds: Seconds) -> Schedule {
+        self.with_fields(ScheduleFields {
+            seconds,
+            ..self.fields.clone()
+        })
+    }
+
+    /// Like `with_seconds`, but replaces the minutes.
+    pub fn with_minutes(&self, minutes: Minutes) -> Schedule {
+        self.with_fields(ScheduleFields {
+            minutes,
+            ..self.fields.clone()
+        })
+    }
+
+    /// Like `with_seconds`, but replaces the hours.
+    pub fn with_hours(&self, hours: Hours) -> Schedule {
+        self.with_fields(ScheduleFields {
+            hours,
+            ..self.fields.clone()
+        })
+    }
+
+    /// Like `with_seconds`, but replaces the days of the month.
+    pub fn with_days_of_month(&self, days_of_month: DaysOfMonth) -> Schedule {
+        self.with_fields(ScheduleFields {
+            days_of_month,
+            ..self.fields.clone()
+        })
+    }
+
+    /// Like `with_seconds`, but replaces the months.
+    pub fn with_months(&self, months: Months) -> Schedule {
+        self.with_fields(ScheduleFields {
+            months,
+            ..self.fields.clone()
+        })
+    }
+
+    /// Like `with_seconds`, but replaces the days of the week.
+    pub fn with_days_of_week(&self, days_of_week: DaysOfWeek) -> Schedule {
+        self.with_fields(ScheduleFields {
+            days_of_week,
+            ..self.fields.clone()
+        })
+    }
+
+    /// Returns a copy of this schedule that keeps its times of day but fires on every day, with
+    /// all of its date fields replaced by `*`. Together with `date_only`, this splits a schedule
+    /// into parts that can be recombined, such as the times of one schedule on the days of
+    /// another. Custom directives are returned unchanged.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 30 9,12,15 1,15 May-Aug Mon,Wed,Fri 2021/2").unwrap();
+    /// assert_eq!("0 30 9,12,15 * * * *", schedule.time_only().to_string());
+    /// ```
+    pub fn time_only(&self) -> Schedule {
+        if self.fields.custom_directive {
+            return self.clone();
+        }
+        self.with_fields(ScheduleFields {
+            years: Years::all(),
+            days_of_week: DaysOfWeek::all(),
+            months: Months::all(),
+            days_of_month: DaysOfMonth::all(),
+            days_of_year: DaysOfYear::all(),
+            weeks_of_year: WeeksOfYear::all(),
+            ..self.fields.clone()
+        })
+    }
+
+    /// Returns a copy of this schedule that keeps its dates but fires once on each of them, at
+    /// midnight, with its seconds, minutes and hours replaced by `0`. Custom directives are
+    /// returned unchanged.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 30 9,12,15 1,15 May-Aug Mon,Wed,Fri").unwrap();
+    /// assert_eq!("0 0 0 1,15 5-8 2,4,6", schedule.date_only().to_string());
+    /// ```
+    pub fn date_only(&self) -> Schedule {
+        if self.fields.custom_directive {
+            return self.clone();
+        }
+        self.with_fields(ScheduleFields {
+            hours: Hours::from_ordinal(0),
+            minutes: Minutes::from_ordinal(0),
+            seconds: Seconds::from_ordinal(0),
+            ..self.fields.clone()
+        })
+    }
+
+    fn with_fields(&self, fields: ScheduleFields) -> Schedule {
+        let expression = fields.to_expression();
+        let syntax_extensions = ExtensionFlags {
+            any: expression.contains('?'),
+            ..ExtensionFlags::default()
+        };
+        Schedule::new(expression, fields.with_syntax_extensions(syntax_extensions))
+    }
+
+    /// Returns the dialect of the expression this schedule was parsed from. Shorthands such as
+    /// `@daily` are `Cron`.
+    /// # Example
+    /// ```
+    /// use cron_schedule::{Dialect, Hours, ParseOptions, Schedule, TimeUnitField};
+    ///
+    /// let options = ParseOptions::new().no_seconds(true);
+    /// let schedule = Schedule::parse_with("*/15 9 * * Mon-Fri", &options).unwrap();
+    /// assert_eq!(Dialect::Unix, schedule.dialect());
+    /// let schedule = schedule.with_hours(Hours::from_ordinal(10));
+    /// assert_eq!("0,15,30,45 10 * * 2-6", schedule.to_string());
+    /// ```
+    pub fn dialect(&self) -> Dialect {
+        self.fields.dialect
+    }
+
+    /// Writes the schedule out in the `Unix` dialect, as read by
+    /// [ParseOptions::no_seconds](struct.ParseOptions.html#method.no_seconds). `?` becomes `*`,
+    /// and the seconds are left out if the schedule only fires at the start of the minute.
+    /// Otherwise they're kept, giving the six field equivalent.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 30 9 ? * Mon-Fri").unwrap();
+    /// assert_eq!("30 9 * * 2-6", schedule.to_unix_string());
+    /// ```
+    pub fn to_unix_string(&self) -> String {
+        self.fields
+            .clone()
+            .with_dialect(Dialect::Unix, false, false)
+            .to_expression()
+    }
+
+    /// Writes the schedule out in the `Quartz` dialect. The days of the month or of the week
+    /// that were `?` stay so, otherwise `?` stands in for whichever of them matches any day,
+    /// preferring the days of the week.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 30 9 1 * *").unwrap();
+    /// assert_eq!("0 30 9 1 * ?", schedule.to_quartz_string());
+    /// ```
+    pub fn to_quartz_string(&self) -> String {
+        let fields = &self.fields;
+        let (any_days_of_month, any_days_of_week) =
+            if fields.any_days_of_month || fields.any_days_of_week {
+                (fields.any_days_of_month, fields.any_days_of_week)
+            } else if fields.days_of_week.is_all() {
+                (false, true)
+            } else {
+                (fields.days_of_month.is_all(), false)
+            };
+        fields
+            .clone()
+            .with_dialect(Dialect::Quartz, any_days_of_month, any_days_of_week)
+            .to_expression()
+    }
+
+    /// Returns the schedule rewritten in a canonical form, so that schedules firing at the same
+    /// times normalize to the same source whichever way they were written. The expression is in
+    /// the `Quartz` dialect with all seven fields, written with numbers rather than names, and
+    /// `?` stands for the days of the week if they match any day, otherwise for the days of the
+    /// month if those do. A days of year or ISO weeks field is kept. Custom directives are
+    /// returned unchanged.
+    /// # Example
+    /// ```
+    /// use cron_schedule::{Dialect, ParseOptions, Schedule};
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 30 9 * * Mon-Fri").unwrap();
+    /// let options = ParseOptions::new().no_seconds(true);
+    /// let unix = Schedule::parse_with("30 9 ? * 2-6", &options).unwrap();
+    /// assert_eq!("0 30 9 ? * 2-6 *", schedule.normalize().to_string());
+    /// assert_eq!(schedule.normalize(), unix.normalize());
+    /// assert_eq!(Dialect::Quartz, unix.normalize().dialect());
+    /// ```
+    pub fn normalize(&self) -> Schedule {
+        let fields = &self.fields;
+        if fields.custom_directive {
+            return self.clone();
+        }
+        let any_days_of_week = fields.days_of_week.is_all();
+        let any_days_of_month = !any_days_of_week && fields.days_of_month.is_all();
+        self.with_fields(fields.clone().with_explicit_years(true).with_dialect(
+            Dialect::Quartz,
+            any_days_of_month,
+            any_days_of_week,
+        ))
+    }
+
+    /// Splits the schedule into its seven fields, from the seconds through the years, for
+    /// editing each on its own. Fields are written with numbers rather than names, and a field
+    /// matching every value as `*`, so the years are `*` unless they were narrowed. Years stepping
+    /// through the end of the span are written as a period such as `2018/2`, rather than listing
+    /// each year.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 30 9,12,15 1,15 May-Aug Mon,Wed,Fri").unwrap();
+    /// assert_eq!(
+    ///     ["0", "30", "9,12,15", "1,15", "5-8", "2,4,6", "*"],
+    ///     schedule.as_cron_fields()
+    /// );
+    /// ```
+    pub fn as_cron_fields(&self) -> [String; 7] {
+        let fields = &self.fields;
+        [
+            fields.seconds.to_field_string(),
+            fields.minutes.to_field_string(),
+            fields.hours.to_field_string(),
+            fields.days_of_month.to_field_string(),
+            fields.months.to_field_string(),
+            fields.days_of_week.to_field_string(),
+            match fields.years.as_period() {
+                Some((start, step)) if step > 1 => format!("{}/{}", start, step),
+                _ => fields.years.to_field_string(),
+            },
+        ]
+    }
+
+    /// Returns true if the expression this schedule was parsed from had a years field.
+    /// Shorthands such as `@daily` don't.
+    pub fn has_explicit_years(&self) -> bool {
+        self.fields.explicit_years
+    }
+
+    /// Returns the number of fields in the expression this schedule was parsed from: 8 if it
+    /// had a days of year field, 7 if it had a years field, otherwise 6. An ISO weeks field adds
+    /// one, and the `Unix` dialect without seconds has one fewer. Shorthands such as `@daily`
+    /// count as 6 fields.
+    pub fn fields_count(&self) -> usize {
+        let fields_count = if self.fields.explicit_days_of_year {
+            8
+        } else if self.has_explicit_years() {
+            7
+        } else {
+            6
+        } + usize::from(self.fields.explicit_weeks_of_year);
+        if self.fields.omits_seconds() {
+            fields_count - 1
+        } else {
+            fields_count
+        }
+    }
+
+    /// Returns the finest unit of time the schedule distinguishes, from the lowest of its
+    /// seconds, minutes and hours fields to include more than one value.
+    /// # Example
+    /// ```
+    /// use cron_schedule::{Granularity, Schedule};
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 * * * *").unwrap();
+    /// assert_eq!(Granularity::Hour, schedule.granularity());
+    /// let schedule = Schedule::from_str("0 * * * * *").unwrap();
+    /// assert_eq!(Granularity::Minute, schedule.granularity());
+    /// ```
+    pub fn granularity(&self) -> Granularity {
+        if self.fields.seconds.count() > 1 {
+            Granularity::Second
+        } else if self.fields.minutes.count() > 1 {
+            Granularity::Minute
+        } else if self.fields.hours.count() > 1 {
+            Granularity::Hour
+        } else {
+            Granularity::Day
+        }
+    }
+
+    /// Classifies how often the schedule fires from which fields it restricts. The date fields
+    /// decide first: schedules restricting only the days of the week, or matching every day, fire
+    /// daily or weekly, or more often according to their [granularity](#method.granularity).
+    /// One day of the month, and optionally one month, gives `Monthly` or `Yearly` if the schedule
+    /// fires once that day. Anything else is `Irregular`, including schedules firing several
+    /// times on one day of the month. The years are ignored.
+    /// # Example
+    /// ```
+    /// use cron_schedule::{FrequencyClass, Schedule};
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 */15 9-17 * * Mon-Fri").unwrap();
+    /// assert_eq!(FrequencyClass::Minutely, schedule.frequency_class());
+    /// let schedule = Schedule::from_str("0 0 9 1 * *").unwrap();
+    /// assert_eq!(FrequencyClass::Monthly, schedule.frequency_class());
+    /// ```
+    pub fn frequency_class(&self) -> FrequencyClass {
+        let fields = &self.fields;
+        if fields.custom_directive || fields.years.count() == 0 {
+            return FrequencyClass::Irregular;
+        }
+        let every_day_of_month = fields.days_of_month.is_all()
+            && fields.months.is_all()
+            && fields.days_of_year.is_all()
+            && fields.weeks_of_year.is_all();
+        if every_day_of_month {
+            return match self.granularity() {
+                Granularity::Second => FrequencyClass::SubMinute,
+                Granularity::Minute => FrequencyClass::Minutely,
+                Granularity::Hour => FrequencyClass::Hourly,
+                Granularity::Day if fields.days_of_week.count() == 1 => FrequencyClass::Weekly,
+                Granularity::Day => FrequencyClass::Daily,
+            };
+        }
+        let one_day_of_month = fields.days_of_month.count() == 1
+            && fields.days_of_week.is_all()
+            && fields.days_of_year.is_all()
+            && fields.weeks_of_year.is_all();
+        match (one_day_of_month, self.granularity()) {
+            (true, Granularity::Day) if fields.months.is_all() => FrequencyClass::Monthly,
+            (true, Granularity::Day) if fields.months.count() == 1 => FrequencyClass::Yearly,
+            _ => FrequencyClass::Irregular,
+        }
+    }
+
+    /// Returns the number of times the schedule fires on a day it fires at all. The time of day
+    /// fields are the same every day, so this only depends on the seconds, minutes and hours. Days
+    /// excluded by the date fields don't fire at all, so the average over all days is lower.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0,30 9-17 * * Mon-Fri").unwrap();
+    /// assert_eq!(18, schedule.fire_times_per_day());
+    /// ```
+    pub fn fire_times_per_day(&self) -> u64 {
+        u64::from(self.fields.seconds.count())
+            * u64::from(self.fields.minutes.count())
+            * u64::from(self.fields.hours.count())
+    }
+
+    /// Returns the shortest time between two consecutive fire times, or `None` if the schedule
+    /// fires at most once. The times of day are the same every day, so this is the shortest gap
+    /// either between two times on the same day, or from the last time on one day the schedule
+    /// fires to the first time on the next. Days are compared within the first eight years the
+    /// schedule fires in, so years far apart or schedules firing only once every eight years or
+    /// more aren't considered.
+    /// # Example
+    /// ```
+    /// use chrono::Duration;
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0,45 9 * * *").unwrap();
+    /// assert_eq!(Some(Duration::minutes(45)), schedule.shortest_interval());
+    /// let schedule = Schedule::from_str("0 0 23 * * Mon,Tue").unwrap();
+    /// assert_eq!(Some(Duration::days(1)), schedule.shortest_interval());
+    /// ```
+    pub fn shortest_interval(&self) -> Option<Duration> {
+        const SECONDS_PER_DAY: i64 = 86_400;
+        // The first day the schedule fires on, looking through its years in turn.
+        let first_day = self.fields.years.ordinals().iter().find_map(|year| {
+            let start = NaiveDate::from_ymd_opt(*year as i32, 1, 1)?;
+            std::iter::successors(Some(start), NaiveDate::succ_opt)
+                .take_while(|date| date.year() == *year as i32)
+                .find(|date| self.fires_on(date))
+        })?;
+        let end = NaiveDate::from_ymd_opt(first_day.year() + 8, 1, 1)?;
+        let mut days = std::iter::successors(Some(first_day), NaiveDate::succ_opt)
+            .take_while(|date| *date < end)
+            .filter(|date| self.fires_on(date));
+        let mut previous_day = days.next()?;
+        let mut shortest_day_gap: Option<i64> = None;
+        for day in days {
+            let gap = (day - previous_day).num_days();
+            shortest_day_gap = Some(shortest_day_gap.map_or(gap, |shortest| shortest.min(gap)));
+            if gap == 1 {
+                break;
+            }
+            previous_day = day;
+        }
+
+        let seconds: Vec<i64> = self
+            .times_of_day()
+            .iter()
+            .map(|time| i64::from(time.num_seconds_from_midnight()))
+            .collect();
+        let within_day = seconds.windows(2).map(|pair| pair[1] - pair[0]).min();
+        let across_days = match (shortest_day_gap, seconds.first(), seconds.last()) {
+            (Some(days), Some(first), Some(last)) => Some(days * SECONDS_PER_DAY - last + first),
+            _ => None,
+        };
+        within_day
+            .into_iter()
+            .chain(across_days)
+            .min()
+            .map(Duration::seconds)
+    }
+
+    /// Returns the number of fire times strictly after `after`, without iterating over them.
+    /// Each day is only checked once, so this is quick even for schedules firing every second.
+    /// Schedules that don't restrict their years are treated as never ending and return `None`,
+    /// even though they stop after the default span.
+    /// # Example
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 9 1 * * 2030").unwrap();
+    /// let july = Utc.with_ymd_and_hms(2030, 7, 1, 12, 0, 0).unwrap();
+    /// let july = july.timestamp_nanos_opt().unwrap() as u64;
+    /// assert_eq!(Some(5), schedule.count_remaining(&july));
+    /// assert_eq!(None, Schedule::from_str("@daily").unwrap().count_remaining(&0));
+    /// ```
+    pub fn count_remaining(&self, after: &u64) -> Option<u64> {
+        if self.fields.years.is_all() {
+            return None;
+        }
+        self.count_seconds_between(after / NANOS_PER_SECOND + 1, u64::MAX / NANOS_PER_SECOND)
+    }
+
+    /// Returns the number of fire times from `start` up to but excluding `end`, counted day by
+    /// day like `count_remaining` rather than by iterating over them. Returns `None` if `end` is
+    /// before `start`, or if the count can't be represented, instead of wrapping. An empty range,
+    /// or one entirely before the epoch, counts `Some(0)`.
+    /// # Example
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 9 * * Mon-Fri").unwrap();
+    /// let start = Utc.with_ymd_and_hms(2021, 6, 1, 0, 0, 0).unwrap();
+    /// let end = Utc.with_ymd_and_hms(2021, 7, 1, 0, 0, 0).unwrap();
+    /// assert_eq!(Some(22), schedule.count_between(start, end));
+    /// assert_eq!(None, schedule.count_between(end, start));
+    /// ```
+    pub fn count_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Option<u64> {
+        if end < start {
+            return None;
+        }
+        match window_seconds(&start, &end) {
+            Some((first, last)) => self.count_seconds_between(first, last),
+            None => Some(0),
+        }
+    }
+
+    /// Summarizes the fire times from `start` up to but excluding `end`: how many there are and
+    /// the shortest, longest and mean gaps between consecutive ones, computed in a single pass
+    /// over `upcoming_in(start..end)`.
+    /// # Example
+    /// ```
+    /// use chrono::{Duration, TimeZone, Utc};
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 9,12 * * *").unwrap();
+    /// let start = Utc.with_ymd_and_hms(2021, 6, 1, 0, 0, 0).unwrap();
+    /// let stats = schedule.interval_stats(start, start + Duration::days(2));
+    /// assert_eq!(4, stats.count);
+    /// assert_eq!(Some(Duration::hours(3)), stats.min);
+    /// assert_eq!(Some(Duration::hours(21)), stats.max);
+    /// assert_eq!(Some(Duration::hours(9)), stats.mean);
+    /// ```
+    pub fn interval_stats(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> IntervalStats {
+        let mut count = 0;
+        let mut first = None;
+        let mut previous: Option<u64> = None;
+        let (mut min, mut max) = (None, None);
+        for fire_time in self.upcoming_in(start..end) {
+            count += 1;
+            if let Some(previous) = previous {
+                let gap = fire_time - previous;
+                min = Some(min.map_or(gap, |min: u64| min.min(gap)));
+                max = Some(max.map_or(gap, |max: u64| max.max(gap)));
+            }
+            first.get_or_insert(fire_time);
+            previous = Some(fire_time);
+        }
+        let to_duration = |nanos: u64| Duration::nanoseconds(nanos.min(i64::MAX as u64) as i64);
+        let mean = match (first, previous) {
+            (Some(first), Some(last)) if count > 1 => Some((last - first) / (count - 1)),
+            _ => None,
+        };
+        IntervalStats {
+            count,
+            min: min.map(to_duration),
+            max: max.map(to_duration),
+            mean: mean.map(to_duration),
+        }
+    }
+
+    // Whether the date fields match `date`, so the schedule fires at each of its times of day.
+    fn fires_on(&self, date: &NaiveDate) -> bool {
+        self.fields.years.includes(date.year() as Ordinal)
+            && self.fields.months.includes(date.month())
+            && self.fields.days_of_month.includes(date.day())
+            && self.fields.includes_date(date)
+    }
+
+    // Counts the fire times from second `first` through second `last` since the epoch,
+    // checking each day once rather than iterating over the fire times. Returns `None` if the
+    // count overflows.
+    fn count_seconds_between(&self, first: u64, last: u64) -> Option<u64> {
+        let years = self.fields.years.ordinals();
+        let (first_year, last_year) = match (years.first(), years.last()) {
+            (Some(first_year), Some(last_year)) => (*first_year as i32, *last_year as i32),
+            _ => return Some(0),
+        };
+        let to_date_time = |secs: u64| {
+            DateTime::from_timestamp(secs as i64, 0)
+                .unwrap()
+                .naive_utc()
+        };
+        let (first, last) = (to_date_time(first), to_date_time(last));
+        let times = self.times_of_day();
+        let per_day = times.len() as u64;
+        let mut date = match NaiveDate::from_ymd_opt(first_year, 1, 1) {
+            Some(first_day) => first_day.max(first.date()),
+            None => first.date(),
+        };
+        let mut count: u64 = 0;
+        while date.year() <= last_year && date <= last.date() {
+            if self.fires_on(&date) {
+                // Only the first and the last day can be partial.
+                let lower = if date == first.date() {
+                    Included(first.time())
+                } else {
+                    Unbounded
+                };
+                let upper = if date == last.date() {
+                    Included(last.time())
+                } else {
+                    Unbounded
+                };
+                count = count.checked_add(match (lower, upper) {
+                    (Unbounded, Unbounded) => per_day,
+                    bounds => times.range(bounds).count() as u64,
+                })?;
+            }
+            date = match date.succ_opt() {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        Some(count)
+    }
+
+    /// Returns the fire times from `start` up to but excluding `end`, like collecting
+    /// `upcoming_in(start..end)`. The fire times are counted first, day by day, so the vector is
+    /// allocated once at its final size, which helps when exporting dense schedules.
+    /// # Example
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 9 * * Mon-Fri").unwrap();
+    /// let start = Utc.with_ymd_and_hms(2021, 6, 1, 0, 0, 0).unwrap();
+    /// let end = Utc.with_ymd_and_hms(2021, 7, 1, 0, 0, 0).unwrap();
+    /// assert_eq!(22, schedule.fire_times_vec(start, end).len());
+    /// ```
+    pub fn fire_times_vec(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let (first, last) = match window_seconds(&start, &end) {
+            Some(window) => window,
+            None => return Vec::new(),
+        };
+        let count = self.count_seconds_between(first, last).unwrap_or(0);
+        let mut fire_times = Vec::with_capacity(count as usize);
+        fire_times.extend(
+            self.upcoming_in(start..end)
+                .map(|fire_time| Utc.timestamp_nanos(fire_time as i64)),
+        );
+        fire_times
+    }
+
+    /// Returns `n` fire times from `start` up to but excluding `end`, each drawn uniformly from
+    /// the fire times in the window with replacement, so the same one may be returned more than
+    /// once. The fire times are counted first, day by day, and the draws are then picked out in a
+    /// single pass over the window, so they come back sorted. A window without fire times gives
+    /// an empty vector whatever `n` is.
+    /// # Example
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use cron_schedule::Schedule;
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 */5 * * * *").unwrap();
+    /// let start = Utc.with_ymd_and_hms(2021, 6, 1, 0, 0, 0).unwrap();
+    /// let end = Utc.with_ymd_and_hms(2021, 6, 2, 0, 0, 0).unwrap();
+    /// let samples = schedule.sample_between(start, end, 10, StdRng::seed_from_u64(7));
+    /// assert_eq!(10, samples.len());
+    /// assert!(samples.iter().all(|sample| schedule.includes(*sample)));
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn sample_between<R: rand::Rng>(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        n: usize,
+        mut rng: R,
+    ) -> Vec<DateTime<Utc>> {
+        let count = match window_seconds(&start, &end) {
+            Some((first, last)) => self.count_seconds_between(first, last).unwrap_or(0),
+            None => 0,
+        };
+        if count == 0 {
+            return Vec::new();
+        }
+        let mut indices: Vec<u64> = (0..n).map(|_| rng.gen_range(0..count)).collect();
+        indices.sort_unstable();
+        let mut samples = Vec::with_capacity(n);
+        let mut fire_times = self.upcoming_in(start..end).zip(0u64..);
+        let mut current = fire_times.next();
+        for index in indices {
+            while matches!(current, Some((_, position)) if position < index) {
+                current = fire_times.next();
+            }
+            match current {
+                Some((fire_time, _)) => samples.push(Utc.timestamp_nanos(fire_time as i64)),
+                None => break,
+            }
+        }
+        samples
+    }
+
+    /// Returns the times of day the schedule fires at on any day it fires at all, from the
+    /// seconds, minutes and hours fields. There are at most 86,400 of them, one per second of
+    /// the day, see [fire_times_per_day](#method.fire_times_per_day) for just the count.
+    /// # Example
+    /// ```
+    /// use chrono::NaiveTime;
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 30 9,12,15 * * *").unwrap();
+    /// let times: Vec<NaiveTime> = schedule.times_of_day().into_iter().collect();
+    /// assert_eq!(NaiveTime::from_hms_opt(9, 30, 0).unwrap(), times[0]);
+    /// assert_eq!(3, times.len());
+    /// ```
+    pub fn times_of_day(&self) -> BTreeSet<NaiveTime> {
+        let mut times = BTreeSet::new();
+        for hour in self.fields.hours.ordinals() {
+            for minute in self.fields.minutes.ordinals() {
+                for second in self.fields.seconds.ordinals() {
+                    times.insert(NaiveTime::from_hms_opt(*hour, *minute, *second).unwrap());
+                }
+            }
+        }
+        times
+    }
+
+    /// Returns the directive, such as `@startup`, if the schedule was parsed from one with
+    /// [ParseOptions::allow_custom_directives](struct.ParseOptions.html#method.allow_custom_directives).
+    /// Such schedules never fire, it's up to the caller to act on the directive.
+    pub fn custom_directive(&self) -> Option<&str> {
+        self.fields.custom_directive.then_some(self.source.as_str())
+    }
+
+    /// Returns the parts of the schedule that plain Unix cron can't express.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("*/10 0 12 ? * * 2030").unwrap();
+    /// let extensions = schedule.extensions();
+    /// assert!(extensions.seconds && extensions.years && extensions.any);
+    /// assert!(!extensions.last_day_of_week);
+    /// ```
+    pub fn extensions(&self) -> ExtensionFlags {
+        ExtensionFlags {
+            seconds: self
+                .fields
+                .seconds
+                .ordinals()
+                .iter()
+                .any(|second| *second != 0),
+            years: !self.fields.years.is_all(),
+            days_of_year: !self.fields.days_of_year.is_all(),
+            weeks_of_year: !self.fields.weeks_of_year.is_all(),
+            ..self.fields.syntax_extensions
+        }
+    }
+
+    /// Returns true if the schedule uses anything plain Unix cron can't express, see
+    /// [extensions](#method.extensions).
+    pub fn uses_extensions(&self) -> bool {
+        !self.extensions().is_empty()
+    }
+
+    pub(crate) fn warnings(&self) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        if self.fields.seconds.is_all() {
+            warnings.push(Warning::EverySecond);
+        }
+        warnings
+    }
+
+    pub fn timeunitspec_eq(&self, other: &Schedule) -> bool {
+        self.fields == other.fields
+    }
+
+    /// Returns true if the two schedules match the same values in every field, however they
+    /// were written, unlike `==`, which compares the expressions. Names and numbers, ranges and
+    /// lists, and `?` and `*` are interchangeable, so `0 0 9 ? * MON-FRI` fires at the same times
+    /// as `0 0 9 * * 2-6`. The fields are compared rather than the fire times, so schedules that
+    /// never fire, such as on February 30th, needn't be the same as each other.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let weekdays = Schedule::from_str("0 0 9 ? * MON-FRI").unwrap();
+    /// assert!(weekdays.same_times_as(&Schedule::from_str("0 0 9 * * 2-6").unwrap()));
+    /// assert!(!weekdays.same_times_as(&Schedule::from_str("0 0 9 * * 1-5").unwrap()));
+    /// ```
+    pub fn same_times_as(&self, other: &Schedule) -> bool {
+        self.timeunitspec_eq(other)
+    }
+}
+
+impl Display for Schedule {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl PartialEq for Schedule {
+    fn eq(&self, other: &Schedule) -> bool {
+        self.source == other.source
+    }
+}
+
+/// Removes the schedules firing at the same times as an earlier one, as compared by
+/// [timeunitspec_eq](struct.Schedule.html#method.timeunitspec_eq), keeping the first of each in
+/// its original position. Custom directives never fire, so they only repeat one another when they
+/// are the same directive.
+/// # Example
+/// ```
+/// use cron_schedule::{dedupe, Schedule};
+/// use std::str::FromStr;
+///
+/// let schedules = ["0 0 9 * * *", "0 0 17 * * *", "0 0 9 * * * *"]
+///     .iter()
+///     .map(|expression| Schedule::from_str(expression).unwrap())
+///     .collect();
+/// let schedules = dedupe(schedules);
+/// assert_eq!(2, schedules.len());
+/// assert_eq!("0 0 17 * * *", schedules[1].to_string());
+/// ```
+pub fn dedupe(schedules: Vec<Schedule>) -> Vec<Schedule> {
+    let mut seen = HashSet::new();
+    let mut unique = Vec::with_capacity(schedules.len());
+    for schedule in schedules {
+        let directive = schedule.custom_directive().map(str::to_owned);
+        if seen.insert((schedule.fields.clone(), directive)) {
+            unique.push(schedule);
+        }
+    }
+    unique
+}
+
+// Writes out a wait of `nanos` nanoseconds in its largest whole unit, such as `in 2 hours`.
+fn describe_time_until(nanos: u64) -> String {
+    let seconds = nanos / NANOS_PER_SECOND;
+    let units = [
+        (86_400, "day"),
+        (3_600, "hour"),
+        (60, "minute"),
+        (1, "second"),
+    ];
+    match units
+        .iter()
+        .find(|(unit_seconds, _)| seconds >= *unit_seconds)
+    {
+        Some((unit_seconds, name)) => {
+            let count = seconds / unit_seconds;
+            let plural = if count == 1 { "" } else { "s" };
+            format!("in {} {}{}", count, name, plural)
+        }
+        None => String::from("in under a second"),
+    }
+}
+
+#[derive(Clone, Debug, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct ScheduleFields {
+    years: Years,
+    days_of_week: DaysOfWeek,
+    months: Months,
+    days_of_month: DaysOfMonth,
+    hours: Hours,
+    minutes: Minutes,
+    seconds: Seconds,
+    days_of_year: DaysOfYear,
+    weeks_of_year: WeeksOfYear,
+    // Whether the expression had a seventh, years, field. This only affects how the fields are
+    // written out, not which instants they match, so it's ignored when comparing.
+    explicit_years: bool,
+    // Whether the expression had an eighth, days of year, field.
+    explicit_days_of_year: bool,
+    // Whether the expression ended with a `W` field of ISO weeks.
+    explicit_weeks_of_year: bool,
+    // Extensions that only show in how the expression was written, such as `?`. These are lost
+    // when the fields are written out again.
+    syntax_extensions: ExtensionFlags,
+    // Whether the expression was a custom `@word` directive, which never fires.
+    custom_directive: bool,
+    // How the expression was written, so regenerated expressions look the same: the dialect,
+    // and whether the days of the month and of the week were `?`.
+    dialect: Dialect,
+    any_days_of_month: bool,
+    any_days_of_week: bool,
+}
+
+impl PartialEq for ScheduleFields {
+    fn eq(&self, other: &ScheduleFields) -> bool {
+        self.years == other.years
+            && self.days_of_week == other.days_of_week
+            && self.months == other.months
+            && self.days_of_month == other.days_of_month
+            && self.hours == other.hours
+            && self.minutes == other.minutes
+            && self.seconds == other.seconds
+            && self.days_of_year == other.days_of_year
+            && self.weeks_of_year == other.weeks_of_year
+    }
+}
+
+// Consistent with `eq`, only the values matched are hashed.
+impl Hash for ScheduleFields {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.years.ordinals().hash(state);
+        self.days_of_week.ordinals().hash(state);
+        self.months.ordinals().hash(state);
+        self.days_of_month.ordinals().hash(state);
+        self.hours.ordinals().hash(state);
+        self.minutes.ordinals().hash(state);
+        self.seconds.ordinals().hash(state);
+        self.days_of_year.ordinals().hash(state);
+        self.weeks_of_year.ordinals().hash(state);
+    }
+}
+
+impl ScheduleFields {
+    pub(crate) fn new(
+        seconds: Seconds,
+        minutes: Minutes,
+        hours: Hours,
+        days_of_month: DaysOfMonth,
+        months: Months,
+        days_of_week: DaysOfWeek,
+        years: Years,
+    ) -> ScheduleFields {
+        ScheduleFields {
+            years,
+            days_of_week,
+            months,
+            days_of_month,
+            hours,
+            minutes,
+            seconds,
+            days_of_year: DaysOfYear::all(),
+            weeks_of_year: WeeksOfYear::all(),
+            explicit_years: false,
+            explicit_days_of_year: false,
+            explicit_weeks_of_year: false,
+            syntax_extensions: ExtensionFlags::default(),
+            custom_directive: false,
+            dialect: Dialect::Cron,
+            any_days_of_month: false,
+            any_days_of_week: false,
+        }
+    }
+
+    /// Fields for a custom directive. Without any years, they never match.
+    pub(crate) fn custom_directive() -> ScheduleFields {
+        ScheduleFields {
+            custom_directive: true,
+            ..ScheduleFields::new(
+                Seconds::all(),
+                Minutes::all(),
+                Hours::all(),
+                DaysOfMonth::all(),
+                Months::all(),
+                DaysOfWeek::all(),
+                Years::from_ordinal_set(OrdinalSet::new()),
+            )
+        }
+    }
+
+    /// Replaces the years with `years` if they cover all of the default span.
+    pub(crate) fn with_default_years(self, years: &Years) -> ScheduleFields {
+        if !self.years.is_all() {
+            return self;
+        }
+        ScheduleFields {
+            years: years.clone(),
+            ..self
+        }
+    }
+
+    pub(crate) fn with_syntax_extensions(
+        self,
+        syntax_extensions: ExtensionFlags,
+    ) -> ScheduleFields {
+        ScheduleFields {
+            syntax_extensions,
+            ..self
+        }
+    }
+
+    pub(crate) fn with_dialect(
+        self,
+        dialect: Dialect,
+        any_days_of_month: bool,
+        any_days_of_week: bool,
+    ) -> ScheduleFields {
+        ScheduleFields {
+            dialect,
+            any_days_of_month,
+            any_days_of_week,
+            ..self
+        }
+    }
+
+    // Unix expressions have no seconds field, which stands for firing at second 0.
+    fn omits_seconds(&self) -> bool {
+        self.dialect == Dialect::Unix && self.seconds.ordinals().iter().eq([0].iter())
+    }
+
+    pub(crate) fn with_days_of_year(self, days_of_year: DaysOfYear) -> ScheduleFields {
+        ScheduleFields {
+            days_of_year,
+            explicit_years: true,
+            explicit_days_of_year: true,
+            ..self
+        }
+    }
+
+    pub(crate) fn with_weeks_of_year(self, weeks_of_year: WeeksOfYear) -> ScheduleFields {
+        ScheduleFields {
+            weeks_of_year,
+            explicit_weeks_of_year: true,
+            ..self
+        }
+    }
+
+    pub(crate) fn has_explicit_days_of_year(&self) -> bool {
+        self.explicit_days_of_year
+    }
+
+    // The date fields that can't be enumerated by the matcher are checked once per day.
+    fn includes_date(&self, date: &NaiveDate) -> bool {
+        self.days_of_week
+            .includes(date.weekday().number_from_sunday())
+            && self.days_of_year.includes(date.ordinal())
+            && self.weeks_of_year.includes(date.iso_week().week())
+    }
+
+    pub(crate) fn with_explicit_years(self, explicit_years: bool) -> ScheduleFields {
+        ScheduleFields {
+            explicit_years,
+            ..self
+        }
+    }
+
+    /// Renders the fields as a cron expression, including the years field only if the fields
+    /// were parsed with one or their years were replaced. A days of year field is always
+    /// preceded by the years, and an ISO weeks field always comes last. The expression is in the
+    /// dialect the fields were parsed from, so `Unix` fields leave out the seconds unless they
+    /// were replaced, and `?` is kept for days that still match any day.
+    pub(crate) fn to_expression(&self) -> String {
+        let any_or = |any: bool, is_all: bool, field: String| {
+            if any && is_all {
+                String::from("?")
+            } else {
+                field
+            }
+        };
+        let mut fields = vec![
+            self.seconds.to_field_string(),
+            self.minutes.to_field_string(),
+            self.hours.to_field_string(),
+            any_or(
+                self.any_days_of_month,
+                self.days_of_month.is_all(),
+                self.days_of_month.to_field_string(),
+            ),
+            self.months.to_field_string(),
+            any_or(
+                self.any_days_of_week,
+                self.days_of_week.is_all(),
+                self.days_of_week.to_field_string(),
+            ),
+        ];
+        if self.omits_seconds() {
+            fields.remove(0);
+        }
+        if self.explicit_years {
+            fields.push(self.years.to_field_string());
+        }
+        if self.explicit_days_of_year {
+            fields.push(self.days_of_year.to_field_string());
+        }
+        if self.explicit_weeks_of_year {
+            fields.push(format!("W{}", self.weeks_of_year.to_field_string()));
+        }
+        fields.join(" ")
+    }
+}
+
+pub struct ScheduleIterator<'a> {
+    is_done: bool,
+    is_inclusive: bool,
+    schedule: &'a Schedule,
+    // The schedule is matched against local time, `offset_nanos` ahead of UTC. The previous fire
+    // time is kept in local time and converted back when yielded.
+    offset_nanos: i64,
+    previous_datetime: u64,
+}
+
+impl<'a> ScheduleIterator<'a> {
+    fn new(
+        schedule: &'a Schedule,
+        starting_datetime: &u64,
+        is_inclusive: bool,
+    ) -> ScheduleIterator<'a> {
+        ScheduleIterator::with_offset(schedule, starting_datetime, is_inclusive, 0)
+    }
+
+    fn with_offset(
+        schedule: &'a Schedule,
+        starting_datetime: &u64,
+        is_inclusive: bool,
+        offset_nanos: i64,
+    ) -> ScheduleIterator<'a> {
         ScheduleIterator {
             is_done: false,
+            is_inclusive,
             schedule,
-            previous_datetime: *starting_datetime,
+            offset_nanos,
+            previous_datetime: starting_datetime.saturating_add_signed(offset_nanos),
+        }
+    }
+}
+
+impl<'a> ScheduleIterator<'a> {
+    /// Wraps the iterator so that each fire time is paired with the number of candidates the
+    /// matcher examined to find it, counting every day and every minute it considered. Sparse
+    /// schedules, such as one firing on Friday the 13th, scan far more candidates per fire time
+    /// than dense ones.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 0 13 * Fri").unwrap();
+    /// let (_fire_time, candidates_scanned) =
+    ///     schedule.after(&0).with_candidates_scanned().next().unwrap();
+    /// assert!(candidates_scanned > 1);
+    /// ```
+    pub fn with_candidates_scanned(self) -> CandidatesScannedIterator<'a> {
+        CandidatesScannedIterator { iterator: self }
+    }
+
+    /// Wraps the iterator so that it yields each fire time as a date and time at the offset the
+    /// iterator was created with, such as by
+    /// [after_with_offset](struct.Schedule.html#method.after_with_offset), or at UTC otherwise.
+    /// The offset is kept rather than normalized to UTC, so RFC 3339 output shows local time.
+    /// # Example
+    /// ```
+    /// use chrono::FixedOffset;
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 30 9 * * *").unwrap();
+    /// let india = FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap();
+    /// let fire_time = schedule.after_with_offset(&0, india).date_times().next().unwrap();
+    /// assert_eq!("1970-01-01T09:30:00+05:30", fire_time.to_rfc3339());
+    /// ```
+    pub fn date_times(self) -> DateTimesIterator<'a> {
+        let offset = FixedOffset::east_opt((self.offset_nanos / NANOS_PER_SECOND as i64) as i32)
+            .expect("the offset came from a FixedOffset");
+        DateTimesIterator {
+            iterator: self,
+            offset,
+        }
+    }
+
+    /// Wraps the iterator so that it yields each fire time as whole seconds since the Unix epoch,
+    /// the same as `DateTime::timestamp` of the fire time.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 * * * *").unwrap();
+    /// let timestamps: Vec<i64> = schedule.after(&0).timestamps().take(2).collect();
+    /// assert_eq!(vec![3_600, 7_200], timestamps);
+    /// ```
+    pub fn timestamps(self) -> impl Iterator<Item = i64> + 'a {
+        self.map(|fire_time| (fire_time / NANOS_PER_SECOND) as i64)
+    }
+
+    /// Like `timestamps`, but yields milliseconds since the Unix epoch, the same as
+    /// `DateTime::timestamp_millis` of the fire time. Fire times fall on whole seconds, so these
+    /// are always multiples of 1,000.
+    pub fn timestamps_millis(self) -> impl Iterator<Item = i64> + 'a {
+        self.map(|fire_time| (fire_time / 1_000_000) as i64)
+    }
+
+    /// Wraps the iterator so that it yields each UTC day the schedule fires on together with all
+    /// of its fire times that day. Each day is yielded once the first fire time of the next one
+    /// has been found, so the fire times are computed one day ahead rather than all at once.
+    pub fn grouped_by_day(self) -> GroupedByDayIterator<'a> {
+        GroupedByDayIterator {
+            iterator: self,
+            pending: None,
+        }
+    }
+
+    /// Wraps the iterator so that it yields at most one fire time, the first, per minute, hour or
+    /// day, for a coarser view of a dense schedule. The later fire times in each unit are skipped
+    /// rather than computed. Units are in UTC, or in the offset the iterator was created with,
+    /// and `Granularity::Second` leaves the fire times unchanged.
+    /// # Example
+    /// ```
+    /// use cron_schedule::{Granularity, Schedule};
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("* * * * * *").unwrap();
+    /// let minute = 60_000_000_000;
+    /// let fire_times = schedule.after(&0).dedup_by(Granularity::Minute);
+    /// let fire_times: Vec<u64> = fire_times.take(3).collect();
+    /// assert_eq!(vec![1_000_000_000, minute, 2 * minute], fire_times);
+    /// ```
+    pub fn dedup_by(self, unit: Granularity) -> DedupByIterator<'a> {
+        let unit_nanos = match unit {
+            Granularity::Second => NANOS_PER_SECOND,
+            Granularity::Minute => 60 * NANOS_PER_SECOND,
+            Granularity::Hour => 3_600 * NANOS_PER_SECOND,
+            Granularity::Day => NANOS_PER_DAY,
+        };
+        DedupByIterator {
+            iterator: self,
+            unit_nanos,
+        }
+    }
+
+    /// Wraps the iterator so that each fire time is logged at the debug level under `target`
+    /// as it is yielded. Requires the `log` feature.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    /// use std::str::FromStr;
+    ///
+    /// let schedule = Schedule::from_str("0 0 * * * *").unwrap();
+    /// for _fire_time in schedule.after(&0).inspect_log("scheduler").take(3) {
+    ///     // Run the job
+    /// }
+    /// ```
+    #[cfg(feature = "log")]
+    pub fn inspect_log(self, target: &'a str) -> InspectLogIterator<'a> {
+        InspectLogIterator {
+            iterator: self,
+            target,
+        }
+    }
+
+    fn next_counted(&mut self, candidates_scanned: &mut usize) -> Option<u64> {
+        if self.is_done {
+            return None;
+        }
+        loop {
+            // Only the starting point may be inclusive, later calls continue after the last result.
+            let query = if self.is_inclusive {
+                self.is_inclusive = false;
+                NextAfterQuery::at_or_after(&self.previous_datetime)
+            } else {
+                NextAfterQuery::from(&self.previous_datetime)
+            };
+            let Some(next_datetime) = self.schedule.next_matching(query, None, candidates_scanned)
+            else {
+                self.is_done = true;
+                return None;
+            };
+            self.previous_datetime = next_datetime;
+            // Local fire times just after the epoch may be before it in UTC, skip those.
+            if let Some(next_datetime) = next_datetime.checked_add_signed(-self.offset_nanos) {
+                return Some(next_datetime);
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for ScheduleIterator<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.next_counted(&mut 0)
+    }
+}
+
+/// Yields each fire time of a [ScheduleIterator](struct.ScheduleIterator.html) together with the
+/// number of candidates scanned to find it.
+pub struct CandidatesScannedIterator<'a> {
+    iterator: ScheduleIterator<'a>,
+}
+
+impl<'a> Iterator for CandidatesScannedIterator<'a> {
+    type Item = (u64, usize);
+
+    fn next(&mut self) -> Option<(u64, usize)> {
+        let mut candidates_scanned = 0;
+        self.iterator
+            .next_counted(&mut candidates_scanned)
+            .map(|next_datetime| (next_datetime, candidates_scanned))
+    }
+}
+
+/// Yields the fire times of a schedule before an instant, latest first, see
+/// [Schedule::upcoming_rev_from](struct.Schedule.html#method.upcoming_rev_from).
+#[derive(Clone, Debug)]
+pub struct ReverseScheduleIterator<'a> {
+    schedule: &'a Schedule,
+    // The next fire time is strictly before this, or `None` once there are none left.
+    before: Option<u64>,
+}
+
+impl<'a> Iterator for ReverseScheduleIterator<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.before = self.schedule.prev_before(&self.before?);
+        self.before
+    }
+}
+
+impl<'a> FusedIterator for ReverseScheduleIterator<'a> {}
+
+/// Yields the fire times of a [ScheduleIterator](struct.ScheduleIterator.html) at its offset from
+/// UTC, see [date_times](struct.ScheduleIterator.html#method.date_times).
+pub struct DateTimesIterator<'a> {
+    iterator: ScheduleIterator<'a>,
+    offset: FixedOffset,
+}
+
+impl<'a> Iterator for DateTimesIterator<'a> {
+    type Item = DateTime<FixedOffset>;
+
+    fn next(&mut self) -> Option<DateTime<FixedOffset>> {
+        let next_datetime = self.iterator.next()?;
+        Some(
+            Utc.timestamp_nanos(next_datetime as i64)
+                .with_timezone(&self.offset),
+        )
+    }
+}
+
+/// Yields the first fire time of a [ScheduleIterator](struct.ScheduleIterator.html) in each unit
+/// of time, see [dedup_by](struct.ScheduleIterator.html#method.dedup_by).
+pub struct DedupByIterator<'a> {
+    iterator: ScheduleIterator<'a>,
+    unit_nanos: u64,
+}
+
+impl<'a> Iterator for DedupByIterator<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let next_datetime = self.iterator.next()?;
+        // Continue from the start of the next unit, kept in local time like the fire time.
+        let unit_nanos = self.unit_nanos;
+        match (self.iterator.previous_datetime / unit_nanos + 1).checked_mul(unit_nanos) {
+            Some(next_unit) => {
+                self.iterator.previous_datetime = next_unit;
+                self.iterator.is_inclusive = true;
+            }
+            None => self.iterator.is_done = true,
+        }
+        Some(next_datetime)
+    }
+}
+
+/// Yields the fire times of a [ScheduleIterator](struct.ScheduleIterator.html) grouped by UTC
+/// day, see [grouped_by_day](struct.ScheduleIterator.html#method.grouped_by_day).
+pub struct GroupedByDayIterator<'a> {
+    iterator: ScheduleIterator<'a>,
+    // The first fire time of the next day, found while finishing the previous one.
+    pending: Option<u64>,
+}
+
+impl<'a> Iterator for GroupedByDayIterator<'a> {
+    type Item = (NaiveDate, Vec<DateTime<Utc>>);
+
+    fn next(&mut self) -> Option<(NaiveDate, Vec<DateTime<Utc>>)> {
+        let first = match self.pending.take() {
+            Some(first) => first,
+            None => self.iterator.next()?,
+        };
+        let day = first / NANOS_PER_DAY;
+        let mut fire_times = vec![Utc.timestamp_nanos(first as i64)];
+        for fire_time in self.iterator.by_ref() {
+            if fire_time / NANOS_PER_DAY != day {
+                self.pending = Some(fire_time);
+                break;
+            }
+            fire_times.push(Utc.timestamp_nanos(fire_time as i64));
+        }
+        Some((fire_times[0].date_naive(), fire_times))
+    }
+}
+
+/// Logs each fire time of a [ScheduleIterator](struct.ScheduleIterator.html) as it is yielded.
+#[cfg(feature = "log")]
+pub struct InspectLogIterator<'a> {
+    iterator: ScheduleIterator<'a>,
+    target: &'a str,
+}
+
+#[cfg(feature = "log")]
+impl<'a> Iterator for InspectLogIterator<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let next_datetime = self.iterator.next()?;
+        log::debug!(
+            target: self.target,
+            "Schedule '{}' fires at {}",
+            self.iterator.schedule,
+            next_datetime
+        );
+        Some(next_datetime)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+    use std::str::FromStr;
+
+    const MONTH_NAMES: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    const DAY_NAMES: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+    /// Generates a single valid specifier for a unit whose ordinals run from `min` to `max`,
+    /// optionally also using the unit's names.
+    fn arb_specifier(min: u32, max: u32, names: &'static [&'static str]) -> BoxedStrategy<String> {
+        let point = (min..=max).prop_map(|point| point.to_string());
+        let range = (min..=max, min..=max)
+            .prop_map(|(a, b)| format!("{}-{}", a.min(b), a.max(b)))
+            .boxed();
+        let period_start = prop_oneof![Just("*".to_owned()), point.clone(), range.clone()];
+        let period = (period_start, 1..=max - min + 1)
+            .prop_map(|(start, step)| format!("{}/{}", start, step));
+        let mut specifiers = vec![
+            Just("*".to_owned()).boxed(),
+            point.boxed(),
+            range,
+            period.boxed(),
+        ];
+        if !names.is_empty() {
+            let named_point = (0..names.len()).prop_map(move |i| names[i].to_owned());
+            let named_range = (0..names.len(), 0..names.len())
+                .prop_map(move |(a, b)| format!("{}-{}", names[a.min(b)], names[a.max(b)]));
+            specifiers.push(named_point.boxed());
+            specifiers.push(named_range.boxed());
+        }
+        proptest::strategy::Union::new(specifiers).boxed()
+    }
+
+    /// Generates a comma separated list of one to three specifiers.
+    fn arb_field(min: u32, max: u32, names: &'static [&'static str]) -> BoxedStrategy<String> {
+        proptest::collection::vec(arb_specifier(min, max, names), 1..=3)
+            .prop_map(|specifiers| specifiers.join(","))
+            .boxed()
+    }
+
+    /// Generates a valid six or seven field expression.
+    fn arb_expression() -> impl Strategy<Value = String> {
+        (
+            arb_field(0, 59, &[]),
+            arb_field(0, 59, &[]),
+            arb_field(0, 23, &[]),
+            arb_field(1, 31, &[]),
+            arb_field(1, 12, &MONTH_NAMES),
+            arb_field(1, 7, &DAY_NAMES),
+            proptest::option::of(arb_field(1970, 2100, &[])),
+        )
+            .prop_map(|(s, m, h, dom, mon, dow, years)| {
+                let mut fields = vec![s, m, h, dom, mon, dow];
+                fields.extend(years);
+                fields.join(" ")
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_rendered_fields_round_trip(expression in arb_expression()) {
+            let schedule = Schedule::from_str(&expression).unwrap();
+            let rendered = schedule.fields.to_expression();
+            let reparsed = Schedule::from_str(&rendered).unwrap();
+            prop_assert!(
+                schedule.timeunitspec_eq(&reparsed),
+                "{} rendered as {}", expression, rendered
+            );
+            prop_assert_eq!(schedule.fields_count(), reparsed.fields_count());
+
+            let after = Utc
+                .with_ymd_and_hms(2021, 6, 15, 14, 29, 36)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap() as u64;
+            let expected: Vec<u64> = schedule.after(&after).take(5).collect();
+            let actual: Vec<u64> = reparsed.after(&after).take(5).collect();
+            prop_assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn test_zero_step_is_invalid() {
+        assert!(Schedule::from_str("*/0 * * * * *").is_err());
+        assert!(Schedule::from_str("0 0 0 1-5/0 * *").is_err());
+    }
+
+    #[test]
+    fn test_next_duration() {
+        let expression = "0 5,13,40-42 17 1 Jan *";
+        let schedule = Schedule::from_str(expression).unwrap();
+        let next = schedule.next_after(&(Utc::now().timestamp_nanos_opt().unwrap() as u64));
+        println!("NEXT DURATION------- for {} {:?}", expression, next);
+        assert!(next.is_some());
+    }
+
+    #[test]
+    fn test_next_after() {
+        let expression = "0 5,13,40-42 17 1 Jan *";
+        let schedule = Schedule::from_str(expression).unwrap();
+        let next = schedule.next_after(&(Utc::now().timestamp_nanos_opt().unwrap() as u64));
+        println!("NEXT AFTER for {} {:?}", expression, next);
+        assert!(next.is_some());
+    }
+
+    #[test]
+    fn test_upcoming_utc() {
+        let expression = "0 0,30 0,6,12,18 1,15 Jan-March Thurs";
+        let schedule = Schedule::from_str(expression).unwrap();
+        let mut upcoming = schedule.upcoming();
+        let next1 = upcoming.next();
+        assert!(next1.is_some());
+        let next2 = upcoming.next();
+        assert!(next2.is_some());
+        let next3 = upcoming.next();
+        assert!(next3.is_some());
+        println!("Upcoming 1 for {} {:?}", expression, next1);
+        println!("Upcoming 2 for {} {:?}", expression, next2);
+        println!("Upcoming 3 for {} {:?}", expression, next3);
+    }
+
+    #[test]
+    fn test_fractional_second_reference() {
+        let schedule = Schedule::from_str("0 0 12 * * *").unwrap();
+        let noon = Utc
+            .with_ymd_and_hms(2021, 6, 15, 12, 0, 0)
+            .unwrap()
+            .timestamp_nanos_opt()
+            .unwrap() as u64;
+        let next_noon = noon + 86_400 * 1_000_000_000;
+
+        // Exactly on the fire time.
+        assert_eq!(Some(noon), schedule.next_at_or_after(&noon));
+        assert_eq!(Some(next_noon), schedule.next_after(&noon));
+
+        // Partway through the fire second.
+        let quarter_past = noon + 250_000_000;
+        assert_eq!(Some(noon), schedule.next_at_or_after(&quarter_past));
+        assert_eq!(Some(next_noon), schedule.next_after(&quarter_past));
+        let last_nanosecond = noon + 999_999_999;
+        assert_eq!(Some(noon), schedule.next_at_or_after(&last_nanosecond));
+
+        // Once the fire second is over, it's skipped in both modes.
+        let just_past = noon + 1_000_000_000;
+        assert_eq!(Some(next_noon), schedule.next_at_or_after(&just_past));
+        assert_eq!(Some(next_noon), schedule.next_after(&just_past));
+
+        // Just before the fire second, both modes find it.
+        let just_before = noon - 250_000_000;
+        assert_eq!(Some(noon), schedule.next_at_or_after(&just_before));
+        assert_eq!(Some(noon), schedule.next_after(&just_before));
+    }
+
+    #[test]
+    fn test_at_or_after_iterator() {
+        let schedule = Schedule::from_str("*/10 * * * * *").unwrap();
+        let start = Utc
+            .with_ymd_and_hms(2021, 6, 15, 12, 0, 0)
+            .unwrap()
+            .timestamp_nanos_opt()
+            .unwrap() as u64;
+        let fire_times: Vec<u64> = schedule
+            .at_or_after(&(start + 500_000_000))
+            .take(3)
+            .collect();
+        assert_eq!(
+            fire_times,
+            vec![start, start + 10_000_000_000, start + 20_000_000_000]
+        );
+    }
+
+    #[test]
+    fn test_includes_tz() {
+        let schedule = Schedule::from_str("0 0 9 * * Mon").unwrap();
+        let pacific = FixedOffset::west_opt(8 * 3600).unwrap();
+        let india = FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap();
+        // Monday 09:00 in the Pacific is 17:00 UTC, and Monday 09:00 in India is 03:30 UTC.
+        let pacific_nine = Utc.with_ymd_and_hms(2021, 6, 14, 17, 0, 0).unwrap();
+        let india_nine = Utc.with_ymd_and_hms(2021, 6, 14, 3, 30, 0).unwrap();
+        assert!(schedule.includes_tz(&pacific_nine, &pacific));
+        assert!(!schedule.includes_tz(&pacific_nine, &india));
+        assert!(!schedule.includes_tz(&pacific_nine, &Utc));
+        assert!(schedule.includes_tz(&india_nine, &india));
+        // The zone of the input doesn't matter, only the instant.
+        assert!(schedule.includes_tz(&india_nine.with_timezone(&pacific), &india));
+        // A day later is a Tuesday locally.
+        assert!(!schedule.includes_tz(&(india_nine + Duration::days(1)), &india));
+
+        for fire_time in schedule.after_with_offset(&0, india).take(5) {
+            let date_time = Utc.timestamp_nanos(fire_time as i64);
+            assert!(schedule.includes_tz(&date_time, &india));
+            assert!(!schedule.includes(date_time));
+        }
+    }
+
+    #[test]
+    fn test_date_times_keep_offset() {
+        let schedule = Schedule::from_str("0 0 9,21 * * *").unwrap();
+        let india = FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap();
+        let pacific = FixedOffset::west_opt(8 * 3600).unwrap();
+        let rfc3339 = |offset| -> Vec<String> {
+            schedule
+                .after_with_offset(&NANOS_PER_DAY, offset)
+                .date_times()
+                .take(2)
+                .map(|date_time| date_time.to_rfc3339())
+                .collect()
+        };
+        assert_eq!(
+            vec!["1970-01-02T09:00:00+05:30", "1970-01-02T21:00:00+05:30"],
+            rfc3339(india)
+        );
+        // Midnight UTC on the 2nd is still the 1st in the Pacific.
+        assert_eq!(
+            vec!["1970-01-01T21:00:00-08:00", "1970-01-02T09:00:00-08:00"],
+            rfc3339(pacific)
+        );
+
+        // The instants are the same as the plain fire times, and without an offset they're UTC.
+        let fire_times: Vec<u64> = schedule.after_with_offset(&0, india).take(2).collect();
+        let date_times = schedule.after_with_offset(&0, india).date_times();
+        for (fire_time, date_time) in fire_times.into_iter().zip(date_times) {
+            assert_eq!(fire_time as i64, date_time.timestamp_nanos_opt().unwrap());
+            assert_eq!(india, *date_time.offset());
+        }
+        let date_time = schedule.after(&0).date_times().next().unwrap();
+        assert_eq!("1970-01-01T09:00:00+00:00", date_time.to_rfc3339());
+    }
+
+    #[test]
+    fn test_upcoming_with_offset() {
+        let nanos = |day, hour, minute| {
+            Utc.with_ymd_and_hms(2021, 6, day, hour, minute, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap() as u64
+        };
+        let schedule = Schedule::from_str("0 0 9 * * *").unwrap();
+
+        let india = FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap();
+        let fire_times: Vec<u64> = schedule
+            .after_with_offset(&nanos(15, 0, 0), india)
+            .take(3)
+            .collect();
+        assert_eq!(
+            fire_times,
+            vec![nanos(15, 3, 30), nanos(16, 3, 30), nanos(17, 3, 30)]
+        );
+        // 04:00 UTC is already past 09:00 in India.
+        assert_eq!(
+            Some(nanos(16, 3, 30)),
+            schedule.after_with_offset(&nanos(15, 4, 0), india).next()
+        );
+
+        // West of UTC, 09:00 local is later in the UTC day.
+        let pacific = FixedOffset::west_opt(8 * 3600).unwrap();
+        assert_eq!(
+            Some(nanos(15, 17, 0)),
+            schedule.after_with_offset(&nanos(15, 0, 0), pacific).next()
+        );
+
+        // A zero offset matches the plain iterator.
+        let utc = FixedOffset::east_opt(0).unwrap();
+        assert!(schedule
+            .after_with_offset(&nanos(15, 0, 0), utc)
+            .take(5)
+            .eq(schedule.after(&nanos(15, 0, 0)).take(5)));
+        assert!(schedule.upcoming_with_offset(india).next().is_some());
+    }
+
+    #[test]
+    fn test_next_n_days() {
+        let nanos = |day, hour| {
+            Utc.with_ymd_and_hms(2021, 6, day, hour, 0, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap() as u64
+        };
+        let schedule = Schedule::from_str("0 0 9,17 * * Mon-Fri").unwrap();
+        // Starting on Saturday the 12th, the weekend is skipped.
+        let days: Vec<u64> = schedule.next_n_days(6, &nanos(12, 0)).collect();
+        assert_eq!(
+            days,
+            vec![
+                nanos(14, 9),
+                nanos(15, 9),
+                nanos(16, 9),
+                nanos(17, 9),
+                nanos(18, 9),
+                nanos(21, 9),
+            ]
+        );
+        // Starting mid-day, the rest of that day still counts.
+        let days: Vec<u64> = schedule.next_n_days(2, &nanos(14, 12)).collect();
+        assert_eq!(days, vec![nanos(14, 17), nanos(15, 9)]);
+        assert_eq!(0, schedule.next_n_days(0, &nanos(12, 0)).count());
+
+        // Iteration ends with the schedule.
+        let schedule = Schedule::from_str("0 0 9 14,15 6 * 2021").unwrap();
+        assert_eq!(2, schedule.next_n_days(5, &nanos(12, 0)).count());
+    }
+
+    #[test]
+    fn test_with_fields() {
+        let base = Schedule::from_str("*/5 30 9 * * Mon-Fri 2030").unwrap();
+        let schedule = base.with_seconds(Seconds::from_ordinal(0));
+        assert_eq!("0 30 9 * * 2-6 2030", schedule.to_string());
+        assert_eq!(1, schedule.seconds().count());
+        assert!(schedule.seconds().includes(0));
+        // The other fields are unchanged.
+        assert!(schedule.fields.minutes == base.fields.minutes);
+        assert!(schedule.fields.hours == base.fields.hours);
+        assert!(schedule.fields.days_of_month == base.fields.days_of_month);
+        assert!(schedule.fields.months == base.fields.months);
+        assert!(schedule.fields.days_of_week == base.fields.days_of_week);
+        assert!(schedule.fields.years == base.fields.years);
+        assert!(schedule.has_explicit_years());
+        // The source is regenerated, and parses back to the same schedule.
+        assert!(Schedule::from_str(&schedule.to_string())
+            .unwrap()
+            .timeunitspec_eq(&schedule));
+
+        let schedule = base
+            .with_minutes(Minutes::from_ordinal_set([0, 45].into()))
+            .with_hours(Hours::all())
+            .with_days_of_month(DaysOfMonth::from_ordinal(15))
+            .with_months(Months::from_ordinal(6))
+            .with_days_of_week(DaysOfWeek::all());
+        assert_eq!(
+            "0,5,10,15,20,25,30,35,40,45,50,55 0,45 * 15 6 * 2030",
+            schedule.to_string()
+        );
+
+        // Shorthands don't gain a years field.
+        let schedule = Schedule::from_str("@daily")
+            .unwrap()
+            .with_hours(Hours::from_ordinal(6));
+        assert_eq!("0 0 6 * * *", schedule.to_string());
+    }
+
+    #[test]
+    fn test_candidates_scanned() {
+        let start = Utc
+            .with_ymd_and_hms(2021, 6, 15, 12, 0, 0)
+            .unwrap()
+            .timestamp_nanos_opt()
+            .unwrap() as u64;
+        let scanned = |expression: &str| -> Vec<usize> {
+            Schedule::from_str(expression)
+                .unwrap()
+                .after(&start)
+                .with_candidates_scanned()
+                .take(5)
+                .map(|(_, candidates_scanned)| candidates_scanned)
+                .collect()
+        };
+        // Dense schedules find a fire time in the first day and the next minute or so.
+        assert_eq!(vec![3, 3, 3, 3, 3], scanned("0 * * * * *"));
+        assert_eq!(vec![2, 2, 2, 2, 2], scanned("* * * * * *"));
+        // Friday the 13th needs the 13th of several months to be checked.
+        assert_eq!(vec![3, 12, 11, 12, 14], scanned("0 0 0 13 * Fri"));
+
+        // Wrapping doesn't change the fire times themselves.
+        let schedule = Schedule::from_str("0 0 0 13 * Fri").unwrap();
+        assert!(schedule.after(&start).take(5).eq(schedule
+            .after(&start)
+            .with_candidates_scanned()
+            .take(5)
+            .map(|(t, _)| t)));
+    }
+
+    #[test]
+    fn test_fields() {
+        let schedule = Schedule::from_str("0 0,30 9-17 * 6 Mon-Fri 2030").unwrap();
+        let kinds: Vec<UnitKind> = schedule.fields().map(|(kind, _)| kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                UnitKind::Seconds,
+                UnitKind::Minutes,
+                UnitKind::Hours,
+                UnitKind::DaysOfMonth,
+                UnitKind::Months,
+                UnitKind::DaysOfWeek,
+                UnitKind::Years,
+            ]
+        );
+        let counts: Vec<u32> = schedule.fields().map(|(_, field)| field.count()).collect();
+        assert_eq!(counts, vec![1, 2, 9, 31, 1, 5, 1]);
+        let all: Vec<bool> = schedule.fields().map(|(_, field)| field.is_all()).collect();
+        assert_eq!(all, vec![false, false, false, true, false, false, false]);
+    }
+
+    #[test]
+    fn test_filtered_skips_weekends() {
+        let is_weekday = |date_time: &DateTime<Utc>| {
+            !matches!(
+                date_time.weekday(),
+                chrono::Weekday::Sat | chrono::Weekday::Sun
+            )
+        };
+        let daily = Schedule::from_str("0 30 8 * * *").unwrap();
+        let weekdays = Schedule::from_str("0 30 8 * * Mon-Fri").unwrap();
+        let after = Utc
+            .with_ymd_and_hms(2021, 6, 15, 12, 0, 0)
+            .unwrap()
+            .timestamp_nanos_opt()
+            .unwrap() as u64;
+        let filtered: Vec<u64> = daily.after_filtered(&after, is_weekday).take(20).collect();
+        assert_eq!(
+            filtered,
+            weekdays.after(&after).take(20).collect::<Vec<u64>>()
+        );
+
+        let upcoming: Vec<u64> = daily.upcoming_filtered(is_weekday).take(5).collect();
+        assert_eq!(5, upcoming.len());
+        assert!(upcoming
+            .iter()
+            .all(|nanos| is_weekday(&Utc.timestamp_nanos(*nanos as i64))));
+    }
+
+    #[test]
+    fn test_except() {
+        let hourly = Schedule::from_str("0 0 * * * *").unwrap();
+        let backup = Schedule::from_str("0 0 2 * * *").unwrap();
+        let midnight = Utc
+            .with_ymd_and_hms(2021, 6, 15, 0, 0, 0)
+            .unwrap()
+            .timestamp_nanos_opt()
+            .unwrap() as u64;
+        let hours: Vec<u32> = hourly
+            .except_after(&midnight, &backup)
+            .take(24)
+            .map(|nanos| Utc.timestamp_nanos(nanos as i64).hour())
+            .collect();
+        let expected: Vec<u32> = (1..24).chain(0..2).filter(|hour| *hour != 2).collect();
+        assert_eq!(expected, hours);
+
+        // Schedules that never coincide leave every fire time in place.
+        let on_the_half_hour = Schedule::from_str("0 30 * * * *").unwrap();
+        assert!(hourly
+            .except_after(&midnight, &on_the_half_hour)
+            .take(48)
+            .eq(hourly.after(&midnight).take(48)));
+    }
+
+    #[test]
+    fn test_upcoming_in() {
+        let schedule = Schedule::from_str("0 0 * * * *").unwrap();
+        let at = |hour| Utc.with_ymd_and_hms(2021, 6, 15, hour, 0, 0).unwrap();
+        let nanos = |hour| at(hour).timestamp_nanos_opt().unwrap() as u64;
+        let collect = |iterator: &mut dyn Iterator<Item = u64>| iterator.collect::<Vec<u64>>();
+
+        assert_eq!(
+            vec![nanos(9), nanos(10), nanos(11)],
+            collect(&mut schedule.upcoming_in(at(9)..at(12)))
+        );
+        assert_eq!(
+            vec![nanos(9), nanos(10), nanos(11), nanos(12)],
+            collect(&mut schedule.upcoming_in(at(9)..=at(12)))
+        );
+        // A start between fire times.
+        let half_past = at(9) + Duration::minutes(30);
+        assert_eq!(
+            vec![nanos(10), nanos(11)],
+            collect(&mut schedule.upcoming_in(half_past..at(12)))
+        );
+        assert_eq!(
+            vec![nanos(10)],
+            collect(&mut schedule.upcoming_in((Excluded(at(9)), Excluded(at(11)))))
+        );
+        assert_eq!(
+            schedule
+                .at_or_after(&nanos(9))
+                .take(50)
+                .collect::<Vec<u64>>(),
+            schedule.upcoming_in(at(9)..).take(50).collect::<Vec<u64>>()
+        );
+
+        // Without a start, the range begins now.
+        let now = Utc::now();
+        let in_a_day: Vec<u64> = schedule.upcoming_in(..now + Duration::days(1)).collect();
+        assert!((24..=25).contains(&in_a_day.len()));
+        assert!(in_a_day[0] > now.timestamp_nanos_opt().unwrap() as u64);
+        assert_eq!(0, schedule.upcoming_in(..now).count());
+
+        // Bounds outside the range of fire times.
+        let before_epoch = Utc.with_ymd_and_hms(1969, 12, 31, 22, 0, 0).unwrap();
+        assert_eq!(
+            vec![0, 3_600_000_000_000],
+            collect(
+                &mut schedule.upcoming_in(before_epoch..=Utc.timestamp_nanos(3_600_000_000_000))
+            )
+        );
+        let far_future = Utc.with_ymd_and_hms(2600, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(None, schedule.upcoming_in(far_future..).next());
+    }
+
+    #[test]
+    fn test_granularity() {
+        for (expression, granularity) in [
+            ("* * * * * *", Granularity::Second),
+            ("*/15 0 9 * * *", Granularity::Second),
+            ("0 * * * * *", Granularity::Minute),
+            ("30 0,30 * * * *", Granularity::Minute),
+            ("0 0 * * * *", Granularity::Hour),
+            ("@hourly", Granularity::Hour),
+            ("0 15 9-17 * * Mon-Fri", Granularity::Hour),
+            ("0 0 0 * * *", Granularity::Day),
+            ("@monthly", Granularity::Day),
+            ("0 30 9 1 1 * 2030", Granularity::Day),
+        ] {
+            let schedule = Schedule::from_str(expression).unwrap();
+            assert_eq!(granularity, schedule.granularity(), "{}", expression);
+        }
+        assert!(Granularity::Second < Granularity::Minute);
+        assert!(Granularity::Hour < Granularity::Day);
+    }
+
+    // A time zone which counts how often local times are worked out with it.
+    #[derive(Clone, Copy, Debug)]
+    struct CountingUtc;
+
+    thread_local! {
+        static LOCAL_TIMES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    impl chrono::Offset for CountingUtc {
+        fn fix(&self) -> FixedOffset {
+            LOCAL_TIMES.with(|count| count.set(count.get() + 1));
+            FixedOffset::east_opt(0).unwrap()
+        }
+    }
+
+    impl TimeZone for CountingUtc {
+        type Offset = CountingUtc;
+
+        fn from_offset(_: &CountingUtc) -> CountingUtc {
+            CountingUtc
+        }
+        fn offset_from_local_date(&self, _: &NaiveDate) -> chrono::LocalResult<CountingUtc> {
+            chrono::LocalResult::Single(CountingUtc)
+        }
+        fn offset_from_local_datetime(
+            &self,
+            _: &chrono::NaiveDateTime,
+        ) -> chrono::LocalResult<CountingUtc> {
+            chrono::LocalResult::Single(CountingUtc)
+        }
+        fn offset_from_utc_date(&self, _: &NaiveDate) -> CountingUtc {
+            CountingUtc
+        }
+        fn offset_from_utc_datetime(&self, _: &chrono::NaiveDateTime) -> CountingUtc {
+            CountingUtc
+        }
+    }
+
+    #[test]
+    fn test_includes_checks_time_first() {
+        let schedule = Schedule::from_str("30 0 9 * * Mon-Fri").unwrap();
+        let count_local_times = |date_time: DateTime<Utc>| {
+            LOCAL_TIMES.with(|count| count.set(0));
+            let included = schedule.includes(date_time.with_timezone(&CountingUtc));
+            (included, LOCAL_TIMES.with(|count| count.get()))
+        };
+        let monday = Utc.with_ymd_and_hms(2024, 6, 3, 9, 0, 30).unwrap();
+        // Only the time is worked out when it doesn't match.
+        assert_eq!((false, 1), count_local_times(monday + Duration::seconds(1)));
+        assert_eq!((true, 2), count_local_times(monday));
+        assert_eq!((false, 2), count_local_times(monday + Duration::days(5)));
+    }
+
+    #[test]
+    fn test_frequency_class() {
+        let class = |expression| Schedule::from_str(expression).unwrap().frequency_class();
+        let expression = "0   30   9,12,15     1,15       May-Aug  Mon,Wed,Fri  2018/2";
+        assert_eq!(FrequencyClass::Irregular, class(expression));
+
+        assert_eq!(FrequencyClass::SubMinute, class("* * * * * *"));
+        assert_eq!(FrequencyClass::SubMinute, class("0,30 0 9 * * Mon"));
+        assert_eq!(FrequencyClass::Minutely, class("0 * * * * *"));
+        assert_eq!(FrequencyClass::Minutely, class("0 */15 9-17 * * Mon-Fri"));
+        assert_eq!(FrequencyClass::Hourly, class("@hourly"));
+        assert_eq!(FrequencyClass::Hourly, class("0 0 9,17 * * *"));
+        assert_eq!(FrequencyClass::Daily, class("@daily"));
+        assert_eq!(FrequencyClass::Daily, class("0 30 9 ? * Mon-Fri 2030"));
+        assert_eq!(FrequencyClass::Weekly, class("@weekly"));
+        assert_eq!(FrequencyClass::Weekly, class("0 0 9 * * Fri"));
+        assert_eq!(FrequencyClass::Monthly, class("@monthly"));
+        assert_eq!(FrequencyClass::Yearly, class("@yearly"));
+        assert_eq!(FrequencyClass::Yearly, class("0 0 9 25 Dec ?"));
+
+        // Other patterns of days.
+        assert_eq!(FrequencyClass::Irregular, class("0 0 9 1,15 * *"));
+        assert_eq!(FrequencyClass::Irregular, class("0 0 9 * Jun-Aug *"));
+        assert_eq!(FrequencyClass::Irregular, class("0 0 9 1 Jan,Jul *"));
+        assert_eq!(FrequencyClass::Irregular, class("0 0 9 13 * Fri"));
+        assert_eq!(FrequencyClass::Irregular, class("0 */5 * 1 * *"));
+        let options = crate::ParseOptions::new().allow_custom_directives(true);
+        let directive = Schedule::parse_with("@startup", &options).unwrap();
+        assert_eq!(FrequencyClass::Irregular, directive.frequency_class());
+    }
+
+    #[test]
+    fn test_shortest_interval() {
+        let shortest = |expression| Schedule::from_str(expression).unwrap().shortest_interval();
+        assert_eq!(Some(Duration::seconds(1)), shortest("* * * * * *"));
+        assert_eq!(Some(Duration::minutes(1)), shortest("0 * * * * *"));
+        assert_eq!(Some(Duration::seconds(5)), shortest("0,5,30 * * * * *"));
+        // Across midnight, 23:00 to 01:00.
+        assert_eq!(Some(Duration::hours(2)), shortest("0 0 1,12,23 * * *"));
+        assert_eq!(Some(Duration::hours(24)), shortest("@daily"));
+        assert_eq!(Some(Duration::days(7)), shortest("@weekly"));
+        // January 31st to February 1st.
+        assert_eq!(Some(Duration::days(1)), shortest("0 0 0 1,31 * *"));
+        assert_eq!(Some(Duration::days(28)), shortest("0 0 0 1 Feb,Mar *"));
+        assert_eq!(Some(Duration::days(4 * 365 + 1)), shortest("0 0 0 29 2 *"));
+        assert_eq!(Some(Duration::days(365)), shortest("0 0 0 1 1 * 2021,2022"));
+        // The eight years start from 2008, the first year with a February 29th.
+        assert_eq!(
+            Some(Duration::days(4 * 365 + 1)),
+            shortest("0 0 0 29 2 * 2001,2007-2030")
+        );
+
+        assert_eq!(None, shortest("0 0 0 1 1 * 2021"));
+        assert_eq!(None, shortest("0 0 0 30 2 *"));
+        let options = crate::ParseOptions::new().allow_custom_directives(true);
+        let directive = Schedule::parse_with("@startup", &options).unwrap();
+        assert_eq!(None, directive.shortest_interval());
+    }
+
+    #[test]
+    fn test_count_remaining() {
+        let schedule = Schedule::from_str("0 0 12 * * Mon 2030").unwrap();
+        assert_eq!(Some(52), schedule.count_remaining(&0));
+        let nanos = |date_time: DateTime<Utc>| date_time.timestamp_nanos_opt().unwrap() as u64;
+        // Mondays after noon on Monday July 1st.
+        let july = Utc.with_ymd_and_hms(2030, 7, 1, 12, 0, 0).unwrap();
+        for reference in [july, july + Duration::milliseconds(500)] {
+            let remaining = schedule.after(&nanos(reference)).count() as u64;
+            assert_eq!(Some(remaining), schedule.count_remaining(&nanos(reference)));
         }
+        assert_eq!(Some(26), schedule.count_remaining(&nanos(july)));
+        assert_eq!(Some(27), schedule.count_remaining(&(nanos(july) - 1)));
+        let later = Utc.with_ymd_and_hms(2031, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(Some(0), schedule.count_remaining(&nanos(later)));
+
+        // Every second of a year, counted without iterating.
+        let schedule = Schedule::from_str("* * * * * * 2031").unwrap();
+        let reference = Utc.with_ymd_and_hms(2031, 12, 31, 23, 0, 0).unwrap();
+        assert_eq!(Some(3_599), schedule.count_remaining(&nanos(reference)));
+        assert_eq!(Some(365 * 86_400), schedule.count_remaining(&0));
+
+        // The last fire times can't be represented.
+        let schedule = Schedule::from_str("0 0 0 * * * 2554").unwrap();
+        let remaining = schedule.after(&0).count() as u64;
+        assert_eq!(Some(remaining), schedule.count_remaining(&0));
+        assert!(remaining < 365);
+        assert_eq!(
+            None,
+            Schedule::from_str("@daily").unwrap().count_remaining(&0)
+        );
     }
-}
 
-impl<'a> Iterator for ScheduleIterator<'a> {
-    type Item = u64;
+    #[test]
+    fn test_fire_windows() {
+        let schedule = Schedule::from_str("*/1 * 9 * * *").unwrap();
+        let day = Utc.with_ymd_and_hms(2021, 6, 15, 0, 0, 0).unwrap();
+        let nanos = |date_time: DateTime<Utc>| date_time.timestamp_nanos_opt().unwrap() as u64;
+        let windows: Vec<(u64, u64, u64)> = schedule
+            .fire_windows(Duration::seconds(1), day..day + Duration::days(2))
+            .collect();
+        let nine = day + Duration::hours(9);
+        let last = nine + Duration::seconds(3_599);
+        assert_eq!(
+            vec![
+                (nanos(nine), nanos(last), 3_600),
+                (
+                    nanos(nine + Duration::days(1)),
+                    nanos(last + Duration::days(1)),
+                    3_600
+                ),
+            ],
+            windows
+        );
 
-    fn next(&mut self) -> Option<u64> {
-        if self.is_done {
-            return None;
+        // A threshold below the spacing leaves every fire time in a window of its own.
+        let hourly = Schedule::from_str("0 0 * * * *").unwrap();
+        let windows: Vec<(u64, u64, u64)> = hourly
+            .fire_windows(Duration::minutes(59), day..day + Duration::hours(3))
+            .collect();
+        assert_eq!(3, windows.len());
+        assert!(windows
+            .iter()
+            .all(|(start, end, count)| start == end && *count == 1));
+        assert_eq!(
+            1,
+            hourly
+                .fire_windows(Duration::hours(1), day..day + Duration::hours(3))
+                .count()
+        );
+        assert_eq!(
+            3,
+            hourly
+                .fire_windows(Duration::hours(-1), day..day + Duration::hours(3))
+                .count()
+        );
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_jittered() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let schedule = Schedule::from_str("0 0 * * * *").unwrap();
+        let max = Duration::minutes(10);
+        let jittered = |seed| {
+            schedule
+                .after_jittered(&0, max, StdRng::seed_from_u64(seed))
+                .take(100)
+                .collect::<Vec<u64>>()
+        };
+        let nominal: Vec<u64> = schedule.after(&0).take(100).collect();
+        let fire_times = jittered(42);
+        assert_eq!(fire_times, jittered(42));
+        assert_ne!(fire_times, jittered(43));
+        let max_nanos = max.num_nanoseconds().unwrap() as u64;
+        for (fire_time, nominal) in fire_times.iter().zip(&nominal) {
+            assert!(*nominal <= *fire_time && *fire_time < nominal + max_nanos);
         }
-        if let Some(next_datetime) = self.schedule.next_after(&self.previous_datetime) {
-            self.previous_datetime = next_datetime;
-            Some(next_datetime)
-        } else {
-            self.is_done = true;
-            None
+        assert!(fire_times
+            .iter()
+            .any(|fire_time| fire_time % 3_600_000_000_000 != 0));
+
+        // Fire times closer together than the jitter stay in order.
+        let schedule = Schedule::from_str("* * * * * *").unwrap();
+        let fire_times: Vec<u64> = schedule
+            .after_jittered(&0, max, StdRng::seed_from_u64(1))
+            .take(1_000)
+            .collect();
+        assert!(fire_times.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        let unjittered: Vec<u64> = schedule
+            .after_jittered(&0, Duration::zero(), StdRng::seed_from_u64(1))
+            .take(10)
+            .collect();
+        assert_eq!(
+            schedule.after(&0).take(10).collect::<Vec<u64>>(),
+            unjittered
+        );
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_sample_between() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let schedule = Schedule::from_str("0 0 9-17 * * Mon-Fri").unwrap();
+        let start = Utc.with_ymd_and_hms(2021, 6, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2021, 7, 1, 0, 0, 0).unwrap();
+        let fire_times = schedule.fire_times_vec(start, end);
+        let samples = schedule.sample_between(start, end, 500, StdRng::seed_from_u64(42));
+        assert_eq!(500, samples.len());
+        assert_eq!(
+            samples,
+            schedule.sample_between(start, end, 500, StdRng::seed_from_u64(42))
+        );
+        assert!(samples.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(samples.iter().all(|sample| fire_times.contains(sample)));
+        // 500 draws from 198 fire times are bound to repeat some.
+        assert!(samples.windows(2).any(|pair| pair[0] == pair[1]));
+
+        let rng = StdRng::seed_from_u64(1);
+        assert!(schedule.sample_between(end, start, 10, rng).is_empty());
+        let weekend = Utc.with_ymd_and_hms(2021, 6, 5, 0, 0, 0).unwrap();
+        let rng = StdRng::seed_from_u64(1);
+        let samples = schedule.sample_between(weekend, weekend + Duration::days(2), 10, rng);
+        assert!(samples.is_empty());
+    }
+
+    #[test]
+    fn test_are_adjacent() {
+        let schedule = Schedule::from_str("0 0 9,17 * * Mon-Fri").unwrap();
+        let at = |day, hour| {
+            Utc.with_ymd_and_hms(2021, 6, day, hour, 0, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap() as u64
+        };
+        // Tuesday the 15th through Monday the 21st.
+        assert!(schedule.are_adjacent(&at(15, 9), &at(15, 17)));
+        assert!(schedule.are_adjacent(&at(15, 17), &at(16, 9)));
+        assert!(schedule.are_adjacent(&at(18, 17), &at(21, 9)));
+        assert!(!schedule.are_adjacent(&at(15, 9), &at(16, 9)));
+        assert!(!schedule.are_adjacent(&at(15, 17), &at(15, 9)));
+        assert!(!schedule.are_adjacent(&at(15, 9), &at(15, 9)));
+
+        // Either instant not being a fire time.
+        assert!(!schedule.are_adjacent(&at(15, 12), &at(15, 17)));
+        assert!(!schedule.are_adjacent(&at(15, 9), &at(15, 12)));
+        assert!(!schedule.are_adjacent(&(at(15, 9) + 1), &at(15, 17)));
+        assert!(!schedule.are_adjacent(&at(19, 17), &at(21, 9)));
+    }
+
+    #[test]
+    fn test_normalize() {
+        let unix = crate::ParseOptions::new().no_seconds(true);
+        let daily = [
+            Schedule::from_str("@daily").unwrap(),
+            Schedule::from_str("0 0 0 * * *").unwrap(),
+            Schedule::from_str("0 0 0 ? * * *").unwrap(),
+            Schedule::from_str("0 0 0 1-31 * ?").unwrap(),
+            Schedule::from_str("0 0 0 * 1-12 1-7 1970-2100").unwrap(),
+            Schedule::parse_with("0 0 * * *", &unix).unwrap(),
+        ];
+        for schedule in &daily {
+            assert_eq!(
+                "0 0 0 * * ? *",
+                schedule.normalize().to_string(),
+                "{}",
+                schedule
+            );
+        }
+
+        let weekdays = [
+            Schedule::from_str("0 30 9,10 ? Jan-Dec MON-FRI").unwrap(),
+            Schedule::from_str("0 30 9-10 * * 2,3,4,5,6").unwrap(),
+            Schedule::parse_with("30 9/1 * * Mon-Fri", &unix)
+                .unwrap()
+                .with_hours(Hours::from_ordinal_set([9, 10].into_iter().collect())),
+        ];
+        for schedule in &weekdays {
+            let normalized = schedule.normalize();
+            assert_eq!(
+                "0 30 9,10 ? * 2-6 *",
+                normalized.to_string(),
+                "{}",
+                schedule
+            );
+            assert_eq!(Dialect::Quartz, normalized.dialect());
+            assert!(normalized.has_explicit_years());
+            assert!(normalized.timeunitspec_eq(schedule));
+            // The canonical form parses back to itself.
+            let reparsed = Schedule::from_str(&normalized.to_string()).unwrap();
+            assert_eq!(normalized, reparsed.normalize());
         }
+
+        assert_eq!(
+            "0 0 12 1 * ? 2030",
+            Schedule::from_str("0 0 12 1 * * 2030")
+                .unwrap()
+                .normalize()
+                .to_string()
+        );
+        assert_eq!(
+            "0 0 12 13 * 6 *",
+            Schedule::from_str("0 0 12 13 * Fri")
+                .unwrap()
+                .normalize()
+                .to_string()
+        );
     }
-}
 
-fn is_leap_year(year: Ordinal) -> bool {
-    let by_four = year % 4 == 0;
-    let by_hundred = year % 100 == 0;
-    let by_four_hundred = year % 400 == 0;
-    by_four && ((!by_hundred) || by_four_hundred)
-}
+    #[test]
+    fn test_time_only_and_date_only() {
+        let schedule = Schedule::from_str("0 30 9,12,15 1,15 May-Aug Mon,Wed,Fri 2021/2").unwrap();
+        let time_only = schedule.time_only();
+        let at = |day, hour, minute| Utc.with_ymd_and_hms(2022, 2, day, hour, minute, 0).unwrap();
+        let expected: Vec<u64> = (3..=4)
+            .flat_map(|day| [9, 12, 15].map(|hour| at(day, hour, 30)))
+            .map(|date_time| date_time.timestamp_nanos_opt().unwrap() as u64)
+            .collect();
+        let after = at(2, 23, 0).timestamp_nanos_opt().unwrap() as u64;
+        assert_eq!(
+            expected,
+            time_only.after(&after).take(6).collect::<Vec<u64>>()
+        );
+        assert_eq!(schedule.times_of_day(), time_only.times_of_day());
+        assert_eq!(3, time_only.fire_times_per_day());
+
+        let date_only = schedule.date_only();
+        assert_eq!(1, date_only.fire_times_per_day());
+        let fire_times: Vec<DateTime<Utc>> = schedule
+            .after(&0)
+            .take(9)
+            .step_by(3)
+            .map(|fire_time| Utc.timestamp_nanos(fire_time as i64))
+            .collect();
+        let days: Vec<DateTime<Utc>> = date_only
+            .after(&0)
+            .take(3)
+            .map(|fire_time| Utc.timestamp_nanos(fire_time as i64))
+            .collect();
+        for (fire_time, day) in fire_times.iter().zip(&days) {
+            assert_eq!(fire_time.date_naive(), day.date_naive());
+            assert_eq!(NaiveTime::MIN, day.time());
+        }
 
-fn days_in_month(month: Ordinal, year: Ordinal) -> u32 {
-    let is_leap_year = is_leap_year(year);
-    match month {
-        9 | 4 | 6 | 11 => 30,
-        2 if is_leap_year => 29,
-        2 => 28,
-        _ => 31,
+        // The times of one schedule on the days of another.
+        let mornings = Schedule::from_str("0 0 8 * * *").unwrap();
+        let weekends = Schedule::from_str("0 0 0 * * Sat,Sun").unwrap().date_only();
+        let weekend_mornings = mornings
+            .time_only()
+            .with_days_of_week(weekends.fields.days_of_week);
+        assert_eq!("0 0 8 * * 1,7", weekend_mornings.to_string());
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std::str::FromStr;
+    #[test]
+    fn test_count_between() {
+        let schedule = Schedule::from_str("0 */15 9-17 * * Mon-Fri").unwrap();
+        let start = Utc.with_ymd_and_hms(2021, 6, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2021, 7, 1, 0, 0, 0).unwrap();
+        let count = schedule.upcoming_in(start..end).count() as u64;
+        assert_eq!(22 * 9 * 4, count);
+        assert_eq!(Some(count), schedule.count_between(start, end));
+        // The end is excluded, the start isn't.
+        let nine = Utc.with_ymd_and_hms(2021, 6, 1, 9, 0, 0).unwrap();
+        assert_eq!(Some(0), schedule.count_between(nine, nine));
+        let quarter_past = nine + Duration::minutes(15);
+        assert_eq!(Some(1), schedule.count_between(nine, quarter_past));
+
+        assert_eq!(None, schedule.count_between(end, start));
+        assert_eq!(
+            None,
+            schedule.count_between(nine, nine - Duration::seconds(1))
+        );
+        let before_epoch = Utc.with_ymd_and_hms(1960, 1, 1, 0, 0, 0).unwrap();
+        let every_second = Schedule::from_str("* * * * * * 1970").unwrap();
+        let new_year = Utc.with_ymd_and_hms(1970, 1, 2, 0, 0, 0).unwrap();
+        assert_eq!(
+            Some(0),
+            every_second.count_between(before_epoch, before_epoch)
+        );
+        assert_eq!(
+            Some(86_400),
+            every_second.count_between(before_epoch, new_year)
+        );
+        assert_eq!(
+            Some(365 * 86_400),
+            every_second.count_between(before_epoch, DateTime::<Utc>::MAX_UTC)
+        );
+    }
 
     #[test]
-    fn test_next_duration() {
-        let expression = "0 5,13,40-42 17 1 Jan *";
+    fn test_next_weekday_occurrence() {
+        let nanos = |date_time: DateTime<Utc>| date_time.timestamp_nanos_opt().unwrap() as u64;
+        let daily = Schedule::from_str("0 30 9 * * *").unwrap();
+        let friday = Utc.with_ymd_and_hms(2021, 6, 18, 9, 30, 0).unwrap();
+        let monday = Utc.with_ymd_and_hms(2021, 6, 21, 9, 30, 0).unwrap();
+        assert_eq!(
+            Some(friday),
+            daily.next_weekday_occurrence(&(nanos(friday) - 1))
+        );
+        // Saturday's and Sunday's fire times are passed over.
+        assert_eq!(Some(monday), daily.next_weekday_occurrence(&nanos(friday)));
+        let saturday = Utc.with_ymd_and_hms(2021, 6, 19, 9, 30, 0).unwrap();
+        assert!(daily.includes(saturday));
+        assert_eq!(
+            Some(monday),
+            daily.next_weekday_occurrence(&nanos(saturday))
+        );
+        let mut after = nanos(friday);
+        for _ in 0..10 {
+            let next = daily.next_weekday_occurrence(&after).unwrap();
+            assert!(!matches!(next.weekday(), Weekday::Sat | Weekday::Sun));
+            after = nanos(next);
+        }
+
+        let every_second = Schedule::from_str("* * * * * *").unwrap();
+        assert_eq!(
+            Some(Utc.with_ymd_and_hms(2021, 6, 21, 0, 0, 0).unwrap()),
+            every_second.next_weekday_occurrence(&nanos(saturday))
+        );
+        let weekends = Schedule::from_str("0 0 12 * * Sat,Sun").unwrap();
+        assert_eq!(None, weekends.next_weekday_occurrence(&0));
+    }
+
+    #[test]
+    fn test_skipping_dates() {
+        let schedule = Schedule::from_str("0 0 9,17 * * Mon-Fri").unwrap();
+        let date = |day| NaiveDate::from_ymd_opt(2021, 12, day).unwrap();
+        let holidays = HashSet::from([date(24), date(27), date(31)]);
+        let after = Utc.with_ymd_and_hms(2021, 12, 22, 12, 0, 0).unwrap();
+        let after = after.timestamp_nanos_opt().unwrap() as u64;
+        let fire_times: Vec<DateTime<Utc>> = schedule
+            .skipping_dates_after(&after, holidays.clone())
+            .take(8)
+            .map(|fire_time| Utc.timestamp_nanos(fire_time as i64))
+            .collect();
+        let at = |day, hour| date(day).and_hms_opt(hour, 0, 0).unwrap().and_utc();
+        assert_eq!(
+            vec![
+                at(22, 17),
+                at(23, 9),
+                at(23, 17),
+                at(28, 9),
+                at(28, 17),
+                at(29, 9),
+                at(29, 17),
+                at(30, 9)
+            ],
+            fire_times
+        );
+        assert!(fire_times
+            .iter()
+            .all(|fire_time| !holidays.contains(&fire_time.date_naive())));
+
+        let unfiltered: Vec<u64> = schedule.after(&after).take(8).collect();
+        let no_holidays: Vec<u64> = schedule
+            .skipping_dates_after(&after, HashSet::new())
+            .take(8)
+            .collect();
+        assert_eq!(unfiltered, no_holidays);
+    }
+
+    #[test]
+    fn test_interval_stats() {
+        let start = Utc.with_ymd_and_hms(2021, 6, 1, 0, 0, 0).unwrap();
+        let end = start + Duration::days(1);
+
+        let regular = Schedule::from_str("0 */30 * * * *").unwrap();
+        let stats = regular.interval_stats(start, end);
+        assert_eq!(48, stats.count);
+        assert_eq!(Some(Duration::minutes(30)), stats.min);
+        assert_eq!(stats.min, stats.max);
+        assert_eq!(stats.min, stats.mean);
+
+        let irregular = Schedule::from_str("0 0 9,10,17 * * *").unwrap();
+        let stats = irregular.interval_stats(start, start + Duration::days(3));
+        assert_eq!(9, stats.count);
+        assert_eq!(Some(Duration::hours(1)), stats.min);
+        assert_eq!(Some(Duration::hours(16)), stats.max);
+        // From 09:00 on the first day to 17:00 on the third, over eight gaps.
+        assert_eq!(Some(Duration::hours(56) / 8), stats.mean);
+
+        let once = Schedule::from_str("0 0 12 * * *")
+            .unwrap()
+            .interval_stats(start, end);
+        assert_eq!(1, once.count);
+        assert_eq!((None, None, None), (once.min, once.max, once.mean));
+        let never = regular.interval_stats(end, start);
+        assert_eq!(0, never.count);
+        assert_eq!(None, never.mean);
+    }
+
+    #[test]
+    fn test_with_year_range() {
+        let schedule = Schedule::from_str("0 0 12 1 * *").unwrap();
+        let decade = schedule.with_year_range(2030, 2039).unwrap();
+        assert!(decade.has_explicit_years());
+        let fire_times: Vec<DateTime<Utc>> = decade
+            .after(&0)
+            .map(|fire_time| Utc.timestamp_nanos(fire_time as i64))
+            .collect();
+        assert_eq!(120, fire_times.len());
+        assert_eq!(
+            Utc.with_ymd_and_hms(2030, 1, 1, 12, 0, 0).unwrap(),
+            fire_times[0]
+        );
+        assert_eq!(
+            Utc.with_ymd_and_hms(2039, 12, 1, 12, 0, 0).unwrap(),
+            fire_times[119]
+        );
+        assert_eq!(
+            Some(0),
+            decade.count_remaining(&(fire_times[119].timestamp_nanos_opt().unwrap() as u64))
+        );
+
+        // Beyond the default span, up to the largest year.
+        let late = schedule.with_year_range(2200, MAX_YEAR).unwrap();
+        let after = Utc.with_ymd_and_hms(2150, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            Some(Utc.with_ymd_and_hms(2200, 1, 1, 12, 0, 0).unwrap()),
+            late.after(&(after.timestamp_nanos_opt().unwrap() as u64))
+                .next()
+                .map(|fire_time| Utc.timestamp_nanos(fire_time as i64))
+        );
+
+        // Years already restricted are narrowed further.
+        let even = Schedule::from_str("0 0 12 1 1 * 2020/2").unwrap();
+        let narrowed = even.with_year_range(2025, 2030).unwrap();
+        assert_eq!(
+            vec![2026, 2028, 2030],
+            narrowed.years().iter().collect::<Vec<_>>()
+        );
+        assert!(even.with_year_range(2031, 2031).is_err());
+
+        assert!(schedule.with_year_range(2039, 2030).is_err());
+        assert!(schedule.with_year_range(1900, 2030).is_err());
+        assert!(schedule.with_year_range(2030, MAX_YEAR + 1).is_err());
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let after = Utc.with_ymd_and_hms(2021, 6, 15, 9, 0, 0).unwrap();
+        // Partway through a second, which the fire times still start from.
+        let after = after.timestamp_nanos_opt().unwrap() as u64 + 250_000_000;
+        for expression in ["*/15 * * * * *", "* * * * * *", "0 30 9 * * Mon-Fri"] {
+            let schedule = Schedule::from_str(expression).unwrap();
+            let date_times: Vec<DateTime<Utc>> = schedule
+                .after(&after)
+                .date_times()
+                .take(20)
+                .map(|date_time| date_time.with_timezone(&Utc))
+                .collect();
+            let timestamps: Vec<i64> = schedule.after(&after).timestamps().take(20).collect();
+            let millis: Vec<i64> = schedule
+                .after(&after)
+                .timestamps_millis()
+                .take(20)
+                .collect();
+            assert_eq!(
+                date_times
+                    .iter()
+                    .map(DateTime::timestamp)
+                    .collect::<Vec<_>>(),
+                timestamps,
+                "{}",
+                expression
+            );
+            assert_eq!(
+                date_times
+                    .iter()
+                    .map(DateTime::timestamp_millis)
+                    .collect::<Vec<_>>(),
+                millis,
+                "{}",
+                expression
+            );
+        }
+
+        let schedule = Schedule::from_str("* * * * * *").unwrap();
+        let now = Utc::now().timestamp();
+        let next = schedule.upcoming_timestamps().next().unwrap();
+        assert!(now <= next && next <= now + 2);
+        let next_millis = schedule.upcoming_timestamps_millis().next().unwrap();
+        assert_eq!(0, next_millis % 1_000);
+        assert!(next * 1_000 <= next_millis);
+    }
+
+    #[test]
+    fn test_grouped_by_day() {
+        let schedule = Schedule::from_str("0 30 9,12,15 * * Mon,Wed,Fri").unwrap();
+        let monday = Utc.with_ymd_and_hms(2021, 6, 14, 0, 0, 0).unwrap();
+        let after = monday.timestamp_nanos_opt().unwrap() as u64;
+        let days: Vec<(NaiveDate, Vec<DateTime<Utc>>)> =
+            schedule.grouped_by_day_after(&after).take(4).collect();
+        let dates: Vec<NaiveDate> = days.iter().map(|(date, _)| *date).collect();
+        let date = |day| NaiveDate::from_ymd_opt(2021, 6, day).unwrap();
+        assert_eq!(vec![date(14), date(16), date(18), date(21)], dates);
+        for (date, fire_times) in &days {
+            let expected: Vec<DateTime<Utc>> = [9, 12, 15]
+                .map(|hour| date.and_hms_opt(hour, 30, 0).unwrap().and_utc())
+                .to_vec();
+            assert_eq!(&expected, fire_times);
+        }
+
+        // Flattening the groups gives the fire times back.
+        let flat: Vec<u64> = schedule.after(&after).take(12).collect();
+        let grouped: Vec<u64> = days
+            .iter()
+            .flat_map(|(_, fire_times)| fire_times)
+            .map(|fire_time| fire_time.timestamp_nanos_opt().unwrap() as u64)
+            .collect();
+        assert_eq!(flat, grouped);
+
+        // A start partway through a day only has that day's remaining fire times.
+        let (first_date, fire_times) = schedule
+            .grouped_by_day_after(&(after + 10 * 3_600 * NANOS_PER_SECOND))
+            .next()
+            .unwrap();
+        assert_eq!(date(14), first_date);
+        assert_eq!(2, fire_times.len());
+
+        let once = Schedule::from_str("0 0 12 1 1 * 2021").unwrap();
+        let mut groups = once.grouped_by_day_after(&0);
+        assert_eq!(1, groups.next().unwrap().1.len());
+        assert_eq!(None, groups.next());
+    }
+
+    #[test]
+    fn test_next_aligned_to() {
+        let schedule = Schedule::from_str("0 * * * * *").unwrap();
+        let grid = Duration::minutes(5);
+        let mut after = Utc.with_ymd_and_hms(2021, 6, 15, 8, 58, 30).unwrap();
+        let mut minutes = Vec::new();
+        for _ in 0..4 {
+            let nanos = after.timestamp_nanos_opt().unwrap() as u64;
+            after = schedule.next_aligned_to(grid, &nanos).unwrap();
+            minutes.push((after.hour(), after.minute()));
+        }
+        assert_eq!(vec![(9, 0), (9, 5), (9, 10), (9, 15)], minutes);
+
+        let nine = Utc.with_ymd_and_hms(2021, 6, 15, 9, 0, 0).unwrap();
+        let nine = nine.timestamp_nanos_opt().unwrap() as u64;
+        // Every second only lands on a grid of 1.5 seconds every other second.
+        let every_second = Schedule::from_str("* * * * * *").unwrap();
+        assert_eq!(
+            Some(Utc.timestamp_nanos((nine + 3 * NANOS_PER_SECOND) as i64)),
+            every_second.next_aligned_to(Duration::milliseconds(1_500), &nine)
+        );
+        let quarters = Schedule::from_str("0 0,15,30,45 * * * *").unwrap();
+        assert_eq!(
+            Some(Utc.with_ymd_and_hms(2021, 6, 15, 10, 0, 0).unwrap()),
+            quarters.next_aligned_to(Duration::hours(1), &nine)
+        );
+
+        assert_eq!(None, schedule.next_aligned_to(Duration::zero(), &nine));
+        assert_eq!(None, schedule.next_aligned_to(Duration::minutes(-5), &nine));
+        let off_grid = Schedule::from_str("7 * * * * * 2021").unwrap();
+        assert_eq!(None, off_grid.next_aligned_to(Duration::hours(1), &nine));
+    }
+
+    #[test]
+    fn test_matches_within() {
+        let schedule = Schedule::from_str("0 */15 * * * *").unwrap();
+        let fire_time = Utc.with_ymd_and_hms(2021, 6, 15, 9, 15, 0).unwrap();
+        let tolerance = Duration::seconds(30);
+        for offset in [0, 1, 29, 30, -30, -1] {
+            let date_time = fire_time + Duration::seconds(offset);
+            assert!(schedule.matches_within(date_time, tolerance), "{}", offset);
+            assert_eq!(offset == 0, schedule.includes(date_time), "{}", offset);
+        }
+        for offset in [31, -31, 7 * 60] {
+            let date_time = fire_time + Duration::seconds(offset);
+            assert!(!schedule.matches_within(date_time, tolerance), "{}", offset);
+        }
+        let just_over = fire_time + tolerance + Duration::nanoseconds(1);
+        assert!(!schedule.matches_within(just_over, tolerance));
+        assert!(schedule.matches_within(fire_time, Duration::seconds(-5)));
+        assert!(!schedule.matches_within(just_over, Duration::seconds(-5)));
+
+        // Instants before the epoch are measured from the first fire time.
+        let before_epoch = Utc.with_ymd_and_hms(1969, 12, 31, 23, 59, 0).unwrap();
+        assert!(schedule.matches_within(before_epoch, Duration::minutes(1)));
+        assert!(!schedule.matches_within(before_epoch, tolerance));
+    }
+
+    #[test]
+    fn test_fire_times_vec() {
+        let start = Utc.with_ymd_and_hms(2021, 6, 15, 9, 0, 0).unwrap();
+        for (expression, end) in [
+            ("* * 9 * * *", start + Duration::hours(2)),
+            ("0 */15 * * * Mon-Fri", start + Duration::days(30)),
+            ("30 0 0 1 * *", start + Duration::days(400)),
+            ("0 0 9 * * *", start + Duration::milliseconds(1)),
+            ("0 0 9 * * *", start),
+        ] {
+            let schedule = Schedule::from_str(expression).unwrap();
+            let fire_times = schedule.fire_times_vec(start, end);
+            let expected: Vec<DateTime<Utc>> = schedule
+                .upcoming_in(start..end)
+                .map(|fire_time| Utc.timestamp_nanos(fire_time as i64))
+                .collect();
+            assert_eq!(expected, fire_times, "{}", expression);
+            // Sized exactly once, without growing.
+            assert_eq!(fire_times.len(), fire_times.capacity(), "{}", expression);
+        }
+        let schedule = Schedule::from_str("* * 9 * * *").unwrap();
+        assert_eq!(
+            3_600,
+            schedule
+                .fire_times_vec(start, start + Duration::days(1))
+                .len()
+        );
+        let before_epoch = Utc.with_ymd_and_hms(1969, 12, 31, 0, 0, 0).unwrap();
+        let epoch = Utc.with_ymd_and_hms(1970, 1, 1, 9, 0, 1).unwrap();
+        assert_eq!(1, schedule.fire_times_vec(before_epoch, epoch).len());
+        assert!(schedule.fire_times_vec(epoch, before_epoch).is_empty());
+    }
+
+    #[test]
+    fn test_explain_next() {
+        let schedule = Schedule::from_str("0 0 */6 * * *").unwrap();
+        let after = Utc.with_ymd_and_hms(2021, 6, 15, 3, 59, 59).unwrap();
+        let after = after.timestamp_nanos_opt().unwrap() as u64;
+        let explanation = schedule.explain_next_after(4, &after);
+        let lines: Vec<&str> = explanation.lines().collect();
+        assert_eq!(
+            vec![
+                "Next 4 fire times of `0 0 */6 * * *` after 2021-06-15 03:59:59 UTC:",
+                "  2021-06-15 06:00:00 UTC  in 2 hours",
+                "  2021-06-15 12:00:00 UTC  in 8 hours",
+                "  2021-06-15 18:00:00 UTC  in 14 hours",
+                "  2021-06-16 00:00:00 UTC  in 20 hours",
+            ],
+            lines
+        );
+
+        let schedule = Schedule::from_str("* * * * * *").unwrap();
+        let explanation = schedule.explain_next_after(2, &(59 * NANOS_PER_SECOND + 1));
+        assert!(explanation
+            .ends_with("01:00 UTC  in under a second\n  1970-01-01 00:01:01 UTC  in 1 second"));
+
+        // Only the fire times found are counted.
+        let schedule = Schedule::from_str("0 0 0 1 1 * 2021,2022").unwrap();
+        assert_eq!(
+            "Next fire time of `0 0 0 1 1 * 2021,2022` after 2021-06-15 03:59:59 UTC:\n  \
+             2022-01-01 00:00:00 UTC  in 199 days",
+            schedule.explain_next_after(3, &after)
+        );
+        let schedule = Schedule::from_str("0 0 0 1 1 * 2020").unwrap();
+        assert_eq!(
+            "Next fire times of `0 0 0 1 1 * 2020` after 2021-06-15 03:59:59 UTC:\n  none",
+            schedule.explain_next_after(3, &after)
+        );
+        assert_eq!("in 3 minutes", describe_time_until(200 * NANOS_PER_SECOND));
+        assert_eq!("in 2 days", describe_time_until(2 * NANOS_PER_DAY + 1));
+    }
+
+    #[test]
+    fn test_suppress_during() {
+        let hourly = Schedule::from_str("0 0 * * * *").unwrap();
+        let maintenance = Schedule::from_str("0 0 2 * * *").unwrap();
+        let hour = 3_600 * NANOS_PER_SECOND;
+        let hours = |window| -> Vec<u64> {
+            hourly
+                .suppress_during_after(&0, &maintenance, window)
+                .take(24)
+                .map(|fire_time| fire_time / hour)
+                .collect()
+        };
+        let hours_except = |skipped: &[u64]| -> Vec<u64> {
+            (1..)
+                .filter(|hour| !skipped.contains(&(hour % 24)))
+                .take(24)
+                .collect()
+        };
+        // The window starts at the maintenance fire time and leaves out its end, every day.
+        assert_eq!(hours_except(&[2]), hours(Duration::seconds(1)));
+        assert_eq!(hours_except(&[2]), hours(Duration::hours(1)));
+        assert_eq!(
+            hours_except(&[2, 3]),
+            hours(Duration::hours(1) + Duration::seconds(1))
+        );
+        assert_eq!(hours_except(&[2, 3, 4, 5]), hours(Duration::hours(4)));
+
+        // Windows that cover nothing.
+        assert_eq!(hours_except(&[]), hours(Duration::zero()));
+        assert_eq!(hours_except(&[]), hours(Duration::hours(-1)));
+
+        // Windows longer than the gap between maintenance fire times merge.
+        let maintenance = Schedule::from_str("0 0 0/2 * * *").unwrap();
+        let default_years =
+            crate::ParseOptions::new().default_years(Years::from_range(2021, 2021).unwrap());
+        let hourly = Schedule::parse_with("0 0 * * * *", &default_years).unwrap();
+        let mut fire_times = hourly.suppress_during_after(&0, &maintenance, Duration::hours(3));
+        assert_eq!(None, fire_times.next());
+    }
+
+    #[test]
+    fn test_upcoming_rev_from() {
+        let schedule = Schedule::from_str("0 30 9 * * * 1970-1971").unwrap();
+        let before = Utc.with_ymd_and_hms(1971, 3, 15, 9, 30, 0).unwrap();
+        let before = before.timestamp_nanos_opt().unwrap() as u64;
+        let fire_times: Vec<u64> = schedule.upcoming_rev_from(&before).collect();
+        // Strictly before, so starting with the previous day.
+        assert_eq!(before - NANOS_PER_DAY, fire_times[0]);
+        assert!(fire_times
+            .windows(2)
+            .all(|pair| pair[0] - pair[1] == NANOS_PER_DAY));
+        // Every day back to January 1st, 1970.
+        assert_eq!(365 + 31 + 28 + 14, fire_times.len());
+        assert_eq!(
+            9 * 3_600 * NANOS_PER_SECOND + 1_800 * NANOS_PER_SECOND,
+            *fire_times.last().unwrap()
+        );
+
+        // A clone resumes from the same place, and the iterator stays done.
+        let mut fire_times = schedule.upcoming_rev_from(&before);
+        fire_times.next();
+        let mut cursor = fire_times.clone();
+        assert_eq!(fire_times.next(), cursor.next());
+        let mut fire_times = schedule.upcoming_rev_from(&NANOS_PER_DAY);
+        assert!(fire_times.next().is_some());
+        assert_eq!(None, fire_times.next());
+        assert_eq!(None, fire_times.next());
+        assert_eq!(None, schedule.upcoming_rev_from(&0).next());
+    }
+
+    #[test]
+    fn test_once_per_day() {
+        let schedule = Schedule::from_str("0 */10 9 * * *").unwrap();
+        let nine = 9 * 3_600 * NANOS_PER_SECOND;
+        let fire_times: Vec<u64> = schedule.once_per_day_after(&0).take(365).collect();
+        let nine_each_day: Vec<u64> = (0..365).map(|day| nine + day * NANOS_PER_DAY).collect();
+        assert_eq!(nine_each_day, fire_times);
+
+        // Starting part way through the day keeps that day's next fire time.
+        let fire_times: Vec<u64> = schedule.once_per_day_after(&nine).take(2).collect();
+        let ten_past = nine + 600 * NANOS_PER_SECOND;
+        assert_eq!(vec![ten_past, nine + NANOS_PER_DAY], fire_times);
+
+        // Days without a fire time are skipped.
+        let schedule = Schedule::from_str("0 */10 9 * * Mon").unwrap();
+        let fire_times: Vec<u64> = schedule.once_per_day_after(&0).take(2).collect();
+        assert_eq!(7 * NANOS_PER_DAY, fire_times[1] - fire_times[0]);
+    }
+
+    #[test]
+    fn test_dedup_by() {
+        let schedule = Schedule::from_str("* * * * * *").unwrap();
+        let minute = 60 * NANOS_PER_SECOND;
+        let fire_times: Vec<u64> = schedule
+            .after(&(minute - 1))
+            .dedup_by(Granularity::Minute)
+            .take(100)
+            .collect();
+        let per_minute: Vec<u64> = (1..=100).map(|n| n * minute).collect();
+        assert_eq!(per_minute, fire_times);
+
+        // Within a unit, the first fire time is kept even if it's not at the start.
+        let schedule = Schedule::from_str("15,45 */20 9-10 * * *").unwrap();
+        let fire_times: Vec<u64> = schedule
+            .after(&0)
+            .dedup_by(Granularity::Hour)
+            .take(3)
+            .collect();
+        let hour = 60 * minute;
+        let first = 9 * hour + 15 * NANOS_PER_SECOND;
+        assert_eq!(vec![first, first + hour, first + NANOS_PER_DAY], fire_times);
+        let days: Vec<u64> = schedule
+            .after(&0)
+            .dedup_by(Granularity::Day)
+            .take(2)
+            .collect();
+        assert_eq!(vec![first, first + NANOS_PER_DAY], days);
+        assert_eq!(
+            schedule.after(&0).take(10).collect::<Vec<u64>>(),
+            schedule
+                .after(&0)
+                .dedup_by(Granularity::Second)
+                .take(10)
+                .collect::<Vec<u64>>()
+        );
+
+        // Days follow the offset the iterator was created with, where local midnight is 1am UTC.
+        let offset = FixedOffset::east_opt(-3_600).unwrap();
+        let schedule = Schedule::from_str("0 0 * * * *").unwrap();
+        let fire_times: Vec<u64> = schedule
+            .after_with_offset(&0, offset)
+            .dedup_by(Granularity::Day)
+            .take(2)
+            .collect();
+        assert_eq!(vec![2 * hour, hour + NANOS_PER_DAY], fire_times);
+    }
+
+    #[test]
+    fn test_dedupe() {
+        let schedules = [
+            "* * * * * *",
+            "0 0 9 * * *",
+            "0-59 * * * * *",
+            "0 0 9 * * * *",
+        ]
+        .iter()
+        .map(|expression| Schedule::from_str(expression).unwrap())
+        .collect();
+        let schedules: Vec<String> = dedupe(schedules).iter().map(Schedule::to_string).collect();
+        assert_eq!(vec!["* * * * * *", "0 0 9 * * *"], schedules);
+        assert!(dedupe(Vec::new()).is_empty());
+
+        let options = crate::ParseOptions::new().allow_custom_directives(true);
+        let schedules = ["@startup", "@shutdown", "@startup"]
+            .iter()
+            .map(|expression| Schedule::parse_with(expression, &options).unwrap())
+            .collect();
+        let schedules: Vec<String> = dedupe(schedules).iter().map(Schedule::to_string).collect();
+        assert_eq!(vec!["@startup", "@shutdown"], schedules);
+    }
+
+    #[test]
+    fn test_times_of_day() {
+        let schedule = Schedule::from_str("0 30 9,12,15 * * *").unwrap();
+        let expected: BTreeSet<NaiveTime> = [(9, 30), (12, 30), (15, 30)]
+            .iter()
+            .map(|(hour, minute)| NaiveTime::from_hms_opt(*hour, *minute, 0).unwrap())
+            .collect();
+        assert_eq!(expected, schedule.times_of_day());
+
+        let schedule = Schedule::from_str("* * * * * *").unwrap();
+        let times = schedule.times_of_day();
+        assert_eq!(schedule.fire_times_per_day(), times.len() as u64);
+        assert_eq!(
+            Some(&NaiveTime::from_hms_opt(23, 59, 59).unwrap()),
+            times.last()
+        );
+    }
+
+    #[test]
+    fn test_unix_and_quartz_strings() {
+        let schedule = Schedule::from_str("* * * ? * *").unwrap();
+        assert_eq!("* * * * * *", schedule.to_unix_string());
+        assert_eq!("* * * ? * *", schedule.to_quartz_string());
+        let schedule = Schedule::from_str("0 * * ? * *").unwrap();
+        assert_eq!("* * * * *", schedule.to_unix_string());
+
+        let schedule = Schedule::from_str("0 0 12 ? * Mon 2030").unwrap();
+        assert_eq!("0 12 * * 2 2030", schedule.to_unix_string());
+        assert_eq!("0 0 12 ? * 2 2030", schedule.to_quartz_string());
+        let schedule = Schedule::from_str("0 0 12 * * Mon").unwrap();
+        assert_eq!("0 0 12 ? * 2", schedule.to_quartz_string());
+        let schedule = Schedule::from_str("0 0 12 1 * Mon").unwrap();
+        assert_eq!("0 0 12 1 * 2", schedule.to_quartz_string());
+
+        // The Unix form parses back into the same schedule.
+        let options = crate::ParseOptions::new().no_seconds(true);
+        let unix = Schedule::parse_with(&schedule.to_unix_string(), &options).unwrap();
+        assert!(unix.timeunitspec_eq(&schedule));
+    }
+
+    #[test]
+    fn test_same_times_as() {
+        let same = [
+            ("0 0 9 * * MON-FRI", "0 0 9 * * 2-6"),
+            ("0 0 9 ? * Mon,Tue,Wed,Thu,Fri", "0 0 9 * * 2,3,4,5,6"),
+            ("0 0,15,30,45 * * * *", "0 */15 * * * *"),
+            ("0 0 0 * * *", "@daily"),
+            (
+                "0 0 0 1 Jan-Mar * 2020-2022",
+                "0 0 0 1 1,2,3 ? 2020,2021,2022",
+            ),
+        ];
+        for (expression, other) in same {
+            let schedule = Schedule::from_str(expression).unwrap();
+            let other = Schedule::from_str(other).unwrap();
+            assert!(schedule.same_times_as(&other), "{}", expression);
+            assert!(other.same_times_as(&schedule), "{}", expression);
+            assert_ne!(schedule, other);
+        }
+
+        let different = [
+            // Days of the week Sunday first, so Monday is 2.
+            ("0 0 9 * * MON-FRI", "0 0 9 * * 1-5"),
+            ("0 0 9 * * *", "0 0 9 * * * 2020"),
+            ("0 0 9 1 * *", "0 0 9 * * 1"),
+            ("0 0 9 * * *", "1 0 9 * * *"),
+        ];
+        for (expression, other) in different {
+            let schedule = Schedule::from_str(expression).unwrap();
+            let other = Schedule::from_str(other).unwrap();
+            assert!(!schedule.same_times_as(&other), "{}", expression);
+        }
+    }
+
+    #[test]
+    fn test_field() {
+        let expression = "0   30   9,12,15     1,15       May-Aug  Mon,Wed,Fri  2018/2";
         let schedule = Schedule::from_str(expression).unwrap();
-        let next = schedule.next_after(&(Utc::now().timestamp_nanos_opt().unwrap() as u64));
-        println!("NEXT DURATION------- for {} {:?}", expression, next);
-        assert!(next.is_some());
+        let hours = schedule.field(UnitKind::Hours);
+        assert_eq!(vec![9, 12, 15], hours.iter().collect::<Vec<Ordinal>>());
+        assert!(hours.includes(12));
+        assert!(!hours.includes(10));
+
+        // Each unit maps to the same field as in `fields`.
+        for (unit, field) in schedule.fields() {
+            assert_eq!(
+                field.iter().collect::<Vec<Ordinal>>(),
+                schedule.field(unit).iter().collect::<Vec<Ordinal>>(),
+                "{:?}",
+                unit
+            );
+        }
+        assert!(schedule.field(UnitKind::DaysOfYear).is_all());
     }
 
     #[test]
-    fn test_next_after() {
-        let expression = "0 5,13,40-42 17 1 Jan *";
+    fn test_describe_field() {
+        let expression = "0   30   9,12,15     1,15       May-Aug  Mon,Wed,Fri  2018/2";
         let schedule = Schedule::from_str(expression).unwrap();
-        let next = schedule.next_after(&(Utc::now().timestamp_nanos_opt().unwrap() as u64));
-        println!("NEXT AFTER for {} {:?}", expression, next);
-        assert!(next.is_some());
+        assert_eq!(
+            "at 9 AM, 12 PM, and 3 PM",
+            schedule.describe_field(UnitKind::Hours)
+        );
+        assert_eq!(
+            "in May through August",
+            schedule.describe_field(UnitKind::Months)
+        );
+        assert_eq!(
+            "on Monday, Wednesday, and Friday",
+            schedule.describe_field(UnitKind::DaysOfWeek)
+        );
+        assert_eq!("at second 0", schedule.describe_field(UnitKind::Seconds));
+        assert_eq!("at minute 30", schedule.describe_field(UnitKind::Minutes));
+        assert!(schedule
+            .describe_field(UnitKind::Years)
+            .starts_with("in 2018, 2020, 2022,"));
+
+        let schedule = Schedule::from_str("*/20 * 0,12 * Jan,Dec Sun-Tue").unwrap();
+        assert_eq!(
+            "at seconds 0, 20, and 40",
+            schedule.describe_field(UnitKind::Seconds)
+        );
+        assert_eq!("every minute", schedule.describe_field(UnitKind::Minutes));
+        assert_eq!(
+            "at 12 AM and 12 PM",
+            schedule.describe_field(UnitKind::Hours)
+        );
+        assert_eq!(
+            "every day of the month",
+            schedule.describe_field(UnitKind::DaysOfMonth)
+        );
+        assert_eq!(
+            "in January and December",
+            schedule.describe_field(UnitKind::Months)
+        );
+        assert_eq!(
+            "on Sunday through Tuesday",
+            schedule.describe_field(UnitKind::DaysOfWeek)
+        );
+        assert_eq!(
+            "every ISO week",
+            schedule.describe_field(UnitKind::WeeksOfYear)
+        );
     }
 
     #[test]
-    fn test_upcoming_utc() {
-        let expression = "0 0,30 0,6,12,18 1,15 Jan-March Thurs";
+    fn test_as_cron_fields() {
+        let expression = "0   30   9,12,15     1,15       May-Aug  Mon,Wed,Fri  2018/2";
         let schedule = Schedule::from_str(expression).unwrap();
-        let mut upcoming = schedule.upcoming();
-        let next1 = upcoming.next();
-        assert!(next1.is_some());
-        let next2 = upcoming.next();
-        assert!(next2.is_some());
-        let next3 = upcoming.next();
-        assert!(next3.is_some());
-        println!("Upcoming 1 for {} {:?}", expression, next1);
-        println!("Upcoming 2 for {} {:?}", expression, next2);
-        println!("Upcoming 3 for {} {:?}", expression, next3);
+        let fields = schedule.as_cron_fields();
+        assert_eq!(["0", "30", "9,12,15", "1,15", "5-8", "2,4,6"], fields[..6]);
+        assert_eq!("2018/2", fields[6]);
+
+        let round_tripped = Schedule::from_str(&fields.join(" ")).unwrap();
+        assert!(round_tripped.timeunitspec_eq(&schedule));
+
+        let schedule = Schedule::from_str("@daily").unwrap();
+        assert_eq!(
+            ["0", "0", "0", "*", "*", "*", "*"],
+            schedule.as_cron_fields()
+        );
+        // Other years are listed, as are steps stopping before the end of the span.
+        let schedule = Schedule::from_str("0 0 0 1 1 * 2030-2040/5").unwrap();
+        assert_eq!("2030,2035,2040", schedule.as_cron_fields()[6]);
+        let schedule = Schedule::from_str("0 0 0 1 1 * 2050-2100").unwrap();
+        assert_eq!("2050-2100", schedule.as_cron_fields()[6]);
+    }
+
+    #[test]
+    fn test_display_in_dialect() {
+        let options = crate::ParseOptions::new().no_seconds(true);
+        let schedule = Schedule::parse_with("*/15 9 * * Mon-Fri", &options).unwrap();
+        assert_eq!(Dialect::Unix, schedule.dialect());
+        assert_eq!(5, schedule.fields_count());
+        let schedule = schedule.with_hours(Hours::from_ordinal(10));
+        assert_eq!("0,15,30,45 10 * * 2-6", schedule.to_string());
+        assert_eq!(5, schedule.fields_count());
+        let reparsed = Schedule::parse_with(&schedule.to_string(), &options).unwrap();
+        assert!(reparsed.timeunitspec_eq(&schedule));
+
+        let schedule = Schedule::from_str("0 0 12 ? * Mon *").unwrap();
+        assert_eq!(Dialect::Quartz, schedule.dialect());
+        let schedule = schedule.with_hours(Hours::from_ordinal(18));
+        assert_eq!("0 0 18 ? * 2 *", schedule.to_string());
+        assert_eq!(7, schedule.fields_count());
+        assert!(schedule.extensions().any);
+        let schedule = schedule.with_days_of_month(DaysOfMonth::from_ordinal(1));
+        assert_eq!("0 0 18 1 * 2 *", schedule.to_string());
+        assert!(!schedule.extensions().any);
+
+        let schedule = Schedule::from_str("@daily").unwrap();
+        assert_eq!(Dialect::Cron, schedule.dialect());
+    }
+
+    #[test]
+    fn test_fire_times_per_day() {
+        for (expression, per_day) in [
+            ("0 0 * * * *", 24),
+            ("*/30 * * * * *", 2_880),
+            ("* * * * * *", 86_400),
+            ("@daily", 1),
+            ("0 0 12 29 2 *", 1),
+        ] {
+            let schedule = Schedule::from_str(expression).unwrap();
+            assert_eq!(per_day, schedule.fire_times_per_day(), "{}", expression);
+        }
+    }
+
+    #[test]
+    fn test_extensions() {
+        for expression in ["0 30 9 * * Mon-Fri", "@daily", "0 0 0 1 1 * *"] {
+            let schedule = Schedule::from_str(expression).unwrap();
+            assert!(!schedule.uses_extensions(), "{}", expression);
+        }
+
+        let schedule = Schedule::from_str("0 0 12 * * L").unwrap();
+        assert!(schedule.uses_extensions());
+        assert!(schedule.extensions().last_day_of_week);
+        assert!(!schedule.extensions().seconds);
+        // Once rewritten, the schedule uses the day's number instead.
+        assert!(!schedule
+            .with_hours(Hours::from_ordinal(9))
+            .uses_extensions());
+
+        let schedule = Schedule::from_str("15 0 12 ? * Mon").unwrap();
+        let extensions = schedule.extensions();
+        assert!(extensions.seconds && extensions.any);
+        assert!(!extensions.years && !extensions.days_of_year);
+
+        let no_seconds = crate::ParseOptions::new().no_seconds(true);
+        let schedule = Schedule::parse_with("0 12 * * L 2030", &no_seconds).unwrap();
+        assert!(schedule.extensions().last_day_of_week);
+        assert!(schedule.extensions().years);
+        let options = crate::ParseOptions::new().allow_day_of_year(true);
+        let schedule = Schedule::parse_with("0 0 12 * * * * 100", &options).unwrap();
+        assert!(schedule.extensions().days_of_year);
+    }
+
+    #[test]
+    fn test_align_to_next() {
+        let schedule = Schedule::from_str("0 0 9,17 * * *").unwrap();
+        let at = |day, hour, minute| {
+            Utc.with_ymd_and_hms(2021, 6, day, hour, minute, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap() as u64
+        };
+        // On a fire time, it is its own boundary.
+        assert_eq!(Some(at(15, 9, 0)), schedule.align_to_next(&at(15, 9, 0)));
+        assert_eq!(Some(at(15, 17, 0)), schedule.align_to_next(&at(15, 17, 0)));
+        // Between fire times, the next one.
+        assert_eq!(Some(at(15, 17, 0)), schedule.align_to_next(&at(15, 9, 1)));
+        assert_eq!(Some(at(16, 9, 0)), schedule.align_to_next(&at(15, 17, 30)));
+        assert_eq!(Some(at(15, 9, 0)), schedule.align_to_next(&at(15, 0, 0)));
+        // Unlike `next_after`, which always moves on.
+        assert_eq!(Some(at(15, 17, 0)), schedule.next_after(&at(15, 9, 0)));
+    }
+
+    #[test]
+    fn test_nth_after() {
+        let after = Utc
+            .with_ymd_and_hms(2021, 6, 15, 14, 29, 36)
+            .unwrap()
+            .timestamp_nanos_opt()
+            .unwrap() as u64;
+        for expression in [
+            "0 0 * * * *",
+            "*/7 5,40 1-3,22 * * *",
+            "0 0 12 * * * 2021-2022",
+            "0 0 9 * * Mon-Fri",
+            "0 0 0 29 2 *",
+            "@monthly",
+        ] {
+            let schedule = Schedule::from_str(expression).unwrap();
+            assert_eq!(schedule.next_after(&after), schedule.nth_after(0, &after));
+            let mut fire_times = schedule.after(&after);
+            let expected: Vec<Option<u64>> = (0..200).map(|_| fire_times.next()).collect();
+            let actual: Vec<Option<u64>> =
+                (0..200).map(|n| schedule.nth_after(n, &after)).collect();
+            assert_eq!(expected, actual, "{}", expression);
+        }
+        let schedule = Schedule::from_str("0 0 12 * * *").unwrap();
+        assert_eq!(schedule.after(&after).nth(5), schedule.nth_after(5, &after));
+        // Past the end of the last year.
+        assert_eq!(None, schedule.nth_after(100_000, &after));
+        assert_eq!(None, schedule.nth_after(usize::MAX / 2, &after));
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let midnight = Utc
+            .with_ymd_and_hms(2021, 6, 15, 0, 0, 0)
+            .unwrap()
+            .timestamp_nanos_opt()
+            .unwrap() as u64;
+        let noon = midnight + 12 * 3_600_000_000_000;
+        let every_four_hours = Schedule::from_str("0 0 */4 * * *").unwrap();
+        let at_noon = Schedule::from_str("0 0 12 * * *").unwrap();
+        let day = Duration::days(1);
+        assert_eq!(
+            Some(noon),
+            every_four_hours.overlaps_after(&midnight, &at_noon, day)
+        );
+        assert_eq!(
+            Some(noon),
+            at_noon.overlaps_after(&midnight, &every_four_hours, day)
+        );
+        // The end of the horizon is inclusive.
+        assert_eq!(
+            Some(noon),
+            every_four_hours.overlaps_after(&midnight, &at_noon, Duration::hours(12))
+        );
+        assert_eq!(
+            None,
+            every_four_hours.overlaps_after(&midnight, &at_noon, Duration::hours(11))
+        );
+
+        // Both fire every day, but never at the same minute.
+        let at_half_past = Schedule::from_str("0 30 * * * *").unwrap();
+        assert_eq!(
+            None,
+            every_four_hours.overlaps_after(&midnight, &at_half_past, Duration::days(365))
+        );
+        // Each fires on its own weekday until they coincide on a Sunday the 1st.
+        let sundays = Schedule::from_str("0 0 9 * * Sun").unwrap();
+        let firsts = Schedule::from_str("0 0 9 1 * *").unwrap();
+        let first_sunday = Utc
+            .with_ymd_and_hms(2021, 8, 1, 9, 0, 0)
+            .unwrap()
+            .timestamp_nanos_opt()
+            .unwrap() as u64;
+        assert_eq!(
+            Some(first_sunday),
+            sundays.overlaps_after(&midnight, &firsts, Duration::days(365))
+        );
+        assert_eq!(
+            None,
+            sundays.overlaps_after(&midnight, &firsts, Duration::days(30))
+        );
+        assert_eq!(
+            None,
+            sundays.overlaps_after(&midnight, &firsts, Duration::days(-1))
+        );
+    }
+
+    #[test]
+    fn test_days_of_year() {
+        let midnight = |year, month, day| {
+            Utc.with_ymd_and_hms(year, month, day, 0, 0, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap() as u64
+        };
+        let options = crate::ParseOptions::new().allow_day_of_year(true);
+        let schedule = Schedule::parse_with("0 0 0 * * * * 60", &options).unwrap();
+        // Day 60 is March 1st in common years and February 29th in leap years.
+        let fire_times: Vec<u64> = schedule.after(&midnight(2023, 1, 1)).take(3).collect();
+        assert_eq!(
+            fire_times,
+            vec![
+                midnight(2023, 3, 1),
+                midnight(2024, 2, 29),
+                midnight(2025, 3, 1)
+            ]
+        );
+        assert_eq!(
+            Some(midnight(2024, 2, 29)),
+            schedule.prev_before(&midnight(2025, 1, 1))
+        );
+        assert!(schedule.includes(Utc.with_ymd_and_hms(2024, 2, 29, 0, 0, 0).unwrap()));
+        assert!(!schedule.includes(Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()));
+
+        // The day of year is combined with the other date fields.
+        let schedule = Schedule::parse_with("0 0 0 * 3 * * 60", &options).unwrap();
+        assert_eq!(
+            Some(midnight(2025, 3, 1)),
+            schedule.next_after(&midnight(2024, 1, 1))
+        );
+        let leap_days = Schedule::parse_with("0 0 0 * * * * 366", &options).unwrap();
+        assert_eq!(
+            Some(Utc.with_ymd_and_hms(2024, 12, 31, 0, 0, 0).unwrap()),
+            leap_days
+                .next_after(&midnight(2021, 1, 1))
+                .map(|nanos| Utc.timestamp_nanos(nanos as i64))
+        );
+    }
+
+    #[test]
+    fn test_reference_at_timestamp_limits() {
+        let every_second = Schedule::from_str("* * * * * *").unwrap();
+        let yearly = Schedule::from_str("@yearly").unwrap();
+
+        // The epoch itself.
+        assert_eq!(Some(1_000_000_000), every_second.next_after(&0));
+        assert_eq!(Some(0), every_second.next_at_or_after(&0));
+        assert_eq!(Some(0), yearly.next_at_or_after(&0));
+        assert_eq!(Some(365 * 86_400 * 1_000_000_000), yearly.next_after(&0));
+
+        // The last representable instant. Its second is still a fire time for at-or-after
+        // queries, but nothing comes after it. `*` stops at 2100, so cover the last year too.
+        let last_year = every_second.with_years(Years::from_range(2554, MAX_YEAR).unwrap());
+        let last_second = u64::MAX / 1_000_000_000 * 1_000_000_000;
+        assert_eq!(None, last_year.next_after(&u64::MAX));
+        assert_eq!(Some(last_second), last_year.next_at_or_after(&u64::MAX));
+        assert_eq!(None, yearly.next_after(&u64::MAX));
+        assert_eq!(None, last_year.after(&u64::MAX).next());
+        assert!(!last_year.fires_within_after(&u64::MAX, Duration::days(1)));
+
+        // Iterating into the limit stays monotonic and ends cleanly.
+        let fire_times: Vec<u64> = last_year.after(&(last_second - 3_000_000_000)).collect();
+        assert_eq!(
+            fire_times,
+            vec![
+                last_second - 2_000_000_000,
+                last_second - 1_000_000_000,
+                last_second
+            ]
+        );
+
+        // Offsets clamp at both ends rather than overflowing.
+        let east = FixedOffset::east_opt(3600).unwrap();
+        let west = FixedOffset::west_opt(3600).unwrap();
+        assert_eq!(None, last_year.after_with_offset(&u64::MAX, east).next());
+        assert_eq!(
+            Some(3_601_000_000_000),
+            every_second.after_with_offset(&0, west).next()
+        );
+        assert_eq!(
+            Some(1_000_000_000),
+            every_second.after_with_offset(&0, east).next()
+        );
+    }
+
+    #[test]
+    fn test_next_excluding() {
+        let nanos = |hour, minute| {
+            Utc.with_ymd_and_hms(2021, 6, 15, hour, minute, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap() as u64
+        };
+        let schedule = Schedule::from_str("0 0,30 * * * *").unwrap();
+        let start = nanos(9, 0);
+
+        assert_eq!(Some(nanos(9, 30)), schedule.next_excluding(&start, &[]));
+        // A fire time inside a blackout skips to the next one outside it.
+        assert_eq!(
+            Some(nanos(10, 30)),
+            schedule.next_excluding(&start, &[(nanos(9, 15), nanos(10, 15))])
+        );
+        // The start of a blackout is excluded, its end isn't.
+        assert_eq!(
+            Some(nanos(10, 0)),
+            schedule.next_excluding(&start, &[(nanos(9, 30), nanos(10, 0))])
+        );
+        assert_eq!(
+            Some(nanos(10, 0)),
+            schedule.next_excluding(&start, &[(nanos(9, 30), nanos(9, 30) + 1)])
+        );
+        // Overlapping and chained blackouts, in any order.
+        let blackouts = [
+            (nanos(10, 45), nanos(11, 45)),
+            (nanos(9, 20), nanos(10, 10)),
+            (nanos(10, 0), nanos(11, 0)),
+        ];
+        assert_eq!(
+            Some(nanos(12, 0)),
+            schedule.next_excluding(&start, &blackouts)
+        );
+        // An empty interval excludes nothing.
+        assert_eq!(
+            Some(nanos(9, 30)),
+            schedule.next_excluding(&start, &[(nanos(9, 30), nanos(9, 30))])
+        );
+        // No fire times are left after a blackout running past the end of the schedule.
+        let schedule = Schedule::from_str("0 0 12 * * * 2021").unwrap();
+        assert_eq!(
+            None,
+            schedule.next_excluding(&start, &[(nanos(11, 0), u64::MAX)])
+        );
+    }
+
+    #[test]
+    fn test_surrounding() {
+        let nanos = |day, hour, minute, second| {
+            Utc.with_ymd_and_hms(2021, 6, day, hour, minute, second)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap() as u64
+        };
+        let schedule = Schedule::from_str("0 0,30 9-17 * * Mon-Fri").unwrap();
+
+        // Between two runs on the same day.
+        assert_eq!(
+            (Some(nanos(15, 9, 30, 0)), Some(nanos(15, 10, 0, 0))),
+            schedule.surrounding(&nanos(15, 9, 45, 0))
+        );
+        // Exactly on a fire time, which counts as the previous run.
+        assert_eq!(
+            (Some(nanos(15, 10, 0, 0)), Some(nanos(15, 10, 30, 0))),
+            schedule.surrounding(&nanos(15, 10, 0, 0))
+        );
+        assert_eq!(
+            Some(nanos(15, 9, 30, 0)),
+            schedule.prev_before(&nanos(15, 10, 0, 0))
+        );
+        assert_eq!(
+            Some(nanos(15, 10, 0, 0)),
+            schedule.prev_before(&(nanos(15, 10, 0, 0) + 1))
+        );
+        // Across the weekend, Monday morning looks back to Friday evening.
+        assert_eq!(
+            (Some(nanos(11, 17, 30, 0)), Some(nanos(14, 9, 0, 0))),
+            schedule.surrounding(&nanos(14, 8, 0, 0))
+        );
+
+        // Back across month and year boundaries, and the start of the schedule.
+        let ymd = |year, month, day, hour| {
+            Utc.with_ymd_and_hms(year, month, day, hour, 0, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap() as u64
+        };
+        let schedule = Schedule::from_str("0 0 12 31 * * 2020-2021").unwrap();
+        assert_eq!(
+            Some(ymd(2021, 5, 31, 12)),
+            schedule.prev_before(&ymd(2021, 6, 30, 0))
+        );
+        assert_eq!(
+            Some(ymd(2020, 12, 31, 12)),
+            schedule.prev_before(&ymd(2021, 1, 15, 0))
+        );
+        assert_eq!(
+            Some(ymd(2021, 12, 31, 12)),
+            schedule.prev_at_or_before(&ymd(2030, 1, 1, 0))
+        );
+        assert_eq!(None, schedule.prev_before(&ymd(2020, 1, 31, 12)));
+        assert_eq!(None, schedule.prev_before(&0));
+    }
+
+    /// Finds the first fire time after `after` by checking each day, then each hour, minute and
+    /// second of a matching day, against the fields one at a time. Gives up after `max_days`.
+    fn brute_force_next_after(schedule: &Schedule, after: &u64, max_days: i64) -> Option<u64> {
+        let after = Utc.timestamp_nanos(*after as i64);
+        let fields = &schedule.fields;
+        let first_day = after.date_naive();
+        for date in (0..max_days).map(|days| first_day + Duration::days(days)) {
+            if !fields.years.includes(date.year() as Ordinal)
+                || !fields.months.includes(date.month())
+                || !fields.days_of_month.includes(date.day())
+                || !fields.includes_date(&date)
+            {
+                continue;
+            }
+            for hour in (0..24).filter(|hour| fields.hours.includes(*hour)) {
+                for minute in (0..60).filter(|minute| fields.minutes.includes(*minute)) {
+                    for second in (0..60).filter(|second| fields.seconds.includes(*second)) {
+                        let candidate = date.and_hms_opt(hour, minute, second).unwrap().and_utc();
+                        if candidate > after {
+                            return Some(candidate.timestamp_nanos_opt().unwrap() as u64);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(1_000))]
+        #[test]
+        fn test_next_after_matches_brute_force(
+            expression in arb_expression(),
+            offset in 0u64..4 * 365 * 86_400,
+        ) {
+            let schedule = Schedule::from_str(&expression).unwrap();
+            let after = 1_623_764_976_000_000_000 + offset * 1_000_000_000;
+            let max_days = 20 * 366;
+            let expected = brute_force_next_after(&schedule, &after, max_days);
+            let actual = schedule.next_after(&after);
+            match expected {
+                Some(_) => prop_assert_eq!(expected, actual, "{}", expression),
+                // The brute force search gave up, so any fire time is further away.
+                None => {
+                    let horizon = after + max_days as u64 * 86_400_000_000_000;
+                    prop_assert!(actual.is_none_or(|actual| actual > horizon), "{}", expression)
+                }
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_prev_mirrors_next(expression in arb_expression(), offset in 0u64..400 * 86_400) {
+            let schedule = Schedule::from_str(&expression).unwrap();
+            let start = 1_623_764_976_000_000_000 + offset * 1_000_000_000;
+            if let Some(next) = schedule.next_after(&start) {
+                // Nothing fires between the reference and its next fire time.
+                if let Some(prev) = schedule.prev_before(&next) {
+                    prop_assert!(prev <= start, "{} {} {}", prev, start, next);
+                    prop_assert_eq!(Some(next), schedule.next_after(&prev));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fires_within() {
+        let schedule = Schedule::from_str("*/10 * * * * *").unwrap();
+        let start = Utc
+            .with_ymd_and_hms(2021, 6, 15, 12, 0, 0)
+            .unwrap()
+            .timestamp_nanos_opt()
+            .unwrap() as u64;
+        assert!(!schedule.fires_within_after(&start, Duration::seconds(5)));
+        assert!(schedule.fires_within_after(&start, Duration::seconds(15)));
+        // The end of the window is inclusive, its start isn't.
+        assert!(schedule.fires_within_after(&start, Duration::seconds(10)));
+        assert!(!schedule.fires_within_after(&start, Duration::zero()));
+        assert!(!schedule.fires_within_after(&start, Duration::seconds(-15)));
+        // A 15 second window always contains one of the fire times.
+        assert!(schedule.fires_within(Duration::seconds(15)));
+
+        // Sparse schedules don't search beyond the window. The next Monday 29th of February is in
+        // 2044.
+        let schedule = Schedule::from_str("0 0 0 29 2 Mon").unwrap();
+        assert!(!schedule.fires_within_after(&start, Duration::days(30)));
+        assert!(schedule.fires_within_after(&start, Duration::days(365 * 24)));
     }
 
     #[test]
@@ -368,6 +4924,30 @@ mod test {
         assert!(schedule.is_err());
     }
 
+    #[test]
+    fn test_fields_count() {
+        let schedule = Schedule::from_str("0 30 9 * * Mon").unwrap();
+        assert!(!schedule.has_explicit_years());
+        assert_eq!(6, schedule.fields_count());
+
+        let schedule = Schedule::from_str("0 30 9 * * Mon 2030").unwrap();
+        assert!(schedule.has_explicit_years());
+        assert_eq!(7, schedule.fields_count());
+
+        // An explicit `*` still counts as a years field.
+        let schedule = Schedule::from_str("0 30 9 * * Mon *").unwrap();
+        assert!(schedule.has_explicit_years());
+
+        let schedule = Schedule::from_str("@daily").unwrap();
+        assert!(!schedule.has_explicit_years());
+        assert_eq!(6, schedule.fields_count());
+
+        // Replacing the years makes them explicit.
+        let schedule = schedule.with_years(Years::from_ordinal(2030));
+        assert!(schedule.has_explicit_years());
+        assert_eq!("0 0 0 * * * 2030", schedule.to_string());
+    }
+
     #[test]
     fn test_time_unit_spec_equality() {
         let schedule_1 = Schedule::from_str("@weekly").unwrap();
@@ -380,4 +4960,48 @@ mod test {
         println!("sc3:{schedule_3:?}\nsc4:{schedule_4:?}");
         assert!(schedule_3.timeunitspec_eq(&schedule_4));
     }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_inspect_log() {
+        use log::{Log, Metadata, Record};
+        use std::sync::Mutex;
+
+        // The logger is global, so only keep the records of this test's target.
+        struct CapturingLogger(Mutex<Vec<String>>);
+
+        impl Log for CapturingLogger {
+            fn enabled(&self, metadata: &Metadata) -> bool {
+                metadata.target() == "test_inspect_log"
+            }
+
+            fn log(&self, record: &Record) {
+                if self.enabled(record.metadata()) {
+                    assert_eq!(log::Level::Debug, record.level());
+                    self.0.lock().unwrap().push(record.args().to_string());
+                }
+            }
+
+            fn flush(&self) {}
+        }
+
+        static LOGGER: CapturingLogger = CapturingLogger(Mutex::new(Vec::new()));
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+
+        let schedule = Schedule::from_str("0 0 * * * *").unwrap();
+        let fire_times: Vec<u64> = schedule
+            .after(&0)
+            .inspect_log("test_inspect_log")
+            .take(3)
+            .collect();
+        assert_eq!(fire_times, schedule.after(&0).take(3).collect::<Vec<u64>>());
+        let messages = LOGGER.0.lock().unwrap();
+        assert_eq!(3, messages.len());
+        assert_eq!("Schedule '0 0 * * * *' fires at 3600000000000", messages[0]);
+        assert_eq!(
+            "Schedule '0 0 * * * *' fires at 10800000000000",
+            messages[2]
+        );
+    }
 }
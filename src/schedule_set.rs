@@ -0,0 +1,519 @@
+use chrono::{DateTime, TimeZone, Utc};
+use std::iter::FusedIterator;
+use std::ops::{BitAnd, BitOr};
+
+use crate::schedule::{Schedule, ScheduleIterator};
+
+/// A union of schedules, firing whenever any of its members does. Instants shared by several
+/// members are only yielded once.
+/// # Example
+/// ```
+/// use cron_schedule::{Schedule, ScheduleSet};
+/// use std::str::FromStr;
+///
+/// let morning = Schedule::from_str("0 0 9 * * *").unwrap();
+/// let evening = Schedule::from_str("0 0 17 * * *").unwrap();
+/// let both: ScheduleSet = morning | evening;
+/// assert_eq!(2, both.schedules().len());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScheduleSet {
+    schedules: Vec<Schedule>,
+}
+
+impl ScheduleSet {
+    pub fn new(schedules: Vec<Schedule>) -> ScheduleSet {
+        ScheduleSet { schedules }
+    }
+
+    pub fn schedules(&self) -> &[Schedule] {
+        &self.schedules
+    }
+
+    /// Returns the earliest fire time of any member strictly after `after`.
+    pub fn next_after(&self, after: &u64) -> Option<u64> {
+        self.schedules
+            .iter()
+            .filter_map(|schedule| schedule.next_after(after))
+            .min()
+    }
+
+    /// Provides an iterator which will return each fire time of any member starting with the
+    /// current time if applicable.
+    pub fn upcoming(&self) -> ScheduleSetIterator<'_> {
+        self.after(&(Utc::now().naive_utc().timestamp_nanos_opt().unwrap() as u64))
+    }
+
+    /// Like the `upcoming` method, but allows you to specify a start time other than the present.
+    pub fn after(&self, after: &u64) -> ScheduleSetIterator<'_> {
+        ScheduleSetIterator::new(self, after)
+    }
+
+    pub fn includes<Z>(&self, date_time: DateTime<Z>) -> bool
+    where
+        Z: TimeZone,
+    {
+        self.schedules
+            .iter()
+            .any(|schedule| schedule.includes(date_time.clone()))
+    }
+}
+
+/// An intersection of schedules, firing only at instants where every member fires.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScheduleIntersection {
+    schedules: Vec<Schedule>,
+}
+
+impl ScheduleIntersection {
+    pub fn new(schedules: Vec<Schedule>) -> ScheduleIntersection {
+        ScheduleIntersection { schedules }
+    }
+
+    pub fn schedules(&self) -> &[Schedule] {
+        &self.schedules
+    }
+
+    /// Returns the earliest instant strictly after `after` at which every member fires. An empty
+    /// intersection never fires.
+    pub fn next_after(&self, after: &u64) -> Option<u64> {
+        let first = self.schedules.first()?;
+        let mut candidate = first.next_after(after)?;
+        // Leapfrog: move the candidate to the next fire time of any member that doesn't include
+        // it, until all members agree.
+        'candidate: loop {
+            for schedule in self.schedules.iter() {
+                let next = schedule.next_at_or_after(&candidate)?;
+                if next != candidate {
+                    candidate = next;
+                    continue 'candidate;
+                }
+            }
+            return Some(candidate);
+        }
+    }
+
+    /// Provides an iterator which will return each instant where every member fires, starting
+    /// with the current time if applicable.
+    pub fn upcoming(&self) -> ScheduleIntersectionIterator<'_> {
+        self.after(&(Utc::now().naive_utc().timestamp_nanos_opt().unwrap() as u64))
+    }
+
+    /// Like the `upcoming` method, but allows you to specify a start time other than the present.
+    pub fn after(&self, after: &u64) -> ScheduleIntersectionIterator<'_> {
+        ScheduleIntersectionIterator::new(self, after)
+    }
+
+    pub fn includes<Z>(&self, date_time: DateTime<Z>) -> bool
+    where
+        Z: TimeZone,
+    {
+        !self.schedules.is_empty()
+            && self
+                .schedules
+                .iter()
+                .all(|schedule| schedule.includes(date_time.clone()))
+    }
+}
+
+/// An ordered list of schedules firing whenever any of them does, like a
+/// [ScheduleSet](struct.ScheduleSet.html), but reporting which schedule each fire time came from.
+/// When several schedules fire at the same instant, the one listed first wins.
+/// # Example
+/// ```
+/// use cron_schedule::{PrioritizedSchedules, Schedule};
+/// use std::str::FromStr;
+///
+/// let primary = Schedule::from_str("0 0 9 * * Mon-Fri").unwrap();
+/// let fallback = Schedule::from_str("0 0 9 * * *").unwrap();
+/// let schedules = PrioritizedSchedules::new(vec![primary, fallback]);
+/// let saturday = 1_623_456_000_000_000_000; // 2021-06-12 00:00:00 UTC
+/// let sources: Vec<usize> = schedules.after(&saturday).take(3).map(|(_, index)| index).collect();
+/// assert_eq!(vec![1, 1, 0], sources);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PrioritizedSchedules {
+    schedules: Vec<Schedule>,
+}
+
+impl PrioritizedSchedules {
+    pub fn new(schedules: Vec<Schedule>) -> PrioritizedSchedules {
+        PrioritizedSchedules { schedules }
+    }
+
+    pub fn schedules(&self) -> &[Schedule] {
+        &self.schedules
+    }
+
+    /// Returns the earliest fire time of any schedule strictly after `after`, with the index of
+    /// the first schedule firing then.
+    pub fn next_after(&self, after: &u64) -> Option<(u64, usize)> {
+        self.schedules
+            .iter()
+            .enumerate()
+            .filter_map(|(index, schedule)| Some((schedule.next_after(after)?, index)))
+            .min()
+    }
+
+    /// Provides an iterator which will return each fire time of any schedule with the index of
+    /// the schedule it came from, starting with the current time if applicable.
+    pub fn upcoming(&self) -> PrioritizedSchedulesIterator<'_> {
+        self.after(&(Utc::now().naive_utc().timestamp_nanos_opt().unwrap() as u64))
+    }
+
+    /// Like the `upcoming` method, but allows you to specify a start time other than the present.
+    pub fn after(&self, after: &u64) -> PrioritizedSchedulesIterator<'_> {
+        PrioritizedSchedulesIterator::new(self, after)
+    }
+}
+
+impl BitOr for Schedule {
+    type Output = ScheduleSet;
+    fn bitor(self, other: Schedule) -> ScheduleSet {
+        ScheduleSet::new(vec![self, other])
+    }
+}
+
+impl BitOr<Schedule> for ScheduleSet {
+    type Output = ScheduleSet;
+    fn bitor(mut self, other: Schedule) -> ScheduleSet {
+        self.schedules.push(other);
+        self
+    }
+}
+
+impl BitAnd for Schedule {
+    type Output = ScheduleIntersection;
+    fn bitand(self, other: Schedule) -> ScheduleIntersection {
+        ScheduleIntersection::new(vec![self, other])
+    }
+}
+
+impl BitAnd<Schedule> for ScheduleIntersection {
+    type Output = ScheduleIntersection;
+    fn bitand(mut self, other: Schedule) -> ScheduleIntersection {
+        self.schedules.push(other);
+        self
+    }
+}
+
+/// Merges the fire times of every member of a [ScheduleSet](struct.ScheduleSet.html). Members
+/// whose fire times run out are dropped from the merge, so the iterator only ends once every
+/// member is exhausted.
+pub struct ScheduleSetIterator<'a> {
+    set: &'a ScheduleSet,
+    starting_datetime: u64,
+    // The next fire time of each member still producing, with the iterator it came from. Filled
+    // in on the first call to `next`.
+    heads: Option<Vec<(u64, ScheduleIterator<'a>)>>,
+}
+
+impl<'a> ScheduleSetIterator<'a> {
+    fn new(set: &'a ScheduleSet, starting_datetime: &u64) -> ScheduleSetIterator<'a> {
+        ScheduleSetIterator {
+            set,
+            starting_datetime: *starting_datetime,
+            heads: None,
+        }
+    }
+}
+
+impl<'a> Iterator for ScheduleSetIterator<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let set = self.set;
+        let starting_datetime = self.starting_datetime;
+        let heads = self.heads.get_or_insert_with(|| {
+            set.schedules
+                .iter()
+                .filter_map(|schedule| {
+                    let mut iterator = schedule.after(&starting_datetime);
+                    iterator.next().map(|head| (head, iterator))
+                })
+                .collect()
+        });
+        let next_datetime = heads.iter().map(|(head, _)| *head).min()?;
+        // Advance every member sharing this instant so it's only yielded once, and drop members
+        // with no fire times left.
+        heads.retain_mut(|(head, iterator)| {
+            if *head != next_datetime {
+                return true;
+            }
+            match iterator.next() {
+                Some(next_head) => {
+                    *head = next_head;
+                    true
+                }
+                None => false,
+            }
+        });
+        Some(next_datetime)
+    }
+}
+
+impl<'a> FusedIterator for ScheduleSetIterator<'a> {}
+
+/// Merges the fire times of every schedule in a
+/// [PrioritizedSchedules](struct.PrioritizedSchedules.html), yielding each instant once with the
+/// index of the first schedule firing then.
+pub struct PrioritizedSchedulesIterator<'a> {
+    schedules: &'a PrioritizedSchedules,
+    starting_datetime: u64,
+    // The next fire time of each schedule still producing, with its index and the iterator it
+    // came from. Filled in on the first call to `next`.
+    heads: Option<Vec<(u64, usize, ScheduleIterator<'a>)>>,
+}
+
+impl<'a> PrioritizedSchedulesIterator<'a> {
+    fn new(
+        schedules: &'a PrioritizedSchedules,
+        starting_datetime: &u64,
+    ) -> PrioritizedSchedulesIterator<'a> {
+        PrioritizedSchedulesIterator {
+            schedules,
+            starting_datetime: *starting_datetime,
+            heads: None,
+        }
+    }
+}
+
+impl<'a> Iterator for PrioritizedSchedulesIterator<'a> {
+    type Item = (u64, usize);
+
+    fn next(&mut self) -> Option<(u64, usize)> {
+        let schedules = self.schedules;
+        let starting_datetime = self.starting_datetime;
+        let heads = self.heads.get_or_insert_with(|| {
+            schedules
+                .schedules
+                .iter()
+                .enumerate()
+                .filter_map(|(index, schedule)| {
+                    let mut iterator = schedule.after(&starting_datetime);
+                    iterator.next().map(|head| (head, index, iterator))
+                })
+                .collect()
+        });
+        let next = heads.iter().map(|(head, index, _)| (*head, *index)).min()?;
+        // Advance every schedule sharing this instant, so lower priority ones don't report it
+        // again, and drop schedules with no fire times left.
+        heads.retain_mut(|(head, _, iterator)| {
+            if *head != next.0 {
+                return true;
+            }
+            match iterator.next() {
+                Some(next_head) => {
+                    *head = next_head;
+                    true
+                }
+                None => false,
+            }
+        });
+        Some(next)
+    }
+}
+
+impl<'a> FusedIterator for PrioritizedSchedulesIterator<'a> {}
+
+pub struct ScheduleIntersectionIterator<'a> {
+    is_done: bool,
+    intersection: &'a ScheduleIntersection,
+    previous_datetime: u64,
+}
+
+impl<'a> ScheduleIntersectionIterator<'a> {
+    fn new(
+        intersection: &'a ScheduleIntersection,
+        starting_datetime: &u64,
+    ) -> ScheduleIntersectionIterator<'a> {
+        ScheduleIntersectionIterator {
+            is_done: false,
+            intersection,
+            previous_datetime: *starting_datetime,
+        }
+    }
+}
+
+impl<'a> Iterator for ScheduleIntersectionIterator<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.is_done {
+            return None;
+        }
+        if let Some(next_datetime) = self.intersection.next_after(&self.previous_datetime) {
+            self.previous_datetime = next_datetime;
+            Some(next_datetime)
+        } else {
+            self.is_done = true;
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    fn nanos(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> u64 {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, second)
+            .unwrap()
+            .timestamp_nanos_opt()
+            .unwrap() as u64
+    }
+
+    #[test]
+    fn test_union_operator_matches_explicit_set() {
+        let a = Schedule::from_str("0 0 9 * * *").unwrap();
+        let b = Schedule::from_str("0 0 9,17 * * Mon").unwrap();
+        let start = nanos(2021, 6, 13, 0, 0, 0); // A Sunday
+
+        let explicit = ScheduleSet::new(vec![a.clone(), b.clone()]);
+        let operator = a | b;
+        assert_eq!(explicit, operator);
+
+        let fire_times: Vec<u64> = operator.after(&start).take(4).collect();
+        assert_eq!(
+            fire_times,
+            explicit.after(&start).take(4).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            fire_times,
+            vec![
+                nanos(2021, 6, 13, 9, 0, 0),
+                nanos(2021, 6, 14, 9, 0, 0),
+                nanos(2021, 6, 14, 17, 0, 0),
+                nanos(2021, 6, 15, 9, 0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_intersection_operator_matches_explicit_intersection() {
+        let a = Schedule::from_str("0 0 9 * * *").unwrap();
+        let b = Schedule::from_str("0 0 * * * Mon").unwrap();
+        let start = nanos(2021, 6, 13, 0, 0, 0);
+
+        let explicit = ScheduleIntersection::new(vec![a.clone(), b.clone()]);
+        let operator = a & b;
+        assert_eq!(explicit, operator);
+
+        let fire_times: Vec<u64> = operator.after(&start).take(2).collect();
+        assert_eq!(
+            fire_times,
+            explicit.after(&start).take(2).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            fire_times,
+            vec![nanos(2021, 6, 14, 9, 0, 0), nanos(2021, 6, 21, 9, 0, 0)]
+        );
+        assert!(operator.includes(Utc.with_ymd_and_hms(2021, 6, 14, 9, 0, 0).unwrap()));
+        assert!(!operator.includes(Utc.with_ymd_and_hms(2021, 6, 15, 9, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_chained_operators() {
+        let a = Schedule::from_str("0 0 1 * * *").unwrap();
+        let b = Schedule::from_str("0 0 2 * * *").unwrap();
+        let c = Schedule::from_str("0 0 3 * * *").unwrap();
+        assert_eq!(3, (a.clone() | b.clone() | c.clone()).schedules().len());
+
+        let disjoint = a & b & c;
+        assert_eq!(3, disjoint.schedules().len());
+        assert_eq!(None, disjoint.after(&nanos(2099, 12, 1, 0, 0, 0)).next());
+    }
+
+    #[test]
+    fn test_union_continues_after_member_exhausts() {
+        let single_year = Schedule::from_str("0 0 12 * * * 2021").unwrap();
+        let monthly = Schedule::from_str("0 0 0 1 * *").unwrap();
+        let set = single_year | monthly;
+        let fire_times: Vec<u64> = set.after(&nanos(2021, 12, 30, 0, 0, 0)).take(5).collect();
+        assert_eq!(
+            fire_times,
+            vec![
+                nanos(2021, 12, 30, 12, 0, 0),
+                nanos(2021, 12, 31, 12, 0, 0),
+                nanos(2022, 1, 1, 0, 0, 0),
+                nanos(2022, 2, 1, 0, 0, 0),
+                nanos(2022, 3, 1, 0, 0, 0),
+            ]
+        );
+
+        // Only once every member is exhausted does the union end.
+        let mut iterator = set.after(&nanos(2100, 11, 15, 0, 0, 0));
+        assert_eq!(Some(nanos(2100, 12, 1, 0, 0, 0)), iterator.next());
+        assert_eq!(None, iterator.next());
+        assert_eq!(None, iterator.next());
+    }
+
+    #[test]
+    fn test_union_yields_shared_instants_once() {
+        let a = Schedule::from_str("0 0 * * * *").unwrap();
+        let b = Schedule::from_str("0 0,30 * * * *").unwrap();
+        let start = nanos(2021, 6, 15, 0, 0, 0);
+        let fire_times: Vec<u64> = (a | b).after(&start).take(3).collect();
+        assert_eq!(
+            fire_times,
+            vec![
+                nanos(2021, 6, 15, 0, 30, 0),
+                nanos(2021, 6, 15, 1, 0, 0),
+                nanos(2021, 6, 15, 1, 30, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_sets() {
+        let start = nanos(2021, 6, 13, 0, 0, 0);
+        assert_eq!(None, ScheduleSet::default().after(&start).next());
+        assert_eq!(None, ScheduleIntersection::default().after(&start).next());
+    }
+
+    #[test]
+    fn test_prioritized_schedules() {
+        let primary = Schedule::from_str("0 0 9 * * Mon-Fri").unwrap();
+        let secondary = Schedule::from_str("0 0 9,12 * * *").unwrap();
+        let schedules = PrioritizedSchedules::new(vec![primary, secondary]);
+        let start = nanos(2021, 6, 11, 10, 0, 0); // A Friday
+
+        let fire_times: Vec<(u64, usize)> = schedules.after(&start).take(6).collect();
+        assert_eq!(
+            fire_times,
+            vec![
+                (nanos(2021, 6, 11, 12, 0, 0), 1),
+                (nanos(2021, 6, 12, 9, 0, 0), 1),
+                (nanos(2021, 6, 12, 12, 0, 0), 1),
+                (nanos(2021, 6, 13, 9, 0, 0), 1),
+                (nanos(2021, 6, 13, 12, 0, 0), 1),
+                // Both fire on Monday morning, the primary wins.
+                (nanos(2021, 6, 14, 9, 0, 0), 0),
+            ]
+        );
+        assert_eq!(
+            Some((nanos(2021, 6, 14, 9, 0, 0), 0)),
+            schedules.next_after(&nanos(2021, 6, 14, 0, 0, 0))
+        );
+
+        // Ties go to the lowest index whatever the order of the schedules.
+        let hourly = Schedule::from_str("0 0 * * * *").unwrap();
+        let half_hourly = Schedule::from_str("0 0,30 * * * *").unwrap();
+        let schedules = PrioritizedSchedules::new(vec![half_hourly, hourly]);
+        let fire_times: Vec<(u64, usize)> = schedules
+            .after(&nanos(2021, 6, 15, 0, 0, 0))
+            .take(2)
+            .collect();
+        assert_eq!(
+            fire_times,
+            vec![
+                (nanos(2021, 6, 15, 0, 30, 0), 0),
+                (nanos(2021, 6, 15, 1, 0, 0), 0)
+            ]
+        );
+
+        assert_eq!(None, PrioritizedSchedules::default().after(&start).next());
+        assert_eq!(None, PrioritizedSchedules::default().next_after(&start));
+    }
+}
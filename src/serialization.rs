@@ -0,0 +1,368 @@
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[cfg(feature = "json")]
+use crate::error::{Error, ErrorKind};
+use crate::options::ParseOptions;
+use crate::ordinal::OrdinalSet;
+use crate::schedule::{Schedule, ScheduleFields};
+use crate::time_unit::*;
+
+/// The version of the envelope schedules are serialized in.
+const FORMAT_VERSION: u32 = 1;
+
+// Schedules used to be stored as bare expressions, which are still accepted.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredSchedule {
+    Expression(String),
+    Versioned { v: u32, expr: String },
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    v: u32,
+    expr: &'a str,
+}
+
+// The expression a schedule is stored as. A schedule parsed with options, such as
+// `no_seconds` or `iso_days_of_week`, may not mean the same thing when its source is read again
+// without them, so it's stored in its normalized form instead.
+fn stored_expression(schedule: &Schedule) -> String {
+    let source = schedule.to_string();
+    match Schedule::from_str(&source) {
+        Ok(reparsed) if reparsed.timeunitspec_eq(schedule) => source,
+        _ => schedule.normalize().to_string(),
+    }
+}
+
+// The options stored expressions are read with. They only accept more syntax, so any expression
+// the defaults accept means the same thing.
+fn stored_options() -> ParseOptions {
+    ParseOptions::new()
+        .allow_day_of_year(true)
+        .allow_iso_week(true)
+        .allow_custom_directives(true)
+}
+
+/// Schedules serialize as `{ "v": 1, "expr": "..." }`, so the format can change without breaking
+/// stored schedules. The expression is the one the schedule was parsed from, unless it was parsed
+/// with options that change what it means, in which case the
+/// [normalized](struct.Schedule.html#method.normalize) expression is stored. Deserializing also
+/// accepts a bare expression.
+impl Serialize for Schedule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Envelope {
+            v: FORMAT_VERSION,
+            expr: &stored_expression(self),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Schedule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match StoredSchedule::deserialize(deserializer)? {
+            StoredSchedule::Expression(expression) => Schedule::from_str(&expression),
+            StoredSchedule::Versioned {
+                v: FORMAT_VERSION,
+                expr,
+            } => Schedule::parse_with(&expr, &stored_options()),
+            StoredSchedule::Versioned { v, .. } => {
+                return Err(de::Error::custom(format!(
+                    "unsupported schedule format version {}",
+                    v
+                )))
+            }
+        }
+        .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(feature = "json")]
+impl Schedule {
+    /// Parses a schedule from a JSON value holding its expression, either as a string or as an
+    /// array of a single string, as some logs write it. Any other value is an error.
+    /// # Example
+    /// ```
+    /// use cron_schedule::Schedule;
+    ///
+    /// let value = serde_json::json!(["0 0 * * * *"]);
+    /// let schedule = Schedule::from_json_value(value).unwrap();
+    /// assert_eq!("0 0 * * * *", schedule.to_string());
+    /// ```
+    pub fn from_json_value(value: serde_json::Value) -> Result<Schedule, Error> {
+        use serde_json::Value;
+        let expression = match value {
+            Value::String(expression) => expression,
+            Value::Array(values) => match <[Value; 1]>::try_from(values) {
+                Ok([Value::String(expression)]) => expression,
+                _ => return Err(json_shape_error("an array other than one string")),
+            },
+            Value::Null => return Err(json_shape_error("null")),
+            Value::Bool(_) => return Err(json_shape_error("a boolean")),
+            Value::Number(_) => return Err(json_shape_error("a number")),
+            Value::Object(_) => return Err(json_shape_error("an object")),
+        };
+        Schedule::from_str(&expression)
+    }
+}
+
+#[cfg(feature = "json")]
+fn json_shape_error(found: &str) -> Error {
+    ErrorKind::Expression(format!(
+        "Expected a JSON string or an array of one string, found {}.",
+        found
+    ))
+    .into()
+}
+
+/// A parsed schedule stored with its fields, so loading it doesn't parse the expression again.
+/// Unlike `Schedule`, which serializes as its expression, this is meant for caching schedules in
+/// compact binary formats such as postcard or bincode. Each field is stored as its list of
+/// values, or as nothing if it matches every value, and is checked against the unit's bounds when
+/// loaded. A cache written with a different format version fails to load, and should be rebuilt
+/// from the expressions.
+/// # Example
+/// ```
+/// use cron_schedule::{CompiledSchedule, Schedule};
+/// use std::str::FromStr;
+///
+/// let schedule = Schedule::from_str("0 30 9 * * Mon-Fri").unwrap();
+/// let bytes = postcard::to_allocvec(&CompiledSchedule::from(&schedule)).unwrap();
+/// let compiled: CompiledSchedule = postcard::from_bytes(&bytes).unwrap();
+/// assert_eq!(schedule, Schedule::from(compiled));
+/// ```
+#[derive(Clone, Debug)]
+pub struct CompiledSchedule {
+    source: String,
+    fields: ScheduleFields,
+}
+
+#[derive(Serialize)]
+struct CompiledEnvelope<'a> {
+    v: u32,
+    source: &'a str,
+    fields: &'a ScheduleFields,
+}
+
+#[derive(Deserialize)]
+struct StoredCompiledSchedule {
+    v: u32,
+    source: String,
+    fields: ScheduleFields,
+}
+
+impl From<&Schedule> for CompiledSchedule {
+    fn from(schedule: &Schedule) -> CompiledSchedule {
+        CompiledSchedule {
+            source: schedule.to_string(),
+            fields: schedule.schedule_fields().clone(),
+        }
+    }
+}
+
+impl From<CompiledSchedule> for Schedule {
+    fn from(compiled: CompiledSchedule) -> Schedule {
+        Schedule::new(compiled.source, compiled.fields)
+    }
+}
+
+impl Serialize for CompiledSchedule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        CompiledEnvelope {
+            v: FORMAT_VERSION,
+            source: &self.source,
+            fields: &self.fields,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CompiledSchedule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let stored = StoredCompiledSchedule::deserialize(deserializer)?;
+        if stored.v != FORMAT_VERSION {
+            return Err(de::Error::custom(format!(
+                "unsupported schedule format version {}",
+                stored.v
+            )));
+        }
+        Ok(CompiledSchedule {
+            source: stored.source,
+            fields: stored.fields,
+        })
+    }
+}
+
+// Fields matching every value are stored as `None` rather than listing the values.
+macro_rules! serde_time_unit {
+    ($($unit:ident),*) => {$(
+        impl Serialize for $unit {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                (!self.is_all())
+                    .then(|| self.ordinals())
+                    .serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $unit {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                match Option::<OrdinalSet>::deserialize(deserializer)? {
+                    None => Ok($unit::all()),
+                    Some(ordinals) => {
+                        for ordinal in &ordinals {
+                            $unit::validate_ordinal(*ordinal).map_err(de::Error::custom)?;
+                        }
+                        Ok($unit::from_ordinal_set(ordinals))
+                    }
+                }
+            }
+        }
+    )*};
+}
+
+serde_time_unit!(
+    Seconds,
+    Minutes,
+    Hours,
+    DaysOfMonth,
+    Months,
+    DaysOfWeek,
+    Years,
+    DaysOfYear,
+    WeeksOfYear
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_serialize_envelope() {
+        let schedule = Schedule::from_str("0 30 9 * * Mon-Fri").unwrap();
+        let json = serde_json::to_string(&schedule).unwrap();
+        assert_eq!(r#"{"v":1,"expr":"0 30 9 * * Mon-Fri"}"#, json);
+        let deserialized: Schedule = serde_json::from_str(&json).unwrap();
+        assert_eq!(schedule, deserialized);
+    }
+
+    #[test]
+    fn test_deserialize_legacy_and_envelope() {
+        let legacy: Schedule = serde_json::from_str(r#""@hourly""#).unwrap();
+        assert_eq!("@hourly", legacy.to_string());
+        let versioned: Schedule = serde_json::from_str(r#"{ "v": 1, "expr": "@hourly" }"#).unwrap();
+        assert_eq!(legacy, versioned);
+
+        let error = serde_json::from_str::<Schedule>(r#"{ "v": 2, "expr": "@hourly" }"#)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("version 2"), "{}", error);
+        assert!(serde_json::from_str::<Schedule>(r#""* * *""#).is_err());
+        assert!(serde_json::from_str::<Schedule>(r#"{ "expr": "@hourly" }"#).is_err());
+    }
+
+    #[test]
+    fn test_round_trip_with_options() {
+        let iso = ParseOptions::new().iso_days_of_week(true);
+        let schedules = [
+            Schedule::parse_with("30 9 * * *", &ParseOptions::new().no_seconds(true)).unwrap(),
+            Schedule::parse_with("0 0 9 * * 1", &iso).unwrap(),
+            Schedule::parse_with("0 ? 9 * * ?", &ParseOptions::new().quartz_lenient(true)).unwrap(),
+            Schedule::parse_with(
+                "0 0 9 * * *",
+                &ParseOptions::new().default_years(Years::from_range(2030, 2031).unwrap()),
+            )
+            .unwrap(),
+            Schedule::parse_with("0 0 0 * * * * 60", &stored_options()).unwrap(),
+            Schedule::parse_with("0 0 9 * * Mon W1-10", &stored_options()).unwrap(),
+            Schedule::parse_with("@startup", &stored_options()).unwrap(),
+        ];
+        for schedule in schedules {
+            let json = serde_json::to_string(&schedule).unwrap();
+            let loaded: Schedule = serde_json::from_str(&json).unwrap();
+            assert!(loaded.timeunitspec_eq(&schedule), "{}", json);
+            assert_eq!(schedule.custom_directive(), loaded.custom_directive());
+            let fire_times: Vec<u64> = schedule.after(&0).take(20).collect();
+            assert_eq!(fire_times, loaded.after(&0).take(20).collect::<Vec<u64>>());
+        }
+        // The ISO Monday is stored so that it is still read as Monday.
+        let json = serde_json::to_string(&Schedule::parse_with("0 0 9 * * 1", &iso).unwrap());
+        assert_eq!(r#"{"v":1,"expr":"0 0 9 ? * 2 *"}"#, json.unwrap());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_from_json_value() {
+        let schedule = Schedule::from_str("0 0 * * * *").unwrap();
+        let value = serde_json::json!("0 0 * * * *");
+        assert_eq!(schedule, Schedule::from_json_value(value).unwrap());
+        let logged: serde_json::Value = serde_json::from_str(r#"["0 0 * * * *"]"#).unwrap();
+        assert_eq!(schedule, Schedule::from_json_value(logged).unwrap());
+
+        let rejected = [
+            (serde_json::json!({ "expr": "0 0 * * * *" }), "an object"),
+            (serde_json::json!([]), "an array"),
+            (serde_json::json!(["0 0 * * * *", "@daily"]), "an array"),
+            (serde_json::json!([["0 0 * * * *"]]), "an array"),
+            (serde_json::json!(42), "a number"),
+            (serde_json::json!(null), "null"),
+        ];
+        for (value, found) in rejected {
+            let error = Schedule::from_json_value(value).unwrap_err().to_string();
+            assert!(error.contains(found), "{}", error);
+        }
+        // The expression itself must still be valid.
+        assert!(Schedule::from_json_value(serde_json::json!(["* * *"])).is_err());
+    }
+
+    #[test]
+    fn test_compiled_schedule_round_trip() {
+        let options = crate::ParseOptions::new()
+            .allow_day_of_year(true)
+            .default_years(Years::from_range(2020, 2030).unwrap());
+        let schedules = [
+            Schedule::from_str("0   30   9,12,15     1,15       May-Aug  Mon,Wed,Fri  2018/2")
+                .unwrap(),
+            Schedule::from_str("@daily").unwrap(),
+            Schedule::from_str("0 0 12 ? * L").unwrap(),
+            Schedule::parse_with("0 0 0 * * * * 60", &options).unwrap(),
+            Schedule::parse_with("*/15 * * * *", &options.clone().no_seconds(true)).unwrap(),
+        ];
+        for schedule in schedules {
+            let bytes = postcard::to_allocvec(&CompiledSchedule::from(&schedule)).unwrap();
+            let compiled: CompiledSchedule = postcard::from_bytes(&bytes).unwrap();
+            let loaded = Schedule::from(compiled);
+            assert_eq!(schedule, loaded);
+            assert!(loaded.timeunitspec_eq(&schedule));
+            assert_eq!(schedule.fields_count(), loaded.fields_count());
+            assert_eq!(schedule.extensions(), loaded.extensions());
+            let fire_times: Vec<u64> = schedule.after(&0).take(50).collect();
+            assert_eq!(fire_times, loaded.after(&0).take(50).collect::<Vec<u64>>());
+            assert_eq!(
+                schedule.with_hours(Hours::from_ordinal(3)).to_string(),
+                loaded.with_hours(Hours::from_ordinal(3)).to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn test_compiled_schedule_is_validated() {
+        let schedule = Schedule::from_str("0 0 9 * * *").unwrap();
+        let json = serde_json::to_value(CompiledSchedule::from(&schedule)).unwrap();
+        assert_eq!(1, json["v"]);
+        assert_eq!(serde_json::json!([9]), json["fields"]["hours"]);
+        assert_eq!(serde_json::Value::Null, json["fields"]["months"]);
+
+        let mut out_of_range = json.clone();
+        out_of_range["fields"]["hours"] = serde_json::json!([9, 24]);
+        let error = serde_json::from_value::<CompiledSchedule>(out_of_range).unwrap_err();
+        assert!(error.to_string().contains("24"), "{}", error);
+        let mut other_version = json;
+        other_version["v"] = serde_json::json!(2);
+        let error = serde_json::from_value::<CompiledSchedule>(other_version).unwrap_err();
+        assert!(error.to_string().contains("version 2"), "{}", error);
+    }
+}
@@ -6,6 +6,9 @@ pub enum Specifier {
     Point(Ordinal),
     Range(Ordinal, Ordinal),
     NamedRange(String, String),
+    // `R(start-end)`, replaced by a random point of the range when the schedule is parsed with a
+    // seed.
+    Random(Ordinal, Ordinal),
 }
 
 // Separating out a root specifier allows for a higher tiered specifier, allowing us to achieve
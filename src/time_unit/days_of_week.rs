@@ -6,6 +6,10 @@ use std::borrow::Cow;
 
 static ALL: Lazy<OrdinalSet> = Lazy::new(DaysOfWeek::supported_ordinals);
 
+/// Names standing for several days of the week, with the list they expand to. These aren't
+/// recognized by default, see `Schedule::from_str_with_day_groups`.
+pub(crate) const DAY_GROUPS: [(&str, &str); 2] = [("weekday", "MON-FRI"), ("weekend", "SAT,SUN")];
+
 #[derive(Clone, Debug, Eq)]
 pub struct DaysOfWeek {
     ordinals: Option<OrdinalSet>,
@@ -36,6 +40,12 @@ impl TimeUnitField for DaysOfWeek {
             "thu" | "thurs" | "thursday" => 5,
             "fri" | "friday" => 6,
             "sat" | "saturday" => 7,
+            "l" => {
+                return Err(ErrorKind::Expression(
+                    "'L' can't be used in a day of the week range.".to_owned(),
+                )
+                .into())
+            }
             _ => {
                 return Err(ErrorKind::Expression(format!(
                     "'{}' is not a valid day of the week.",
@@ -46,6 +56,14 @@ impl TimeUnitField for DaysOfWeek {
         };
         Ok(ordinal)
     }
+    fn ordinal_from_point_name(name: &str) -> Result<Ordinal, Error> {
+        // Some dialects accept a bare `L` for the last day of the week. Weeks start on
+        // Sunday, so that's Saturday.
+        if name.eq_ignore_ascii_case("l") {
+            return Ok(7);
+        }
+        Self::ordinal_from_name(name)
+    }
     fn ordinals(&self) -> &OrdinalSet {
         match &self.ordinals {
             Some(ordinal_set) => ordinal_set,
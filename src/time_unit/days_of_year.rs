@@ -0,0 +1,43 @@
+use crate::ordinal::{Ordinal, OrdinalSet};
+use crate::time_unit::TimeUnitField;
+use once_cell::sync::Lazy;
+use std::borrow::Cow;
+
+static ALL: Lazy<OrdinalSet> = Lazy::new(DaysOfYear::supported_ordinals);
+
+/// The days of the year, from 1 for January 1st through 366, which only leap years have. This is
+/// the optional eighth field enabled by
+/// [ParseOptions::allow_day_of_year](struct.ParseOptions.html#method.allow_day_of_year).
+#[derive(Clone, Debug, Eq)]
+pub struct DaysOfYear {
+    ordinals: Option<OrdinalSet>,
+}
+
+impl TimeUnitField for DaysOfYear {
+    fn from_optional_ordinal_set(ordinal_set: Option<OrdinalSet>) -> Self {
+        DaysOfYear {
+            ordinals: ordinal_set,
+        }
+    }
+    fn name() -> Cow<'static, str> {
+        Cow::from("Days of Year")
+    }
+    fn inclusive_min() -> Ordinal {
+        1
+    }
+    fn inclusive_max() -> Ordinal {
+        366
+    }
+    fn ordinals(&self) -> &OrdinalSet {
+        match &self.ordinals {
+            Some(ordinal_set) => ordinal_set,
+            None => &ALL,
+        }
+    }
+}
+
+impl PartialEq for DaysOfYear {
+    fn eq(&self, other: &DaysOfYear) -> bool {
+        self.ordinals() == other.ordinals()
+    }
+}
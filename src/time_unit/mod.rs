@@ -1,18 +1,23 @@
 mod days_of_month;
 mod days_of_week;
+mod days_of_year;
 mod hours;
 mod minutes;
 mod months;
 mod seconds;
+mod weeks_of_year;
 mod years;
 
 pub use self::days_of_month::DaysOfMonth;
 pub use self::days_of_week::DaysOfWeek;
+pub(crate) use self::days_of_week::DAY_GROUPS;
+pub use self::days_of_year::DaysOfYear;
 pub use self::hours::Hours;
 pub use self::minutes::Minutes;
 pub use self::months::Months;
 pub use self::seconds::Seconds;
-pub use self::years::Years;
+pub use self::weeks_of_year::WeeksOfYear;
+pub use self::years::{Years, MAX_YEAR};
 
 use crate::error::*;
 use crate::ordinal::{Ordinal, OrdinalSet};
@@ -22,6 +27,21 @@ use std::collections::btree_set;
 use std::iter;
 use std::ops::RangeBounds;
 
+/// The unit of time a schedule field describes, as listed by
+/// [Schedule::fields](struct.Schedule.html#method.fields).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UnitKind {
+    Seconds,
+    Minutes,
+    Hours,
+    DaysOfMonth,
+    Months,
+    DaysOfWeek,
+    Years,
+    DaysOfYear,
+    WeeksOfYear,
+}
+
 pub struct OrdinalIter<'a> {
     set_iter: btree_set::Iter<'a, Ordinal>,
 }
@@ -123,7 +143,7 @@ pub trait TimeUnitSpec {
     /// assert_eq!(Some(8), summer.next());
     /// assert_eq!(None, summer.next());
     /// ```
-    fn iter(&self) -> OrdinalIter;
+    fn iter(&self) -> OrdinalIter<'_>;
 
     /// Provides an iterator which will return each included ordinal within the specified range.
     /// # Example
@@ -140,9 +160,10 @@ pub trait TimeUnitSpec {
     /// assert_eq!(Some(15), mid_month_paydays.next());
     /// assert_eq!(None, mid_month_paydays.next());
     /// ```
-    fn range<R>(&self, range: R) -> OrdinalRangeIter
+    fn range<R>(&self, range: R) -> OrdinalRangeIter<'_>
     where
-        R: RangeBounds<Ordinal>;
+        R: RangeBounds<Ordinal>,
+        Self: Sized;
 
     /// Returns the number of ordinals included in the associated schedule
     /// # Example
@@ -170,6 +191,42 @@ pub trait TimeUnitSpec {
     /// assert_eq!(true, schedule.months().is_all());
     /// ```
     fn is_all(&self) -> bool;
+
+    /// Returns the start and step if the included ordinals are every `step`th one from `start`
+    /// through the end of the unit, as written `start/step`. A field of `*` is `(min, 1)`, while
+    /// single ordinals, steps stopping before the end and irregular sets return `None`. The
+    /// default implementation doesn't know where the unit ends, so it accepts any evenly spaced
+    /// ordinals.
+    /// # Example
+    /// ```
+    /// use cron_schedule::{Schedule, TimeUnitSpec};
+    /// use std::str::FromStr;
+    ///
+    /// let expression = "5/10 */15 9,12,15 * * * *";
+    /// let schedule = Schedule::from_str(expression).expect("Failed to parse expression.");
+    ///
+    /// assert_eq!(Some((5, 10)), schedule.seconds().as_period());
+    /// assert_eq!(Some((0, 15)), schedule.minutes().as_period());
+    /// assert_eq!(None, schedule.hours().as_period());
+    /// ```
+    fn as_period(&self) -> Option<(Ordinal, u32)> {
+        progression(self.iter()).map(|(start, step, _)| (start, step))
+    }
+}
+
+// Returns the start, step and last ordinal if the ordinals are evenly spaced, and there are at
+// least two of them.
+fn progression(mut ordinals: OrdinalIter<'_>) -> Option<(Ordinal, u32, Ordinal)> {
+    let (start, second) = (ordinals.next()?, ordinals.next()?);
+    let step = second - start;
+    let mut last = second;
+    for ordinal in ordinals {
+        if ordinal != last + step {
+            return None;
+        }
+        last = ordinal;
+    }
+    Some((start, step, last))
 }
 
 impl<T> TimeUnitSpec for T
@@ -179,7 +236,7 @@ where
     fn includes(&self, ordinal: Ordinal) -> bool {
         self.ordinals().contains(&ordinal)
     }
-    fn iter(&self) -> OrdinalIter {
+    fn iter(&self) -> OrdinalIter<'_> {
         OrdinalIter {
             set_iter: TimeUnitField::ordinals(self).iter(),
         }
@@ -187,6 +244,7 @@ where
     fn range<R>(&'_ self, range: R) -> OrdinalRangeIter<'_>
     where
         R: RangeBounds<Ordinal>,
+        Self: Sized,
     {
         OrdinalRangeIter {
             range_iter: TimeUnitField::ordinals(self).range(range),
@@ -196,12 +254,26 @@ where
         self.ordinals().len() as u32
     }
 
+    fn as_period(&self) -> Option<(Ordinal, u32)> {
+        let (start, step, last) = progression(self.iter())?;
+        // The next step would be past the end of the unit.
+        (last + step > Self::inclusive_max()).then_some((start, step))
+    }
+
     fn is_all(&self) -> bool {
         let max_supported_ordinals = Self::inclusive_max() - Self::inclusive_min() + 1;
-        self.ordinals().len() == max_supported_ordinals as usize
+        let ordinals = self.ordinals();
+        // Some units (years) accept ordinals outside the span covered by `*`, so the count alone
+        // isn't enough.
+        ordinals.len() == max_supported_ordinals as usize
+            && ordinals.first() == Some(&Self::inclusive_min())
+            && ordinals.last() == Some(&Self::inclusive_max())
     }
 }
 
+/// Constructors and metadata shared by the field types, such as `Seconds` or `Years`. Fields
+/// built this way can replace those of a parsed schedule, see
+/// [Schedule::with_seconds](struct.Schedule.html#method.with_seconds).
 pub trait TimeUnitField
 where
     Self: Sized,
@@ -237,10 +309,15 @@ where
         ))
         .into())
     }
+    /// Resolves a name that makes up a whole list element, as opposed to a range bound.
+    /// Defaults to [`ordinal_from_name`](Self::ordinal_from_name).
+    fn ordinal_from_point_name(name: &str) -> Result<Ordinal, Error> {
+        Self::ordinal_from_name(name)
+    }
     fn validate_ordinal(ordinal: Ordinal) -> Result<Ordinal, Error> {
         //println!("validate_ordinal for {} => {}", Self::name(), ordinal);
         match ordinal {
-            i if i < Self::inclusive_min() => Err(ErrorKind::Expression(format!(
+            i if i < Self::inclusive_min() => Err(ErrorKind::OutOfRange(format!(
                 "{} must be greater than or equal to {}. ('{}' \
                  specified.)",
                 Self::name(),
@@ -248,7 +325,7 @@ where
                 i
             ))
             .into()),
-            i if i > Self::inclusive_max() => Err(ErrorKind::Expression(format!(
+            i if i > Self::inclusive_max() => Err(ErrorKind::OutOfRange(format!(
                 "{} must be less than {}. ('{}' specified.)",
                 Self::name(),
                 Self::inclusive_max(),
@@ -259,60 +336,129 @@ where
         }
     }
 
+    /// Returns true if a range whose start is after its end, such as `22-6` for hours, stands for
+    /// the ordinals from the start through the maximum and then from the minimum through the end.
+    /// Years don't wrap around.
+    fn wraps_around() -> bool {
+        true
+    }
+
+    #[doc(hidden)]
     fn ordinals_from_specifier(specifier: &Specifier) -> Result<OrdinalSet, Error> {
+        Ok(Self::ordinal_sequence_from_specifier(specifier)?
+            .into_iter()
+            .collect())
+    }
+
+    // Lists the ordinals of a specifier in the order they occur, so that stepping through a range
+    // which wraps around continues past the maximum. Every ordinal is validated before anything
+    // is expanded, so an out of range bound fails without building the range.
+    #[doc(hidden)]
+    fn ordinal_sequence_from_specifier(specifier: &Specifier) -> Result<Vec<Ordinal>, Error> {
         use self::Specifier::*;
         //println!("ordinals_from_specifier for {} => {:?}", Self::name(), specifier);
-        match *specifier {
-            All => Ok(Self::supported_ordinals()),
-            Point(ordinal) => Ok(([ordinal]).iter().cloned().collect()),
-            Range(start, end) => {
-                match (Self::validate_ordinal(start), Self::validate_ordinal(end)) {
-                    (Ok(start), Ok(end)) if start <= end => Ok((start..end + 1).collect()),
-                    _ => Err(ErrorKind::Expression(format!(
-                        "Invalid range for {}: {}-{}",
-                        Self::name(),
-                        start,
-                        end
-                    ))
-                    .into()),
-                }
+        let range = |start: Ordinal, end: Ordinal| -> Result<Option<Vec<Ordinal>>, Error> {
+            let start = Self::validate_ordinal(start)?;
+            let end = Self::validate_ordinal(end)?;
+            if start <= end {
+                Ok(Some((start..=end).collect()))
+            } else if Self::wraps_around() {
+                Ok(Some(
+                    (start..=Self::inclusive_max())
+                        .chain(Self::inclusive_min()..=end)
+                        .collect(),
+                ))
+            } else {
+                Ok(None)
             }
+        };
+        match *specifier {
+            All => Ok(Self::supported_ordinals().into_iter().collect()),
+            Point(ordinal) => Ok(vec![Self::validate_ordinal(ordinal)?]),
+            Range(start, end) => range(start, end)?.ok_or_else(|| {
+                ErrorKind::Expression(format!(
+                    "Invalid range for {}: {}-{}",
+                    Self::name(),
+                    start,
+                    end
+                ))
+                .into()
+            }),
+            Random(start, end) => Err(ErrorKind::Expression(format!(
+                "R({}-{}) in {} needs a random seed, see Schedule::from_str_with_seed.",
+                start,
+                end,
+                Self::name()
+            ))
+            .into()),
             NamedRange(ref start_name, ref end_name) => {
                 let start = Self::ordinal_from_name(start_name)?;
                 let end = Self::ordinal_from_name(end_name)?;
-                match (Self::validate_ordinal(start), Self::validate_ordinal(end)) {
-                    (Ok(start), Ok(end)) if start <= end => Ok((start..end + 1).collect()),
-                    _ => Err(ErrorKind::Expression(format!(
+                range(start, end)?.ok_or_else(|| {
+                    ErrorKind::Expression(format!(
                         "Invalid named range for {}: {}-{}",
                         Self::name(),
                         start_name,
                         end_name
                     ))
-                    .into()),
-                }
+                    .into()
+                })
             }
         }
     }
 
+    /// Renders the field's ordinals as a cron field, collapsing runs of three or more consecutive
+    /// ordinals into ranges, e.g. `1-5,7,9`.
+    fn to_field_string(&self) -> String {
+        if self.is_all() {
+            return String::from("*");
+        }
+        let mut parts = Vec::new();
+        let mut ordinals = self.ordinals().iter().cloned().peekable();
+        while let Some(start) = ordinals.next() {
+            let mut end = start;
+            while ordinals.peek() == Some(&(end + 1)) {
+                end = ordinals.next().unwrap();
+            }
+            match end - start {
+                0 => parts.push(start.to_string()),
+                1 => parts.push(format!("{},{}", start, end)),
+                _ => parts.push(format!("{}-{}", start, end)),
+            }
+        }
+        parts.join(",")
+    }
+
+    #[doc(hidden)]
     fn ordinals_from_root_specifier(root_specifier: &RootSpecifier) -> Result<OrdinalSet, Error> {
         let ordinals = match root_specifier {
             RootSpecifier::Specifier(specifier) => Self::ordinals_from_specifier(specifier)?,
+            RootSpecifier::Period(_, 0) => {
+                return Err(ErrorKind::Expression(format!(
+                    "The step for {} must be greater than 0.",
+                    Self::name()
+                ))
+                .into())
+            }
             RootSpecifier::Period(start, step) => {
-                let base_set = match start {
+                let base_sequence = match start {
                     // A point prior to a period implies a range whose start is the specified
                     // point and terminating inclusively with the inclusive max
                     Specifier::Point(start) => {
                         let start = Self::validate_ordinal(*start)?;
                         (start..=Self::inclusive_max()).collect()
                     }
-                    specifier => Self::ordinals_from_specifier(specifier)?,
+                    specifier => Self::ordinal_sequence_from_specifier(specifier)?,
                 };
-                base_set.into_iter().step_by(*step as usize).collect()
+                base_sequence.into_iter().step_by(*step as usize).collect()
+            }
+            RootSpecifier::NamedPoint(ref name) => {
+                let ordinal = Self::ordinal_from_point_name(name)?;
+                ([Self::validate_ordinal(ordinal)?])
+                    .iter()
+                    .cloned()
+                    .collect::<OrdinalSet>()
             }
-            RootSpecifier::NamedPoint(ref name) => ([Self::ordinal_from_name(name)?])
-                .iter()
-                .cloned()
-                .collect::<OrdinalSet>(),
         };
         Ok(ordinals)
     }
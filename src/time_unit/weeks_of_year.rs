@@ -0,0 +1,43 @@
+use crate::ordinal::{Ordinal, OrdinalSet};
+use crate::time_unit::TimeUnitField;
+use once_cell::sync::Lazy;
+use std::borrow::Cow;
+
+static ALL: Lazy<OrdinalSet> = Lazy::new(WeeksOfYear::supported_ordinals);
+
+/// The weeks of the ISO 8601 week-numbering year, from 1 for the week containing the first
+/// Thursday of the year through 53, which only some years have. This is the optional `W` field
+/// enabled by [ParseOptions::allow_iso_week](struct.ParseOptions.html#method.allow_iso_week).
+#[derive(Clone, Debug, Eq)]
+pub struct WeeksOfYear {
+    ordinals: Option<OrdinalSet>,
+}
+
+impl TimeUnitField for WeeksOfYear {
+    fn from_optional_ordinal_set(ordinal_set: Option<OrdinalSet>) -> Self {
+        WeeksOfYear {
+            ordinals: ordinal_set,
+        }
+    }
+    fn name() -> Cow<'static, str> {
+        Cow::from("Weeks of Year")
+    }
+    fn inclusive_min() -> Ordinal {
+        1
+    }
+    fn inclusive_max() -> Ordinal {
+        53
+    }
+    fn ordinals(&self) -> &OrdinalSet {
+        match &self.ordinals {
+            Some(ordinal_set) => ordinal_set,
+            None => &ALL,
+        }
+    }
+}
+
+impl PartialEq for WeeksOfYear {
+    fn eq(&self, other: &WeeksOfYear) -> bool {
+        self.ordinals() == other.ordinals()
+    }
+}
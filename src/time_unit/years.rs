@@ -1,3 +1,4 @@
+use crate::error::*;
 use crate::ordinal::{Ordinal, OrdinalSet};
 use crate::time_unit::TimeUnitField;
 use once_cell::sync::Lazy;
@@ -5,11 +6,47 @@ use std::borrow::Cow;
 
 static ALL: Lazy<OrdinalSet> = Lazy::new(Years::supported_ordinals);
 
+/// The latest year that can be scheduled. Fire times are reported as `u64` nanoseconds since the
+/// Unix epoch, which overflows during 2554.
+pub const MAX_YEAR: Ordinal = 2554;
+
+/// The years a schedule fires in. Without a years field, or with `*`, these are 1970 through 2100,
+/// so iterating over any schedule eventually ends. The span can be changed with
+/// [ParseOptions::default_years](struct.ParseOptions.html#method.default_years).
 #[derive(Clone, Debug, Eq)]
 pub struct Years {
     ordinals: Option<OrdinalSet>,
 }
 
+impl Years {
+    /// Creates a `Years` field including every year from `min` to `max`, inclusive.
+    ///
+    /// Unlike `*`, which covers 1970 through 2100, the range may extend up to
+    /// [MAX_YEAR](constant.MAX_YEAR.html).
+    /// # Example
+    /// ```
+    /// use cron_schedule::{TimeUnitSpec, Years};
+    ///
+    /// let years = Years::from_range(2090, 2110).unwrap();
+    /// assert_eq!(21, years.count());
+    /// assert!(Years::from_range(2090, 3000).is_err());
+    /// ```
+    pub fn from_range(min: Ordinal, max: Ordinal) -> Result<Years, Error> {
+        let min = Self::validate_ordinal(min)?;
+        let max = Self::validate_ordinal(max)?;
+        if min > max {
+            return Err(ErrorKind::Expression(format!(
+                "Invalid range for {}: {}-{}",
+                Self::name(),
+                min,
+                max
+            ))
+            .into());
+        }
+        Ok(Self::from_ordinal_set((min..=max).collect()))
+    }
+}
+
 impl TimeUnitField for Years {
     fn from_optional_ordinal_set(ordinal_set: Option<OrdinalSet>) -> Self {
         Years {
@@ -28,6 +65,30 @@ impl TimeUnitField for Years {
     fn inclusive_max() -> Ordinal {
         2100
     }
+    fn wraps_around() -> bool {
+        false
+    }
+    // Explicitly listed years may go beyond the span covered by `*`.
+    fn validate_ordinal(ordinal: Ordinal) -> Result<Ordinal, Error> {
+        match ordinal {
+            i if i < Self::inclusive_min() => Err(ErrorKind::OutOfRange(format!(
+                "{} must be greater than or equal to {}. ('{}' \
+                 specified.)",
+                Self::name(),
+                Self::inclusive_min(),
+                i
+            ))
+            .into()),
+            i if i > MAX_YEAR => Err(ErrorKind::OutOfRange(format!(
+                "{} must be less than or equal to {}. ('{}' specified.)",
+                Self::name(),
+                MAX_YEAR,
+                i
+            ))
+            .into()),
+            i => Ok(i),
+        }
+    }
     fn ordinals(&self) -> &OrdinalSet {
         match &self.ordinals {
             Some(ordinal_set) => ordinal_set,
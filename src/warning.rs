@@ -0,0 +1,21 @@
+use std::fmt;
+
+/// A likely mistake in an otherwise valid expression, reported by
+/// [Schedule::from_str_with_warnings](../struct.Schedule.html#method.from_str_with_warnings).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Warning {
+    /// The seconds field includes every second, so the schedule fires 60 times per minute. This
+    /// usually means a five field expression was written without its leading seconds field.
+    EverySecond,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::EverySecond => write!(
+                f,
+                "Seconds is `*`, this fires 60 times per minute. Did you mean `0`?"
+            ),
+        }
+    }
+}
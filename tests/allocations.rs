@@ -0,0 +1,118 @@
+extern crate chrono;
+extern crate cron_schedule;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Counts the allocations made by threads that opted in, so the test harness running on other
+// threads doesn't interfere.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static LARGEST_ALLOCATION: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.with(Cell::get) {
+            ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+            LARGEST_ALLOCATION.fetch_max(layout.size(), Ordering::SeqCst);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if COUNTING.with(Cell::get) {
+            ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+            LARGEST_ALLOCATION.fetch_max(new_size, Ordering::SeqCst);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn count_allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    COUNTING.with(|counting| counting.set(true));
+    f();
+    COUNTING.with(|counting| counting.set(false));
+    ALLOCATIONS.load(Ordering::SeqCst) - before
+}
+
+// Returns the size of the largest single allocation `f` makes.
+fn largest_allocation<F: FnOnce()>(f: F) -> usize {
+    LARGEST_ALLOCATION.store(0, Ordering::SeqCst);
+    count_allocations(f);
+    LARGEST_ALLOCATION.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count_allocations, largest_allocation};
+    use chrono::*;
+    use cron_schedule::{ParseOptions, Schedule};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_includes_does_not_allocate() {
+        // Make sure the allocator is actually counting.
+        assert!(count_allocations(|| drop(std::hint::black_box(vec![0u8; 16]))) > 0);
+
+        let expressions = [
+            "* * * * * *",
+            "0 */15 9-17 * * Mon-Fri",
+            "0 0 0 29 2 * 2024-2040",
+            "@yearly",
+        ];
+        let date_times = [
+            Utc.with_ymd_and_hms(2021, 6, 15, 14, 30, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 2, 29, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2100, 12, 31, 23, 59, 59).unwrap(),
+        ];
+        let offset = FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap();
+        for expression in expressions {
+            let schedule = Schedule::from_str(expression).unwrap();
+            // The sets standing for `*` are built on first use, which may allocate once.
+            for date_time in date_times {
+                schedule.includes(date_time);
+            }
+
+            let allocations = count_allocations(|| {
+                for date_time in date_times {
+                    schedule.includes(date_time);
+                    schedule.includes(date_time.with_timezone(&offset));
+                }
+            });
+            assert_eq!(0, allocations, "{}", expression);
+        }
+    }
+
+    #[test]
+    fn test_huge_ranges_fail_without_expanding() {
+        let options = ParseOptions::new().random_seed(1);
+        for expression in [
+            "1-4000000000 * * * * *",
+            "* 4000000000-1 * * * *",
+            "1/1,4000000000 * * * * *",
+            "* * * * * * 1970-4000000000",
+            "R(0-4000000000) * * * * *",
+        ] {
+            let largest = largest_allocation(|| {
+                let error = Schedule::parse_with(expression, &options).unwrap_err();
+                assert!(error.is_out_of_range(), "{}", expression);
+            });
+            // A handful of strings and small sets, nowhere near one entry per ordinal.
+            assert!(largest < 4_096, "{}: {} bytes", expression, largest);
+        }
+    }
+}
@@ -4,7 +4,7 @@ extern crate cron_schedule;
 #[cfg(test)]
 mod tests {
     use chrono::*;
-    use cron_schedule::{Schedule, TimeUnitSpec};
+    use cron_schedule::{Schedule, TimeUnitSpec, Years, MAX_YEAR};
     use std::ops::Bound::{Excluded, Included};
     use std::str::FromStr;
 
@@ -62,6 +62,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_all_seconds_with_year() {
+        let schedule = Schedule::from_str("* 0 12 * * MON 2025").unwrap();
+        assert!(schedule.seconds().is_all());
+        assert_eq!(vec![2025], schedule.years().iter().collect::<Vec<_>>());
+        let starting_date = Utc
+            .with_ymd_and_hms(2024, 12, 31, 0, 0, 0)
+            .unwrap()
+            .timestamp_nanos_opt()
+            .unwrap() as u64;
+        assert_eq!(
+            Utc.with_ymd_and_hms(2025, 1, 6, 12, 0, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap() as u64,
+            schedule.after(&starting_date).next().unwrap()
+        );
+
+        let schedule = Schedule::from_str("* 0 12 * * MON").unwrap();
+        assert!(schedule.seconds().is_all());
+        assert!(schedule.years().is_all());
+    }
+
     #[test]
     fn test_parse_without_year() {
         let expression = "1 2 3 4 5 6";
@@ -463,4 +486,241 @@ mod tests {
         assert!(schedule.minutes().is_all());
         assert!(schedule.seconds().is_all());
     }
+
+    #[test]
+    fn test_years_from_range() {
+        let years = Years::from_range(2090, 2110).unwrap();
+        assert_eq!(21, years.count());
+        assert_eq!(Some(2090), years.iter().next());
+        assert_eq!(Some(2110), years.iter().next_back());
+        assert!(!years.is_all());
+
+        assert!(Years::from_range(2110, 2090).is_err());
+        assert!(Years::from_range(1969, 2000).is_err());
+        // Years this late can't be represented as u64 nanosecond timestamps.
+        assert!(Years::from_range(2090, 3000).is_err());
+        assert!(Years::from_range(2090, MAX_YEAR).is_ok());
+    }
+
+    #[test]
+    fn test_stepped_year_ranges() {
+        let schedule = Schedule::from_str("0 0 0 1 1 * 2020-2030/2").unwrap();
+        let years: Vec<u32> = schedule.years().iter().collect();
+        assert_eq!(vec![2020, 2022, 2024, 2026, 2028, 2030], years);
+        let fired: Vec<i32> = schedule
+            .after(&0)
+            .map(|nanos| Utc.timestamp_nanos(nanos as i64).year())
+            .collect();
+        assert_eq!(vec![2020, 2022, 2024, 2026, 2028, 2030], fired);
+
+        // A step from a single year runs to the end of the default span, and `*` starts at
+        // its beginning.
+        let schedule = Schedule::from_str("0 0 0 1 1 * 2090/5").unwrap();
+        let years: Vec<u32> = schedule.years().iter().collect();
+        assert_eq!(vec![2090, 2095, 2100], years);
+        let schedule = Schedule::from_str("0 0 0 1 1 * */50").unwrap();
+        let years: Vec<u32> = schedule.years().iter().collect();
+        assert_eq!(vec![1970, 2020, 2070], years);
+        assert!(Schedule::from_str("0 0 0 1 1 * 2020-2030/0").is_err());
+        assert!(Schedule::from_str("0 0 0 1 1 * 2020-3000/2").is_err());
+    }
+
+    #[test]
+    fn test_iterate_years_beyond_default_span() {
+        let schedule = Schedule::from_str("@yearly")
+            .unwrap()
+            .with_years(Years::from_range(2550, MAX_YEAR).unwrap());
+        assert_eq!("0 0 0 1 1 * 2550-2554", schedule.to_string());
+        let starting_date = Utc
+            .with_ymd_and_hms(2100, 6, 15, 0, 0, 0)
+            .unwrap()
+            .timestamp_nanos_opt()
+            .unwrap() as u64;
+        let years: Vec<i32> = schedule
+            .after(&starting_date)
+            .map(|nanos| {
+                let secs = (nanos / 1_000_000_000) as i64;
+                DateTime::from_timestamp(secs, 0).unwrap().year()
+            })
+            .collect();
+        assert_eq!(vec![2550, 2551, 2552, 2553, 2554], years);
+
+        // The source of a schedule using later years can be parsed again.
+        let reparsed = Schedule::from_str(&schedule.to_string()).unwrap();
+        assert!(reparsed.timeunitspec_eq(&schedule));
+    }
+
+    #[test]
+    fn test_first_year_after_reference_year() {
+        let schedule = Schedule::from_str("0 0 0 1 1 * 2018").unwrap();
+        let starting_date = Utc
+            .with_ymd_and_hms(2017, 6, 15, 14, 29, 36)
+            .unwrap()
+            .timestamp_nanos_opt()
+            .unwrap() as u64;
+        assert_eq!(
+            Utc.with_ymd_and_hms(2018, 1, 1, 0, 0, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap() as u64,
+            schedule.after(&starting_date).next().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_iteration_stops_at_timestamp_limit() {
+        // 2554-12-01 is past the last instant representable in u64 nanoseconds.
+        let schedule = Schedule::from_str("0 0 0 1 12 * 2554").unwrap();
+        assert_eq!(None, schedule.after(&0).next());
+        assert!(Schedule::from_str("0 0 0 1 12 * 2555").is_err());
+    }
+
+    #[test]
+    fn test_iteration_reaches_last_default_year() {
+        let nanos = |year, month, day, hour| {
+            Utc.with_ymd_and_hms(year, month, day, hour, 0, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap() as u64
+        };
+        // `*` spans the years up to 2100, which must itself be reached.
+        let schedule = Schedule::from_str("0 0 12 31 12 *").unwrap();
+        let fire_times: Vec<u64> = schedule.after(&nanos(2098, 6, 1, 0)).collect();
+        assert_eq!(
+            fire_times,
+            vec![
+                nanos(2098, 12, 31, 12),
+                nanos(2099, 12, 31, 12),
+                nanos(2100, 12, 31, 12)
+            ]
+        );
+
+        // Starting within the last year, and just before its last fire time.
+        assert_eq!(
+            Some(nanos(2100, 12, 31, 12)),
+            schedule.after(&nanos(2100, 1, 1, 0)).next()
+        );
+        assert_eq!(
+            Some(nanos(2100, 12, 31, 12)),
+            schedule.after(&(nanos(2100, 12, 31, 12) - 1)).next()
+        );
+        assert_eq!(None, schedule.after(&nanos(2100, 12, 31, 12)).next());
+
+        // The same holds for an explicit year list ending in the last year.
+        let schedule = Schedule::from_str("0 0 0 1 1 * 2099,2100").unwrap();
+        assert_eq!(
+            Some(nanos(2100, 1, 1, 0)),
+            schedule.after(&nanos(2099, 1, 1, 0)).next()
+        );
+    }
+
+    #[test]
+    fn test_day_31_skips_short_months() {
+        let nanos = |year, month, day| {
+            Utc.with_ymd_and_hms(year, month, day, 0, 0, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap() as u64
+        };
+        let schedule = Schedule::from_str("0 0 0 31 * *").unwrap();
+        let fire_times: Vec<u64> = schedule.after(&nanos(2023, 1, 1)).take(7).collect();
+        assert_eq!(
+            fire_times,
+            vec![
+                nanos(2023, 1, 31),
+                nanos(2023, 3, 31),
+                nanos(2023, 5, 31),
+                nanos(2023, 7, 31),
+                nanos(2023, 8, 31),
+                nanos(2023, 10, 31),
+                nanos(2023, 12, 31)
+            ]
+        );
+        // Starting within a short month moves on to the next long one.
+        assert_eq!(
+            Some(nanos(2023, 5, 31)),
+            schedule.after(&nanos(2023, 4, 30)).next()
+        );
+        assert_eq!(
+            Some(nanos(2023, 3, 31)),
+            schedule.prev_before(&nanos(2023, 5, 1))
+        );
+
+        // Only leap years have a February 29th, so this skips three years at a time.
+        let schedule = Schedule::from_str("0 0 0 29 2 *").unwrap();
+        let fire_times: Vec<u64> = schedule.after(&nanos(2023, 1, 1)).take(2).collect();
+        assert_eq!(fire_times, vec![nanos(2024, 2, 29), nanos(2028, 2, 29)]);
+        // And a day that no month has never fires.
+        let schedule = Schedule::from_str("0 0 0 30 2 *").unwrap();
+        assert_eq!(None, schedule.after(&nanos(2023, 1, 1)).next());
+    }
+
+    #[test]
+    fn test_as_period() {
+        let schedule = Schedule::from_str("5/10 */15 0-23/6 1,10,20 * ? 2018/2").unwrap();
+        assert_eq!(Some((5, 10)), schedule.seconds().as_period());
+        assert_eq!(Some((0, 15)), schedule.minutes().as_period());
+        assert_eq!(Some((0, 6)), schedule.hours().as_period());
+        assert_eq!(None, schedule.days_of_month().as_period());
+        assert_eq!(Some((1, 1)), schedule.months().as_period());
+        assert_eq!(Some((2018, 2)), schedule.years().as_period());
+
+        let schedule = Schedule::from_str("0,30 5-10/5 9 * Jan,Apr,Jul,Oct 2-6").unwrap();
+        assert_eq!(Some((0, 30)), schedule.seconds().as_period());
+        // Stops before the end of the hour.
+        assert_eq!(None, schedule.minutes().as_period());
+        assert_eq!(None, schedule.hours().as_period());
+        assert_eq!(Some((1, 3)), schedule.months().as_period());
+        assert_eq!(None, schedule.days_of_week().as_period());
+        let schedule = Schedule::from_str("0,10,15,30,40,50 0,20,40,50 22-6/2 * * *").unwrap();
+        assert_eq!(None, schedule.seconds().as_period());
+        assert_eq!(None, schedule.minutes().as_period());
+        assert_eq!(None, schedule.hours().as_period());
+    }
+
+    #[test]
+    fn test_hours_range_spanning_midnight() {
+        let nanos = |day, hour, minute| {
+            Utc.with_ymd_and_hms(2021, 6, day, hour, minute, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap() as u64
+        };
+        let schedule = Schedule::from_str("0 */15 22-6 * * *").unwrap();
+        assert_eq!(9, schedule.hours().count());
+
+        let fire_times: Vec<u64> = schedule.after(&nanos(15, 21, 50)).take(37).collect();
+        assert_eq!(nanos(15, 22, 0), fire_times[0]);
+        assert_eq!(nanos(16, 6, 45), fire_times[35]);
+        // The window is continuous across midnight, and the next one starts that evening.
+        assert!(fire_times[..36]
+            .windows(2)
+            .all(|pair| pair[1] - pair[0] == 15 * 60 * 1_000_000_000));
+        assert!(fire_times.contains(&nanos(15, 23, 45)));
+        assert!(fire_times.contains(&nanos(16, 0, 0)));
+        assert_eq!(nanos(16, 22, 0), fire_times[36]);
+    }
+
+    #[test]
+    fn test_ranges_wrapping_around() {
+        let schedule = Schedule::from_str("0 0 22-6/2 28-2 Nov-Feb Fri-Mon").unwrap();
+        assert_eq!(
+            vec![0, 2, 4, 6, 22],
+            schedule.hours().iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![1, 2, 28, 29, 30, 31],
+            schedule.days_of_month().iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![1, 2, 11, 12],
+            schedule.months().iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![1, 2, 6, 7],
+            schedule.days_of_week().iter().collect::<Vec<_>>()
+        );
+        // Years run on and never wrap.
+        assert!(Schedule::from_str("0 0 0 1 1 * 2030-2020").is_err());
+    }
 }